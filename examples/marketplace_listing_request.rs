@@ -51,7 +51,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let client = DiscogsClient::with_default_user_agent()
-        .auth(Auth::UserToken { token })
+        .auth(Auth::UserToken {
+            token: token.into(),
+        })
         .build()?;
 
     let created = client.marketplace().add_listing(&request).await?;
@@ -1,5 +1,6 @@
-use discogs_rs::DiscogsOAuthClient;
+use discogs_rs::{Auth, DiscogsOAuthClient, OAuthFlow};
 use std::env;
+use std::io::BufRead;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -7,8 +8,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // - DISCOGS_CONSUMER_KEY
     // - DISCOGS_CONSUMER_SECRET
     // Optional env vars:
-    // - DISCOGS_CALLBACK_URL (default: "https://example.com/callback")
-    // - DISCOGS_REQUEST_TOKEN, DISCOGS_REQUEST_TOKEN_SECRET, DISCOGS_OAUTH_VERIFIER
+    // - DISCOGS_OAUTH_OOB (set to paste a PIN instead of using the loopback callback)
+    // - DISCOGS_API_BASE (default: "https://api.discogs.com")
     let consumer_key = match env::var("DISCOGS_CONSUMER_KEY") {
         Ok(v) => v,
         Err(_) => {
@@ -24,36 +25,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let oauth = DiscogsOAuthClient::new(
+    let mut oauth = DiscogsOAuthClient::new(
         consumer_key,
         consumer_secret,
         format!("discogs-rs-oauth-example/{}", env!("CARGO_PKG_VERSION")),
     )?;
+    if let Ok(base_url) = env::var("DISCOGS_API_BASE") {
+        oauth = oauth.with_base_url(base_url);
+    }
+    let flow = OAuthFlow::new(oauth);
 
-    let request_token = env::var("DISCOGS_REQUEST_TOKEN").ok();
-    let request_token_secret = env::var("DISCOGS_REQUEST_TOKEN_SECRET").ok();
-    let verifier = env::var("DISCOGS_OAUTH_VERIFIER").ok();
+    let client = if env::var_os("DISCOGS_OAUTH_OOB").is_some() {
+        let pending = flow.start_out_of_band().await?;
+        println!("open {} and paste the PIN here:", pending.authorize_url());
+        let mut pin = String::new();
+        std::io::stdin().lock().read_line(&mut pin)?;
+        pending.finish(&pin).await?
+    } else {
+        let pending = flow.start_loopback().await?;
+        println!("open {} in a browser", pending.authorize_url());
+        println!("waiting for the redirect to {}", pending.callback_url());
+        pending.finish().await?
+    };
 
-    if let (Some(token), Some(token_secret), Some(verifier)) =
-        (request_token, request_token_secret, verifier)
+    let identity = client.get_identity().await?;
+    println!("authorized as {}", identity.data.username);
+    if let Auth::OAuth {
+        access_token,
+        access_token_secret,
+        ..
+    } = client.auth()
     {
-        let access = oauth.access_token(&token, &token_secret, &verifier).await?;
-        println!("access_token: {}", access.access_token);
-        println!("access_token_secret: {}", access.access_token_secret);
-        return Ok(());
+        println!("access_token: {access_token}");
+        println!("access_token_secret: {access_token_secret}");
     }
 
-    let callback_url =
-        env::var("DISCOGS_CALLBACK_URL").unwrap_or_else(|_| "https://example.com/callback".into());
-    let request = oauth.request_token(&callback_url).await?;
-
-    println!("authorize_url: {}", request.authorize_url);
-    println!("request_token: {}", request.token);
-    println!("request_token_secret: {}", request.token_secret);
-    println!("callback_confirmed: {}", request.callback_confirmed);
-    println!(
-        "after authorization, rerun with DISCOGS_REQUEST_TOKEN, DISCOGS_REQUEST_TOKEN_SECRET, and DISCOGS_OAUTH_VERIFIER"
-    );
-
     Ok(())
 }
@@ -28,7 +28,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = DiscogsClient::with_default_user_agent()
         .auth(Auth::Discogs {
             consumer_key,
-            consumer_secret,
+            consumer_secret: consumer_secret.into(),
         })
         .build()?;
 
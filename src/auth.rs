@@ -1,7 +1,13 @@
+use crate::oauth::{
+    Clock, NonceSource, OAuthCredentials, RandomNonce, SignatureMethod, SystemClock,
+};
+use crate::secret::Secret;
+use http::Method;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AuthLevel {
+    #[default]
     None,
     Consumer,
     User,
@@ -12,17 +18,18 @@ pub enum Auth {
     #[default]
     None,
     UserToken {
-        token: String,
+        token: Secret,
     },
     Discogs {
         consumer_key: String,
-        consumer_secret: String,
+        consumer_secret: Secret,
     },
     OAuth {
         consumer_key: String,
-        consumer_secret: String,
-        access_token: String,
-        access_token_secret: String,
+        consumer_secret: Secret,
+        access_token: Secret,
+        access_token_secret: Secret,
+        signature_method: SignatureMethod,
     },
 }
 
@@ -35,27 +42,64 @@ impl Auth {
         }
     }
 
-    pub fn authorization_header(&self) -> Option<String> {
+    /// OAuth credentials signed with HMAC-SHA1.
+    pub fn oauth(
+        consumer_key: impl Into<String>,
+        consumer_secret: impl Into<Secret>,
+        access_token: impl Into<Secret>,
+        access_token_secret: impl Into<Secret>,
+    ) -> Self {
+        Auth::OAuth {
+            consumer_key: consumer_key.into(),
+            consumer_secret: consumer_secret.into(),
+            access_token: access_token.into(),
+            access_token_secret: access_token_secret.into(),
+            signature_method: SignatureMethod::default(),
+        }
+    }
+
+    /// The `Authorization` header for a request. OAuth signatures cover the method and the full
+    /// URL, query included.
+    pub fn authorization_header(&self, method: &Method, url: &str) -> Option<String> {
+        self.authorization_header_with(method, url, &[], &SystemClock, &RandomNonce)
+    }
+
+    /// `form` holds the decoded fields of an `application/x-www-form-urlencoded` body, which OAuth
+    /// signatures cover too.
+    pub(crate) fn authorization_header_with(
+        &self,
+        method: &Method,
+        url: &str,
+        form: &[(&str, &str)],
+        clock: &dyn Clock,
+        nonce_source: &dyn NonceSource,
+    ) -> Option<String> {
         match self {
             Auth::None => None,
-            Auth::UserToken { token } => Some(format!("Discogs token={token}")),
+            Auth::UserToken { token } => Some(format!("Discogs token={}", token.expose())),
             Auth::Discogs {
                 consumer_key,
                 consumer_secret,
             } => Some(format!(
-                "Discogs key={consumer_key}, secret={consumer_secret}"
+                "Discogs key={consumer_key}, secret={}",
+                consumer_secret.expose()
             )),
             Auth::OAuth {
                 consumer_key,
                 consumer_secret,
                 access_token,
                 access_token_secret,
-            } => Some(crate::oauth::build_oauth_header(
-                consumer_key,
-                consumer_secret,
-                access_token,
-                access_token_secret,
-            )),
+                signature_method,
+            } => Some(
+                OAuthCredentials {
+                    consumer_key,
+                    consumer_secret: consumer_secret.expose(),
+                    token: Some(access_token.expose()),
+                    token_secret: access_token_secret.expose(),
+                    signature_method: *signature_method,
+                }
+                .authorization_header(method, url, &[], form, clock, nonce_source),
+            ),
         }
     }
 }
@@ -82,6 +126,7 @@ impl OutputFormat {
 #[cfg(test)]
 mod tests {
     use super::{Auth, AuthLevel, OutputFormat};
+    use http::Method;
 
     #[test]
     fn auth_levels_match_expected_capabilities() {
@@ -121,9 +166,27 @@ mod tests {
         let header = Auth::UserToken {
             token: "abc123".into(),
         }
-        .authorization_header()
+        .authorization_header(&Method::GET, "https://api.discogs.com/")
         .expect("header should exist");
 
         assert_eq!(header, "Discogs token=abc123");
     }
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let debug = format!(
+            "{:?}",
+            Auth::oauth(
+                "consumer-key",
+                "consumer-secret",
+                "access-token",
+                "token-secret"
+            )
+        );
+
+        assert!(debug.contains("consumer-key"));
+        assert!(!debug.contains("consumer-secret"));
+        assert!(!debug.contains("access-token"));
+        assert!(!debug.contains("token-secret"));
+    }
 }
@@ -0,0 +1,238 @@
+//! Bounded-concurrency fan-out over many keys, e.g. thousands of release ids.
+//!
+//! Every item is an ordinary client call, so it goes through the client's rate limiter, retry
+//! policy and middleware.
+
+use crate::error::{DiscogsError, Result};
+use futures_util::future::{Either, select};
+use futures_util::stream::{self, StreamExt};
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+
+/// Outcome of a batch, each list in input order.
+#[derive(Debug)]
+pub struct BatchReport<K, T> {
+    pub succeeded: Vec<(K, T)>,
+    pub failed: Vec<(K, DiscogsError)>,
+    /// Items that had not finished when the batch was cancelled.
+    pub skipped: Vec<K>,
+}
+
+impl<K, T> BatchReport<K, T> {
+    /// Whether every item succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+}
+
+/// Counts passed to the progress callback after each finished item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchProgress {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl BatchProgress {
+    pub fn completed(&self) -> usize {
+        self.succeeded + self.failed
+    }
+}
+
+/// Stops a running batch. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Items not yet finished are dropped and reported as skipped.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+type ProgressCallback = dyn Fn(&BatchProgress) + Send + Sync;
+
+/// Runs one call per key with at most `concurrency` in flight.
+#[derive(Clone)]
+pub struct BatchExecutor {
+    concurrency: usize,
+    cancel: Option<CancelToken>,
+    on_progress: Option<Arc<ProgressCallback>>,
+}
+
+impl fmt::Debug for BatchExecutor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchExecutor")
+            .field("concurrency", &self.concurrency)
+            .field("cancel", &self.cancel)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BatchExecutor {
+    /// A `concurrency` of zero is treated as one.
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            cancel: None,
+            on_progress: None,
+        }
+    }
+
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&BatchProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Calls `fetch` for every key and collects the outcomes.
+    pub async fn run<K, T, F, Fut>(
+        &self,
+        keys: impl IntoIterator<Item = K>,
+        mut fetch: F,
+    ) -> BatchReport<K, T>
+    where
+        K: Clone,
+        F: FnMut(K) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let mut progress = BatchProgress {
+            total: keys.len(),
+            succeeded: 0,
+            failed: 0,
+        };
+        let mut outcomes: Vec<Option<Result<T>>> = keys.iter().map(|_| None).collect();
+
+        let mut results = stream::iter(keys.iter().cloned().enumerate())
+            .map(|(index, key)| {
+                let call = fetch(key);
+                async move { (index, call.await) }
+            })
+            .buffer_unordered(self.concurrency);
+
+        let cancelled = async {
+            match &self.cancel {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        let mut cancelled = pin!(cancelled);
+
+        loop {
+            let next = match select(cancelled.as_mut(), results.next()).await {
+                Either::Left(_) => break,
+                Either::Right((next, _)) => next,
+            };
+            let Some((index, outcome)) = next else {
+                break;
+            };
+            match &outcome {
+                Ok(_) => progress.succeeded += 1,
+                Err(_) => progress.failed += 1,
+            }
+            outcomes[index] = Some(outcome);
+            if let Some(callback) = &self.on_progress {
+                callback(&progress);
+            }
+        }
+        drop(results);
+
+        let mut report = BatchReport {
+            succeeded: Vec::with_capacity(progress.succeeded),
+            failed: Vec::with_capacity(progress.failed),
+            skipped: Vec::new(),
+        };
+        for (key, outcome) in keys.into_iter().zip(outcomes) {
+            match outcome {
+                Some(Ok(value)) => report.succeeded.push((key, value)),
+                Some(Err(error)) => report.failed.push((key, error)),
+                None => report.skipped.push(key),
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BatchExecutor, CancelToken};
+    use crate::transport::TransportError;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn report_keeps_input_order_and_stops_on_cancel() {
+        let token = CancelToken::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let executor = BatchExecutor::new(2)
+            .cancel_token(token.clone())
+            .on_progress({
+                let seen = Arc::clone(&seen);
+                move |progress| {
+                    seen.lock()
+                        .expect("progress lock")
+                        .push(progress.completed());
+                    if progress.completed() == 3 {
+                        token.cancel();
+                    }
+                }
+            });
+
+        let report = executor
+            .run(0..10u64, |id| async move {
+                if id == 1 {
+                    Err(TransportError::timeout().into())
+                } else {
+                    Ok(id * 10)
+                }
+            })
+            .await;
+
+        assert_eq!(report.succeeded, vec![(0, 0), (2, 20)]);
+        assert_eq!(
+            report.failed.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(report.skipped, (3..10).collect::<Vec<_>>());
+        assert!(!report.is_complete());
+        assert_eq!(*seen.lock().expect("progress lock"), vec![1, 2, 3]);
+    }
+}
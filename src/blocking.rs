@@ -0,0 +1,1101 @@
+//! Synchronous facade over [`crate::DiscogsClient`], enabled by the `blocking` feature.
+//!
+//! Every call runs on a small runtime owned by the client, so auth checks, retries, rate
+//! limiting, middleware and errors behave exactly as in the async client. Do not call these
+//! methods from inside an async runtime; use the async client there instead.
+
+use crate::auth::AuthLevel;
+use crate::batch::BatchReport;
+use crate::client::{DiscogsClientBuilder, RequestOptions};
+use crate::error::Result;
+use crate::links::{Link, Resource};
+use crate::models::{
+    AboutResponse, AddCollectionReleaseResponse, AddFolderRequest, AddOrderMessageRequest,
+    ApiResponse, Artist, ArtistReleases, ArtistReleasesQuery, CollectionFields, CollectionFolder,
+    CollectionFolders, CollectionReleases, CollectionReleasesQuery, CollectionValue,
+    CreateListingRequest, CreateListingResponse, Currency, DiscogsList,
+    EditCollectionReleaseRequest, EditOrderRequest, EditProfileRequest, Identity, InventoryExport,
+    InventoryExports, Label, LabelReleases, Listing, MarketplaceReleaseStats, Master,
+    MasterVersions, MasterVersionsQuery, Order, OrderMessage, OrderMessagesResponse, OrdersQuery,
+    OrdersResponse, PaginationParams, Price, RateLimit, Release, ReleaseCommunityRating,
+    ReleaseRating, ReleaseStats, SearchQuery, SearchResults, UserContributions,
+    UserContributionsQuery, UserInventory, UserInventoryQuery, UserLists, UserProfile,
+    UserSubmissions, WantlistEntries, WantlistEntry, WantlistUpdateRequest,
+};
+use crate::pagination::{Page, PageStream, Paginated};
+use crate::retry::RetryPolicy;
+use crate::secret::Secret;
+use crate::transport::{TransportError, TransportErrorKind};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use http::Method;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+#[derive(Clone)]
+pub struct DiscogsClient {
+    inner: crate::DiscogsClient,
+    runtime: Arc<Runtime>,
+}
+
+impl DiscogsClient {
+    /// Wraps an async client, starting the runtime that drives it.
+    pub fn new(inner: crate::DiscogsClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("discogs-rs-blocking")
+            .enable_all()
+            .build()
+            .map_err(|error| TransportError::with_source(TransportErrorKind::Other, error))?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Same builder as the async client; finish it with
+    /// [`DiscogsClientBuilder::build_blocking`].
+    pub fn builder(user_agent: impl Into<String>) -> DiscogsClientBuilder {
+        crate::DiscogsClient::builder(user_agent)
+    }
+
+    pub fn with_default_user_agent() -> DiscogsClientBuilder {
+        crate::DiscogsClient::with_default_user_agent()
+    }
+
+    pub fn with_user_token(
+        user_agent: impl Into<String>,
+        token: impl Into<Secret>,
+    ) -> Result<DiscogsClient> {
+        Self::builder(user_agent).user_token(token).build_blocking()
+    }
+
+    pub fn with_default_user_agent_and_user_token(
+        token: impl Into<Secret>,
+    ) -> Result<DiscogsClient> {
+        Self::with_default_user_agent()
+            .user_token(token)
+            .build_blocking()
+    }
+
+    /// The async client behind this facade.
+    pub fn as_async(&self) -> &crate::DiscogsClient {
+        &self.inner
+    }
+
+    pub fn auth_level(&self) -> AuthLevel {
+        self.inner.auth_level()
+    }
+
+    /// See [`crate::DiscogsClient::with_options`].
+    pub fn with_options(&self, options: RequestOptions) -> DiscogsClient {
+        Self {
+            inner: self.inner.with_options(options),
+            runtime: Arc::clone(&self.runtime),
+        }
+    }
+
+    /// See [`crate::DiscogsClient::with_retry_policy`].
+    pub fn with_retry_policy(&self, policy: impl RetryPolicy + 'static) -> DiscogsClient {
+        Self {
+            inner: self.inner.with_retry_policy(policy),
+            runtime: Arc::clone(&self.runtime),
+        }
+    }
+
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.inner.rate_limit()
+    }
+
+    pub fn about(&self) -> Result<ApiResponse<AboutResponse>> {
+        self.block_on(self.inner.about())
+    }
+
+    pub fn get_identity(&self) -> Result<ApiResponse<Identity>> {
+        self.block_on(self.inner.get_identity())
+    }
+
+    pub fn database(&self) -> DatabaseApi<'_> {
+        DatabaseApi::new(self)
+    }
+
+    pub fn marketplace(&self) -> MarketplaceApi<'_> {
+        MarketplaceApi::new(self)
+    }
+
+    pub fn inventory(&self) -> InventoryApi<'_> {
+        InventoryApi::new(self)
+    }
+
+    pub fn user(&self) -> UserApi<'_> {
+        UserApi::new(self)
+    }
+
+    pub fn collection(&self) -> CollectionApi<'_> {
+        CollectionApi::new(self)
+    }
+
+    pub fn wantlist(&self) -> WantlistApi<'_> {
+        WantlistApi::new(self)
+    }
+
+    pub fn list(&self) -> ListApi<'_> {
+        ListApi::new(self)
+    }
+
+    pub fn follow<T: Resource + 'static>(&self, link: &Link<T>) -> Result<ApiResponse<T>> {
+        self.block_on(self.inner.follow(link))
+    }
+
+    pub fn next_page<R>(&self, page: &ApiResponse<R>) -> Result<Option<ApiResponse<R>>>
+    where
+        R: Paginated + DeserializeOwned + 'static,
+    {
+        self.block_on(self.inner.next_page(page))
+    }
+
+    /// See [`crate::DiscogsClient::raw`].
+    pub fn raw(&self, method: Method, path: impl Into<String>) -> RawRequest<'_> {
+        RawRequest {
+            inner: self.inner.raw(method, path),
+            client: self,
+        }
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+/// Blocking counterpart of [`DiscogsClient::raw`](crate::DiscogsClient::raw)'s request builder.
+pub struct RawRequest<'a> {
+    inner: crate::endpoints::raw::RawRequest<'a>,
+    client: &'a DiscogsClient,
+}
+
+impl<'a> RawRequest<'a> {
+    pub fn with_options(self, options: RequestOptions) -> Self {
+        self.map(|inner| inner.with_options(options))
+    }
+
+    pub fn param(self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.map(|inner| inner.param(name, value))
+    }
+
+    pub fn query<Q: Serialize + ?Sized>(self, query: &Q) -> Self {
+        self.map(|inner| inner.query(query))
+    }
+
+    pub fn json<B: Serialize + ?Sized>(self, body: &B) -> Self {
+        self.map(|inner| inner.json(body))
+    }
+
+    pub fn auth(self, required: AuthLevel) -> Self {
+        self.map(|inner| inner.auth(required))
+    }
+
+    pub fn endpoint(self, name: &'static str, template: &'static str) -> Self {
+        self.map(|inner| inner.endpoint(name, template))
+    }
+
+    pub fn send<T: DeserializeOwned + 'static>(self) -> Result<ApiResponse<T>> {
+        self.client.block_on(self.inner.send())
+    }
+
+    pub fn send_value(self) -> Result<ApiResponse<serde_json::Value>> {
+        self.client.block_on(self.inner.send_value())
+    }
+
+    pub fn send_empty(self) -> Result<ApiResponse<()>> {
+        self.client.block_on(self.inner.send_empty())
+    }
+
+    pub fn send_bytes(self) -> Result<ApiResponse<Bytes>> {
+        self.client.block_on(self.inner.send_bytes())
+    }
+
+    fn map(
+        self,
+        f: impl FnOnce(crate::endpoints::raw::RawRequest<'a>) -> crate::endpoints::raw::RawRequest<'a>,
+    ) -> Self {
+        Self {
+            inner: f(self.inner),
+            client: self.client,
+        }
+    }
+}
+
+/// Blocking counterpart of [`crate::Paginator`].
+pub struct Paginator<'a, R> {
+    inner: crate::Paginator<'a, R>,
+    runtime: &'a Runtime,
+}
+
+impl<'a, R> Paginator<'a, R>
+where
+    R: Paginated + Send + 'a,
+    R::Item: Send + 'a,
+{
+    fn new(inner: crate::Paginator<'a, R>, runtime: &'a Runtime) -> Self {
+        Self { inner, runtime }
+    }
+
+    pub fn prefetch(self, pages: usize) -> Self {
+        Self {
+            inner: self.inner.prefetch(pages),
+            runtime: self.runtime,
+        }
+    }
+
+    pub fn pages(self) -> Iter<'a, Page<R::Item>> {
+        Iter {
+            stream: self.inner.pages(),
+            runtime: self.runtime,
+        }
+    }
+
+    pub fn items(self) -> Iter<'a, R::Item> {
+        Iter {
+            stream: self.inner.items(),
+            runtime: self.runtime,
+        }
+    }
+}
+
+/// Iterator fetching pages on demand. Ends after yielding the first error.
+pub struct Iter<'a, T> {
+    stream: PageStream<'a, T>,
+    runtime: &'a Runtime,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+pub struct DatabaseApi<'a> {
+    client: &'a DiscogsClient,
+}
+
+impl<'a> DatabaseApi<'a> {
+    fn new(client: &'a DiscogsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn get_artist(&self, artist_id: u64) -> Result<ApiResponse<Artist>> {
+        self.client
+            .block_on(self.client.inner.database().get_artist(artist_id))
+    }
+
+    pub fn get_artists(
+        &self,
+        artist_ids: impl IntoIterator<Item = u64>,
+        concurrency: usize,
+    ) -> BatchReport<u64, ApiResponse<Artist>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_artists(artist_ids, concurrency),
+        )
+    }
+
+    pub fn get_artist_releases(
+        &self,
+        artist_id: u64,
+        query: Option<&ArtistReleasesQuery>,
+    ) -> Result<ApiResponse<ArtistReleases>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_artist_releases(artist_id, query),
+        )
+    }
+
+    pub fn get_artist_releases_paginated(
+        &self,
+        artist_id: u64,
+        query: Option<&ArtistReleasesQuery>,
+    ) -> Paginator<'a, ArtistReleases> {
+        Paginator::new(
+            self.client
+                .inner
+                .database()
+                .get_artist_releases_paginated(artist_id, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_release(
+        &self,
+        release_id: u64,
+        currency: Option<Currency>,
+    ) -> Result<ApiResponse<Release>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_release(release_id, currency),
+        )
+    }
+
+    pub fn get_releases(
+        &self,
+        release_ids: impl IntoIterator<Item = u64>,
+        concurrency: usize,
+    ) -> BatchReport<u64, ApiResponse<Release>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_releases(release_ids, concurrency),
+        )
+    }
+
+    pub fn get_release_rating(
+        &self,
+        release_id: u64,
+        username: &str,
+    ) -> Result<ApiResponse<ReleaseRating>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_release_rating(release_id, username),
+        )
+    }
+
+    pub fn set_release_rating(
+        &self,
+        release_id: u64,
+        username: &str,
+        rating: u8,
+    ) -> Result<ApiResponse<ReleaseRating>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .set_release_rating(release_id, username, rating),
+        )
+    }
+
+    pub fn delete_release_rating(
+        &self,
+        release_id: u64,
+        username: &str,
+    ) -> Result<ApiResponse<()>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .delete_release_rating(release_id, username),
+        )
+    }
+
+    pub fn get_release_community_rating(
+        &self,
+        release_id: u64,
+    ) -> Result<ApiResponse<ReleaseCommunityRating>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_release_community_rating(release_id),
+        )
+    }
+
+    pub fn get_release_stats(&self, release_id: u64) -> Result<ApiResponse<ReleaseStats>> {
+        self.client
+            .block_on(self.client.inner.database().get_release_stats(release_id))
+    }
+
+    pub fn get_master(&self, master_id: u64) -> Result<ApiResponse<Master>> {
+        self.client
+            .block_on(self.client.inner.database().get_master(master_id))
+    }
+
+    pub fn get_masters(
+        &self,
+        master_ids: impl IntoIterator<Item = u64>,
+        concurrency: usize,
+    ) -> BatchReport<u64, ApiResponse<Master>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_masters(master_ids, concurrency),
+        )
+    }
+
+    pub fn get_master_versions(
+        &self,
+        master_id: u64,
+        query: Option<&MasterVersionsQuery>,
+    ) -> Result<ApiResponse<MasterVersions>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_master_versions(master_id, query),
+        )
+    }
+
+    pub fn get_master_versions_paginated(
+        &self,
+        master_id: u64,
+        query: Option<&MasterVersionsQuery>,
+    ) -> Paginator<'a, MasterVersions> {
+        Paginator::new(
+            self.client
+                .inner
+                .database()
+                .get_master_versions_paginated(master_id, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_label(&self, label_id: u64) -> Result<ApiResponse<Label>> {
+        self.client
+            .block_on(self.client.inner.database().get_label(label_id))
+    }
+
+    pub fn get_label_releases(
+        &self,
+        label_id: u64,
+        query: Option<&PaginationParams>,
+    ) -> Result<ApiResponse<LabelReleases>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .database()
+                .get_label_releases(label_id, query),
+        )
+    }
+
+    pub fn get_label_releases_paginated(
+        &self,
+        label_id: u64,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, LabelReleases> {
+        Paginator::new(
+            self.client
+                .inner
+                .database()
+                .get_label_releases_paginated(label_id, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn search(&self, query: Option<&SearchQuery>) -> Result<ApiResponse<SearchResults>> {
+        self.client
+            .block_on(self.client.inner.database().search(query))
+    }
+
+    pub fn search_paginated(&self, query: Option<&SearchQuery>) -> Paginator<'a, SearchResults> {
+        Paginator::new(
+            self.client.inner.database().search_paginated(query),
+            &self.client.runtime,
+        )
+    }
+}
+
+pub struct MarketplaceApi<'a> {
+    client: &'a DiscogsClient,
+}
+
+impl<'a> MarketplaceApi<'a> {
+    fn new(client: &'a DiscogsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn get_listing(
+        &self,
+        listing_id: u64,
+        currency: Option<Currency>,
+    ) -> Result<ApiResponse<Listing>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .marketplace()
+                .get_listing(listing_id, currency),
+        )
+    }
+
+    pub fn add_listing(
+        &self,
+        request: &CreateListingRequest,
+    ) -> Result<ApiResponse<CreateListingResponse>> {
+        self.client
+            .block_on(self.client.inner.marketplace().add_listing(request))
+    }
+
+    pub fn edit_listing(
+        &self,
+        listing_id: u64,
+        request: &CreateListingRequest,
+    ) -> Result<ApiResponse<()>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .marketplace()
+                .edit_listing(listing_id, request),
+        )
+    }
+
+    pub fn delete_listing(&self, listing_id: u64) -> Result<ApiResponse<()>> {
+        self.client
+            .block_on(self.client.inner.marketplace().delete_listing(listing_id))
+    }
+
+    pub fn get_orders(&self, query: Option<&OrdersQuery>) -> Result<ApiResponse<OrdersResponse>> {
+        self.client
+            .block_on(self.client.inner.marketplace().get_orders(query))
+    }
+
+    pub fn get_orders_paginated(
+        &self,
+        query: Option<&OrdersQuery>,
+    ) -> Paginator<'a, OrdersResponse> {
+        Paginator::new(
+            self.client.inner.marketplace().get_orders_paginated(query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_order(&self, order_id: &str) -> Result<ApiResponse<Order>> {
+        self.client
+            .block_on(self.client.inner.marketplace().get_order(order_id))
+    }
+
+    pub fn edit_order(
+        &self,
+        order_id: &str,
+        request: &EditOrderRequest,
+    ) -> Result<ApiResponse<Order>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .marketplace()
+                .edit_order(order_id, request),
+        )
+    }
+
+    pub fn get_order_messages(
+        &self,
+        order_id: &str,
+        query: Option<&PaginationParams>,
+    ) -> Result<ApiResponse<OrderMessagesResponse>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .marketplace()
+                .get_order_messages(order_id, query),
+        )
+    }
+
+    pub fn get_order_messages_paginated(
+        &self,
+        order_id: &str,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, OrderMessagesResponse> {
+        Paginator::new(
+            self.client
+                .inner
+                .marketplace()
+                .get_order_messages_paginated(order_id, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn add_order_message(
+        &self,
+        order_id: &str,
+        request: &AddOrderMessageRequest,
+    ) -> Result<ApiResponse<OrderMessage>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .marketplace()
+                .add_order_message(order_id, request),
+        )
+    }
+
+    pub fn get_fee(&self, amount: f64, currency: Option<Currency>) -> Result<ApiResponse<Price>> {
+        self.client
+            .block_on(self.client.inner.marketplace().get_fee(amount, currency))
+    }
+
+    pub fn get_price_suggestions(
+        &self,
+        release_id: u64,
+    ) -> Result<ApiResponse<BTreeMap<String, Price>>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .marketplace()
+                .get_price_suggestions(release_id),
+        )
+    }
+
+    pub fn get_release_stats(
+        &self,
+        release_id: u64,
+        currency: Option<Currency>,
+    ) -> Result<ApiResponse<MarketplaceReleaseStats>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .marketplace()
+                .get_release_stats(release_id, currency),
+        )
+    }
+}
+
+pub struct InventoryApi<'a> {
+    client: &'a DiscogsClient,
+}
+
+impl<'a> InventoryApi<'a> {
+    fn new(client: &'a DiscogsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn export_inventory(&self) -> Result<ApiResponse<()>> {
+        self.client
+            .block_on(self.client.inner.inventory().export_inventory())
+    }
+
+    pub fn get_exports(
+        &self,
+        query: Option<&PaginationParams>,
+    ) -> Result<ApiResponse<InventoryExports>> {
+        self.client
+            .block_on(self.client.inner.inventory().get_exports(query))
+    }
+
+    pub fn get_exports_paginated(
+        &self,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, InventoryExports> {
+        Paginator::new(
+            self.client.inner.inventory().get_exports_paginated(query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_export(&self, export_id: u64) -> Result<ApiResponse<InventoryExport>> {
+        self.client
+            .block_on(self.client.inner.inventory().get_export(export_id))
+    }
+
+    pub fn download_export(&self, export_id: u64) -> Result<ApiResponse<Bytes>> {
+        self.client
+            .block_on(self.client.inner.inventory().download_export(export_id))
+    }
+}
+
+pub struct UserApi<'a> {
+    client: &'a DiscogsClient,
+}
+
+impl<'a> UserApi<'a> {
+    fn new(client: &'a DiscogsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn get_profile(&self, username: &str) -> Result<ApiResponse<UserProfile>> {
+        self.client
+            .block_on(self.client.inner.user().get_profile(username))
+    }
+
+    pub fn edit_profile(
+        &self,
+        username: &str,
+        request: &EditProfileRequest,
+    ) -> Result<ApiResponse<UserProfile>> {
+        self.client
+            .block_on(self.client.inner.user().edit_profile(username, request))
+    }
+
+    pub fn get_inventory(
+        &self,
+        username: &str,
+        query: Option<&UserInventoryQuery>,
+    ) -> Result<ApiResponse<UserInventory>> {
+        self.client
+            .block_on(self.client.inner.user().get_inventory(username, query))
+    }
+
+    pub fn get_inventory_paginated(
+        &self,
+        username: &str,
+        query: Option<&UserInventoryQuery>,
+    ) -> Paginator<'a, UserInventory> {
+        Paginator::new(
+            self.client
+                .inner
+                .user()
+                .get_inventory_paginated(username, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_identity(&self) -> Result<ApiResponse<Identity>> {
+        self.client
+            .block_on(self.client.inner.user().get_identity())
+    }
+
+    pub fn collection(&self) -> CollectionApi<'a> {
+        CollectionApi::new(self.client)
+    }
+
+    pub fn wantlist(&self) -> WantlistApi<'a> {
+        WantlistApi::new(self.client)
+    }
+
+    pub fn list(&self) -> ListApi<'a> {
+        ListApi::new(self.client)
+    }
+
+    pub fn get_contributions(
+        &self,
+        username: &str,
+        query: Option<&UserContributionsQuery>,
+    ) -> Result<ApiResponse<UserContributions>> {
+        self.client
+            .block_on(self.client.inner.user().get_contributions(username, query))
+    }
+
+    pub fn get_contributions_paginated(
+        &self,
+        username: &str,
+        query: Option<&UserContributionsQuery>,
+    ) -> Paginator<'a, UserContributions> {
+        Paginator::new(
+            self.client
+                .inner
+                .user()
+                .get_contributions_paginated(username, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_submissions(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Result<ApiResponse<UserSubmissions>> {
+        self.client
+            .block_on(self.client.inner.user().get_submissions(username, query))
+    }
+
+    pub fn get_submissions_paginated(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, UserSubmissions> {
+        Paginator::new(
+            self.client
+                .inner
+                .user()
+                .get_submissions_paginated(username, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_lists(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Result<ApiResponse<UserLists>> {
+        self.client
+            .block_on(self.client.inner.user().get_lists(username, query))
+    }
+
+    pub fn get_lists_paginated(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, UserLists> {
+        Paginator::new(
+            self.client
+                .inner
+                .user()
+                .get_lists_paginated(username, query),
+            &self.client.runtime,
+        )
+    }
+}
+
+pub struct CollectionApi<'a> {
+    client: &'a DiscogsClient,
+}
+
+impl<'a> CollectionApi<'a> {
+    fn new(client: &'a DiscogsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn get_folders(&self, username: &str) -> Result<ApiResponse<CollectionFolders>> {
+        self.client
+            .block_on(self.client.inner.collection().get_folders(username))
+    }
+
+    pub fn get_folder(
+        &self,
+        username: &str,
+        folder_id: u64,
+    ) -> Result<ApiResponse<CollectionFolder>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .collection()
+                .get_folder(username, folder_id),
+        )
+    }
+
+    pub fn add_folder(
+        &self,
+        username: &str,
+        request: &AddFolderRequest,
+    ) -> Result<ApiResponse<CollectionFolder>> {
+        self.client
+            .block_on(self.client.inner.collection().add_folder(username, request))
+    }
+
+    pub fn set_folder_name(
+        &self,
+        username: &str,
+        folder_id: u64,
+        request: &AddFolderRequest,
+    ) -> Result<ApiResponse<CollectionFolder>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .collection()
+                .set_folder_name(username, folder_id, request),
+        )
+    }
+
+    pub fn delete_folder(&self, username: &str, folder_id: u64) -> Result<ApiResponse<()>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .collection()
+                .delete_folder(username, folder_id),
+        )
+    }
+
+    pub fn get_releases(
+        &self,
+        username: &str,
+        folder_id: u64,
+        query: Option<&CollectionReleasesQuery>,
+    ) -> Result<ApiResponse<CollectionReleases>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .collection()
+                .get_releases(username, folder_id, query),
+        )
+    }
+
+    pub fn get_releases_paginated(
+        &self,
+        username: &str,
+        folder_id: u64,
+        query: Option<&CollectionReleasesQuery>,
+    ) -> Paginator<'a, CollectionReleases> {
+        Paginator::new(
+            self.client
+                .inner
+                .collection()
+                .get_releases_paginated(username, folder_id, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn get_release_instances(
+        &self,
+        username: &str,
+        release_id: u64,
+    ) -> Result<ApiResponse<CollectionReleases>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .collection()
+                .get_release_instances(username, release_id),
+        )
+    }
+
+    pub fn add_release(
+        &self,
+        username: &str,
+        release_id: u64,
+        folder_id: Option<u64>,
+    ) -> Result<ApiResponse<AddCollectionReleaseResponse>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .collection()
+                .add_release(username, release_id, folder_id),
+        )
+    }
+
+    pub fn edit_release(
+        &self,
+        username: &str,
+        folder_id: u64,
+        release_id: u64,
+        instance_id: u64,
+        request: &EditCollectionReleaseRequest,
+    ) -> Result<ApiResponse<()>> {
+        self.client
+            .block_on(self.client.inner.collection().edit_release(
+                username,
+                folder_id,
+                release_id,
+                instance_id,
+                request,
+            ))
+    }
+
+    pub fn remove_release(
+        &self,
+        username: &str,
+        folder_id: u64,
+        release_id: u64,
+        instance_id: u64,
+    ) -> Result<ApiResponse<()>> {
+        self.client
+            .block_on(self.client.inner.collection().remove_release(
+                username,
+                folder_id,
+                release_id,
+                instance_id,
+            ))
+    }
+
+    pub fn get_fields(&self, username: &str) -> Result<ApiResponse<CollectionFields>> {
+        self.client
+            .block_on(self.client.inner.collection().get_fields(username))
+    }
+
+    pub fn edit_instance_note(
+        &self,
+        username: &str,
+        folder_id: u64,
+        release_id: u64,
+        instance_id: u64,
+        field_id: u64,
+        value: &str,
+    ) -> Result<ApiResponse<()>> {
+        self.client
+            .block_on(self.client.inner.collection().edit_instance_note(
+                username,
+                folder_id,
+                release_id,
+                instance_id,
+                field_id,
+                value,
+            ))
+    }
+
+    pub fn get_value(&self, username: &str) -> Result<ApiResponse<CollectionValue>> {
+        self.client
+            .block_on(self.client.inner.collection().get_value(username))
+    }
+}
+
+pub struct WantlistApi<'a> {
+    client: &'a DiscogsClient,
+}
+
+impl<'a> WantlistApi<'a> {
+    fn new(client: &'a DiscogsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn get_releases(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Result<ApiResponse<WantlistEntries>> {
+        self.client
+            .block_on(self.client.inner.wantlist().get_releases(username, query))
+    }
+
+    pub fn get_releases_paginated(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, WantlistEntries> {
+        Paginator::new(
+            self.client
+                .inner
+                .wantlist()
+                .get_releases_paginated(username, query),
+            &self.client.runtime,
+        )
+    }
+
+    pub fn add_release(
+        &self,
+        username: &str,
+        release_id: u64,
+        request: &WantlistUpdateRequest,
+    ) -> Result<ApiResponse<WantlistEntry>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .wantlist()
+                .add_release(username, release_id, request),
+        )
+    }
+
+    pub fn edit_notes(
+        &self,
+        username: &str,
+        release_id: u64,
+        request: &WantlistUpdateRequest,
+    ) -> Result<ApiResponse<WantlistEntry>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .wantlist()
+                .edit_notes(username, release_id, request),
+        )
+    }
+
+    pub fn remove_release(&self, username: &str, release_id: u64) -> Result<ApiResponse<()>> {
+        self.client.block_on(
+            self.client
+                .inner
+                .wantlist()
+                .remove_release(username, release_id),
+        )
+    }
+}
+
+pub struct ListApi<'a> {
+    client: &'a DiscogsClient,
+}
+
+impl<'a> ListApi<'a> {
+    fn new(client: &'a DiscogsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn get_items(&self, list_id: u64) -> Result<ApiResponse<DiscogsList>> {
+        self.client
+            .block_on(self.client.inner.list().get_items(list_id))
+    }
+}
@@ -0,0 +1,730 @@
+//! Opt-in response cache for GET requests, installed with
+//! [`DiscogsClientBuilder::cache`](crate::DiscogsClientBuilder::cache).
+//!
+//! Reference: <https://www.discogs.com/developers/#page:home,header:home-rate-limiting>
+
+use crate::auth::Auth;
+use crate::error::Result;
+use crate::middleware::{Middleware, Next};
+use crate::transport::{HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use http::header::{
+    ACCEPT, CACHE_CONTROL, CONTENT_LENGTH, ETAG, HeaderMap, HeaderName, HeaderValue,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use http::{Extensions, Method, StatusCode};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Storage backend for cached responses.
+///
+/// Stores are best effort: a failed read is a miss and a failed write is ignored.
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+    fn put(&self, key: &str, entry: CachedResponse);
+    fn remove(&self, key: &str);
+    /// Removes every entry whose key satisfies `matches`.
+    fn remove_where(&self, matches: &dyn Fn(&str) -> bool);
+}
+
+impl<T> CacheStore for Arc<T>
+where
+    T: CacheStore + ?Sized,
+{
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        (**self).get(key)
+    }
+
+    fn put(&self, key: &str, entry: CachedResponse) {
+        (**self).put(key, entry)
+    }
+
+    fn remove(&self, key: &str) {
+        (**self).remove(key)
+    }
+
+    fn remove_where(&self, matches: &dyn Fn(&str) -> bool) {
+        (**self).remove_where(matches)
+    }
+}
+
+/// A stored `200 OK` response together with its freshness information.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    pub stored_at: SystemTime,
+    pub ttl: Duration,
+}
+
+impl CachedResponse {
+    fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.stored_at)
+            .unwrap_or(Duration::ZERO)
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.age() < self.ttl
+    }
+
+    fn has_validators(&self) -> bool {
+        self.headers.contains_key(ETAG) || self.headers.contains_key(LAST_MODIFIED)
+    }
+
+    /// A copy without `X-Discogs-Ratelimit*` headers. They describe the quota when the response
+    /// was fetched, so replaying them on a cache hit would report stale numbers.
+    fn without_rate_limit(mut self) -> Self {
+        let stale: Vec<HeaderName> = self
+            .headers
+            .keys()
+            .filter(|name| name.as_str().starts_with("x-discogs-ratelimit"))
+            .cloned()
+            .collect();
+        for name in stale {
+            self.headers.remove(name);
+        }
+        self
+    }
+
+    fn to_response(&self) -> HttpResponse {
+        HttpResponse {
+            status: StatusCode::OK,
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+            extensions: Extensions::new(),
+        }
+    }
+}
+
+/// Which paths are cached and for how long.
+///
+/// Only paths opted in through `endpoint_ttls` (or `default_ttl`) are cached. The default opts in
+/// the public database endpoints, so user, collection, wantlist and marketplace data always goes
+/// to the network.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// TTL for paths not matched by `endpoint_ttls`; `None` leaves them uncached.
+    pub default_ttl: Option<Duration>,
+    /// Path prefixes with their own TTL, e.g. `("/releases/", one day)`. The longest matching
+    /// prefix wins. A zero TTL always revalidates.
+    pub endpoint_ttls: Vec<(String, Duration)>,
+    /// How long past its TTL an entry may still be served while it is refreshed in the
+    /// background.
+    pub stale_while_revalidate: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl: None,
+            endpoint_ttls: vec![
+                ("/releases/".to_string(), Duration::from_secs(24 * 60 * 60)),
+                ("/masters/".to_string(), Duration::from_secs(24 * 60 * 60)),
+                ("/artists/".to_string(), Duration::from_secs(24 * 60 * 60)),
+                ("/labels/".to_string(), Duration::from_secs(24 * 60 * 60)),
+            ],
+            stale_while_revalidate: Duration::ZERO,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Opts paths starting with `prefix` into the cache with `ttl`.
+    pub fn endpoint(mut self, prefix: impl Into<String>, ttl: Duration) -> Self {
+        self.endpoint_ttls.push((prefix.into(), ttl));
+        self
+    }
+
+    fn ttl_for(&self, path: &str) -> Option<Duration> {
+        self.endpoint_ttls
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, ttl)| *ttl)
+            .or(self.default_ttl)
+    }
+}
+
+/// In-memory store evicting the least recently used entry once `capacity` is reached.
+#[derive(Debug)]
+pub struct MemoryCacheStore {
+    entries: Mutex<LruCache<String, CachedResponse>>,
+}
+
+impl MemoryCacheStore {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries
+            .lock()
+            .expect("cache lock poisoned")
+            .get(key)
+            .cloned()
+    }
+
+    fn put(&self, key: &str, entry: CachedResponse) {
+        self.entries
+            .lock()
+            .expect("cache lock poisoned")
+            .put(key.to_string(), entry);
+    }
+
+    fn remove(&self, key: &str) {
+        self.entries.lock().expect("cache lock poisoned").pop(key);
+    }
+
+    fn remove_where(&self, matches: &dyn Fn(&str) -> bool) {
+        let mut entries = self.entries.lock().expect("cache lock poisoned");
+        let keys: Vec<String> = entries
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| matches(key))
+            .cloned()
+            .collect();
+        for key in keys {
+            entries.pop(&key);
+        }
+    }
+}
+
+/// Store keeping one file per entry in a directory, so the cache survives restarts.
+///
+/// Each file holds a JSON metadata line followed by the raw body.
+#[derive(Debug, Clone)]
+pub struct DiskCacheStore {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntryMeta {
+    key: String,
+    headers: Vec<(String, String)>,
+    stored_at: u64,
+    ttl: u64,
+}
+
+impl DiskCacheStore {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.entry", sha256_hex(key.as_bytes())))
+    }
+
+    fn read(&self, key: &str) -> io::Result<Option<CachedResponse>> {
+        let mut reader = io::BufReader::new(fs::File::open(self.path(key))?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let meta: DiskEntryMeta = serde_json::from_str(&line)?;
+        // The file stores the full key; anything else under this name is a miss.
+        if meta.key != key {
+            return Ok(None);
+        }
+
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body)?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in meta.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        Ok(Some(CachedResponse {
+            headers,
+            body: Bytes::from(body),
+            stored_at: UNIX_EPOCH + Duration::from_secs(meta.stored_at),
+            ttl: Duration::from_secs(meta.ttl),
+        }))
+    }
+
+    fn read_key(path: &Path) -> io::Result<String> {
+        let mut line = String::new();
+        io::BufReader::new(fs::File::open(path)?).read_line(&mut line)?;
+        let meta: DiskEntryMeta = serde_json::from_str(&line)?;
+        Ok(meta.key)
+    }
+
+    fn write(&self, key: &str, entry: &CachedResponse) -> io::Result<()> {
+        let meta = DiskEntryMeta {
+            key: key.to_string(),
+            headers: entry
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            stored_at: entry
+                .stored_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs(),
+            ttl: entry.ttl.as_secs(),
+        };
+
+        // Write to a temporary file and rename so readers never see a torn entry. The name is
+        // unique per write, so concurrent puts of one key cannot interleave.
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let path = self.path(key);
+        let tmp = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written = (|| {
+            let mut file = fs::File::create(&tmp)?;
+            serde_json::to_writer(&mut file, &meta)?;
+            file.write_all(b"\n")?;
+            file.write_all(&entry.body)?;
+            file.sync_all()?;
+            fs::rename(&tmp, path)
+        })();
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        written
+    }
+}
+
+impl CacheStore for DiskCacheStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.read(key).ok().flatten()
+    }
+
+    fn put(&self, key: &str, entry: CachedResponse) {
+        let _ = self.write(key, &entry);
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path(key));
+    }
+
+    fn remove_where(&self, matches: &dyn Fn(&str) -> bool) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "entry")
+                && Self::read_key(&path).is_ok_and(|key| matches(&key))
+            {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Middleware serving GET requests from a [`CacheStore`].
+///
+/// It sits directly outside the retry layer, so cache hits never consume rate limit and
+/// revalidations are retried like any other request. A successful write (any other method)
+/// evicts the cached entries for its path, the paths below it and the collections above it.
+#[derive(Debug)]
+pub(crate) struct CacheLayer {
+    store: Arc<dyn CacheStore>,
+    config: CacheConfig,
+    revalidating: Arc<Mutex<HashSet<String>>>,
+}
+
+impl CacheLayer {
+    pub(crate) fn new(store: Arc<dyn CacheStore>, config: CacheConfig) -> Self {
+        Self {
+            store,
+            config,
+            revalidating: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    async fn fetch(
+        store: Arc<dyn CacheStore>,
+        key: String,
+        ttl: Duration,
+        mut request: HttpRequest,
+        cached: Option<CachedResponse>,
+        next: Next,
+    ) -> Result<HttpResponse> {
+        if let Some(cached) = &cached {
+            if let Some(etag) = cached.headers.get(ETAG) {
+                request.headers.insert(IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(modified) = cached.headers.get(LAST_MODIFIED) {
+                request.headers.insert(IF_MODIFIED_SINCE, modified.clone());
+            }
+        }
+
+        let mut response = next.clone().run(request.clone()).await?;
+        match (response.status, cached) {
+            (StatusCode::NOT_MODIFIED, Some(mut cached)) => {
+                // The 304 carries fresh rate-limit headers and possibly new validators.
+                for (name, value) in &response.headers {
+                    if name != CONTENT_LENGTH {
+                        cached.headers.insert(name, value.clone());
+                    }
+                }
+                cached.stored_at = SystemTime::now();
+                cached.ttl = ttl;
+                store.put(&key, cached.clone().without_rate_limit());
+                let mut refreshed = cached.to_response();
+                refreshed.extensions = response.extensions;
+                return Ok(refreshed);
+            }
+            (StatusCode::NOT_MODIFIED, None) => {
+                // Nothing to refresh, e.g. the caller sent its own validators; ask again for the
+                // full body.
+                request.headers.remove(IF_NONE_MATCH);
+                request.headers.remove(IF_MODIFIED_SINCE);
+                response = next.run(request).await?;
+            }
+            _ => {}
+        }
+
+        if response.status == StatusCode::OK && is_storable(&response.headers) {
+            let entry = CachedResponse {
+                headers: response.headers.clone(),
+                body: response.body.clone(),
+                stored_at: SystemTime::now(),
+                ttl,
+            };
+            store.put(&key, entry.without_rate_limit());
+        }
+        Ok(response)
+    }
+}
+
+impl Middleware for CacheLayer {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next,
+    ) -> BoxFuture<'a, Result<HttpResponse>> {
+        Box::pin(async move {
+            let path = url_path(&request.url);
+            if request.method != Method::GET {
+                let response = next.run(request).await?;
+                if response.status.is_success() {
+                    self.invalidate(&path);
+                }
+                return Ok(response);
+            }
+
+            let Some(ttl) = self.config.ttl_for(&path) else {
+                return next.run(request).await;
+            };
+            let key = cache_key(&request, next.client().auth());
+            let cached = self.store.get(&key);
+
+            if let Some(cached) = &cached {
+                if cached.is_fresh() {
+                    return Ok(cached.to_response());
+                }
+                if cached.age() < cached.ttl + self.config.stale_while_revalidate {
+                    self.revalidate_in_background(key, ttl, request, cached.clone(), next);
+                    return Ok(cached.to_response());
+                }
+            }
+
+            let cached = cached.filter(CachedResponse::has_validators);
+            Self::fetch(Arc::clone(&self.store), key, ttl, request, cached, next).await
+        })
+    }
+}
+
+impl CacheLayer {
+    fn invalidate(&self, written: &str) {
+        let written = written.trim_end_matches('/');
+        self.store.remove_where(&|key| {
+            // Keys start with `GET {url} `.
+            let Some(url) = key.split(' ').nth(1) else {
+                return false;
+            };
+            let path = url_path(url);
+            let path = path.trim_end_matches('/');
+            is_path_prefix(path, written) || is_path_prefix(written, path)
+        });
+    }
+
+    fn revalidate_in_background(
+        &self,
+        key: String,
+        ttl: Duration,
+        request: HttpRequest,
+        cached: CachedResponse,
+        next: Next,
+    ) {
+        if !self
+            .revalidating
+            .lock()
+            .expect("cache lock poisoned")
+            .insert(key.clone())
+        {
+            return;
+        }
+
+        let store = Arc::clone(&self.store);
+        let revalidating = Arc::clone(&self.revalidating);
+        let cached = Some(cached).filter(CachedResponse::has_validators);
+        tokio::spawn(async move {
+            let _ = Self::fetch(store, key.clone(), ttl, request, cached, next).await;
+            revalidating
+                .lock()
+                .expect("cache lock poisoned")
+                .remove(&key);
+        });
+    }
+}
+
+fn is_storable(headers: &HeaderMap) -> bool {
+    !headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.to_ascii_lowercase().contains("no-store"))
+}
+
+/// Method, URL, `Accept` (the [`OutputFormat`](crate::OutputFormat)) and a fingerprint of the
+/// credentials, so users never see each other's private data.
+pub(crate) fn cache_key(request: &HttpRequest, auth: &Auth) -> String {
+    let accept = request
+        .headers
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    format!(
+        "{} {} accept={} auth={}",
+        request.method,
+        request.url,
+        accept,
+        auth_fingerprint(auth)
+    )
+}
+
+// Keys end up in file contents, so credentials are hashed rather than embedded.
+fn auth_fingerprint(auth: &Auth) -> String {
+    let identity = match auth {
+        Auth::None => return "none".to_string(),
+        Auth::UserToken { token } => format!("token:{}", token.expose()),
+        Auth::Discogs { consumer_key, .. } => format!("consumer:{consumer_key}"),
+        Auth::OAuth {
+            consumer_key,
+            access_token,
+            ..
+        } => format!("oauth:{consumer_key}:{}", access_token.expose()),
+    };
+    sha256_hex(identity.as_bytes())
+}
+
+/// Whether `prefix` is `path` or one of its parent paths, segment-wise.
+fn is_path_prefix(prefix: &str, path: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn url_path(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_default()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CacheConfig, CacheStore, CachedResponse, DiskCacheStore, MemoryCacheStore,
+        auth_fingerprint, is_path_prefix,
+    };
+    use crate::auth::Auth;
+    use bytes::Bytes;
+    use http::header::{ETAG, HeaderMap, HeaderValue};
+    use std::time::{Duration, SystemTime};
+
+    fn entry(body: &'static str) -> CachedResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+        CachedResponse {
+            headers,
+            body: Bytes::from_static(body.as_bytes()),
+            stored_at: SystemTime::now(),
+            ttl: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn memory_store_evicts_least_recently_used() {
+        let store = MemoryCacheStore::new(2);
+        store.put("a", entry("a"));
+        store.put("b", entry("b"));
+        assert!(store.get("a").is_some());
+
+        store.put("c", entry("c"));
+        assert!(store.get("b").is_none());
+        assert!(store.get("a").is_some());
+        assert!(store.get("c").is_some());
+    }
+
+    #[test]
+    fn disk_store_round_trips_entries() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let store = DiskCacheStore::new(dir.path()).expect("disk store");
+
+        store.put("GET /releases/1", entry("{\"id\":1}"));
+        let cached = store.get("GET /releases/1").expect("cached entry");
+        assert_eq!(cached.body, Bytes::from_static(b"{\"id\":1}"));
+        assert_eq!(cached.headers.get(ETAG).expect("etag"), "\"v1\"");
+        assert_eq!(cached.ttl, Duration::from_secs(60));
+
+        store.remove("GET /releases/1");
+        assert!(store.get("GET /releases/1").is_none());
+    }
+
+    #[test]
+    fn concurrent_disk_writes_of_one_key_stay_intact() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let store = DiskCacheStore::new(dir.path()).expect("disk store");
+        let bodies = ["a".repeat(64 * 1024), "b".repeat(64 * 1024)];
+
+        std::thread::scope(|scope| {
+            for body in &bodies {
+                let store = store.clone();
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        let mut entry = entry("");
+                        entry.body = Bytes::from(body.clone());
+                        store.put("GET /releases/1", entry);
+                    }
+                });
+            }
+        });
+
+        let cached = store.get("GET /releases/1").expect("cached entry");
+        assert!(bodies.iter().any(|body| cached.body == body.as_bytes()));
+        let leftovers = std::fs::read_dir(dir.path())
+            .expect("read dir")
+            .filter(|entry| {
+                let path = entry.as_ref().expect("dir entry").path();
+                path.extension().is_some_and(|extension| extension == "tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn longest_endpoint_prefix_decides_ttl() {
+        let config = CacheConfig {
+            default_ttl: Some(Duration::from_secs(1)),
+            endpoint_ttls: vec![
+                ("/releases/".to_string(), Duration::from_secs(10)),
+                ("/releases/1/rating".to_string(), Duration::ZERO),
+            ],
+            stale_while_revalidate: Duration::ZERO,
+        };
+
+        assert_eq!(config.ttl_for("/releases/1"), Some(Duration::from_secs(10)));
+        assert_eq!(
+            config.ttl_for("/releases/1/rating/me"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(config.ttl_for("/users/me"), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn unlisted_paths_are_not_cached_by_default() {
+        let config = CacheConfig::default();
+        assert!(config.ttl_for("/releases/1").is_some());
+        assert_eq!(
+            config.ttl_for("/users/me/collection/folders/0/releases"),
+            None
+        );
+        assert_eq!(config.ttl_for("/marketplace/orders/1"), None);
+
+        let config = config.endpoint("/users/", Duration::from_secs(60));
+        assert_eq!(config.ttl_for("/users/me"), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn stores_remove_matching_entries() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let disk = DiskCacheStore::new(dir.path()).expect("disk store");
+        let memory = MemoryCacheStore::new(4);
+        for store in [&disk as &dyn CacheStore, &memory] {
+            store.put("GET /lists/1", entry("a"));
+            store.put("GET /lists/2", entry("b"));
+            store.remove_where(&|key| key.ends_with("/1"));
+            assert!(store.get("GET /lists/1").is_none());
+            assert!(store.get("GET /lists/2").is_some());
+        }
+    }
+
+    #[test]
+    fn auth_fingerprints_are_sha256_digests() {
+        let token = Auth::UserToken {
+            token: "secret-token".into(),
+        };
+        let fingerprint = auth_fingerprint(&token);
+        assert_eq!(fingerprint.len(), 64);
+        assert!(!fingerprint.contains("secret-token"));
+        assert_eq!(fingerprint, auth_fingerprint(&token));
+        assert_ne!(
+            fingerprint,
+            auth_fingerprint(&Auth::UserToken {
+                token: "other-token".into(),
+            })
+        );
+        assert_ne!(
+            auth_fingerprint(&Auth::oauth("key", "secret", "token-a", "token-secret")),
+            auth_fingerprint(&Auth::oauth("key", "secret", "token-b", "token-secret"))
+        );
+        assert_eq!(auth_fingerprint(&Auth::None), "none");
+    }
+
+    #[test]
+    fn disk_store_rejects_entries_stored_under_another_key() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let store = DiskCacheStore::new(dir.path()).expect("disk store");
+        store.put("GET /releases/1", entry("{\"id\":1}"));
+
+        // Simulate a file-name collision by moving the entry to another key's file.
+        std::fs::rename(store.path("GET /releases/1"), store.path("GET /releases/2"))
+            .expect("rename entry");
+        assert!(store.get("GET /releases/2").is_none());
+    }
+
+    #[test]
+    fn path_prefixes_match_whole_segments() {
+        assert!(is_path_prefix("/lists/1", "/lists/1"));
+        assert!(is_path_prefix("/lists", "/lists/1"));
+        assert!(!is_path_prefix("/lists/1", "/lists/12"));
+    }
+}
@@ -0,0 +1,582 @@
+//! Record/replay transport for deterministic integration tests, installed with
+//! [`DiscogsClientBuilder::cassette`](crate::DiscogsClientBuilder::cassette).
+//!
+//! A cassette is a JSON file of request/response pairs. Recording scrubs the `Authorization`
+//! header, cookies and credential-like or per-request OAuth query parameters (nonce, timestamp,
+//! signature) before anything is written.
+
+use crate::error::{Result, is_sensitive_query_key};
+use crate::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportErrorKind, default_transport,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use http::header::{AUTHORIZATION, SET_COOKIE};
+use http::{Extensions, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const SCRUBBED: &str = "REDACTED";
+
+/// Query parameters that change on every OAuth request. Scrubbing them keeps replays matching.
+const VOLATILE_QUERY_KEYS: [&str; 2] = ["oauth_nonce", "oauth_timestamp"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Sends requests over the real transport and keeps every exchange in memory. The cassette
+    /// file is replaced with them once the client, and every clone of it, is dropped.
+    Record,
+    /// Answers requests from the cassette without touching the network.
+    Replay,
+}
+
+/// Which parts of a request must equal a recorded one for it to be replayed.
+#[derive(Debug, Clone)]
+pub struct MatchRules {
+    pub method: bool,
+    /// Scheme, host and port.
+    pub host: bool,
+    pub path: bool,
+    /// Compared as a sorted list of pairs, so parameter order does not matter.
+    pub query: bool,
+    /// JSON bodies are compared as values, so key order does not matter.
+    pub body: bool,
+    /// Query parameters left out of the comparison.
+    pub ignored_query_params: Vec<String>,
+    /// Request headers that must match as well, e.g. `accept` for different output formats.
+    pub headers: Vec<String>,
+    /// Serve each interaction at most once, in recorded order. When off, an interaction can
+    /// answer any number of identical requests.
+    pub once: bool,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            method: true,
+            host: true,
+            path: true,
+            query: true,
+            body: true,
+            ignored_query_params: Vec::new(),
+            headers: Vec::new(),
+            once: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CassetteConfig {
+    path: PathBuf,
+    mode: CassetteMode,
+    match_rules: MatchRules,
+}
+
+impl CassetteConfig {
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(path, CassetteMode::Record)
+    }
+
+    pub fn replay(path: impl Into<PathBuf>) -> Self {
+        Self::new(path, CassetteMode::Replay)
+    }
+
+    pub fn new(path: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+        Self {
+            path: path.into(),
+            mode,
+            match_rules: MatchRules::default(),
+        }
+    }
+
+    /// Only used when replaying.
+    pub fn match_rules(mut self, match_rules: MatchRules) -> Self {
+        self.match_rules = match_rules;
+        self
+    }
+
+    /// The transport the client sends through. Recording wraps `inner`, or the default
+    /// transport when there is none; replaying never needs one.
+    pub(crate) fn into_transport(
+        self,
+        inner: Option<Arc<dyn HttpTransport>>,
+    ) -> Result<Arc<dyn HttpTransport>> {
+        let (mode, cassette) = match self.mode {
+            CassetteMode::Record => {
+                let inner = match inner {
+                    Some(inner) => inner,
+                    None => default_transport()?,
+                };
+                (Mode::Record { inner }, Cassette::default())
+            }
+            CassetteMode::Replay => {
+                let cassette = Cassette::load(&self.path).map_err(|error| {
+                    TransportError::new(
+                        TransportErrorKind::InvalidRequest,
+                        format!("cannot read cassette {}: {error}", self.path.display()),
+                    )
+                })?;
+                (Mode::Replay, cassette)
+            }
+        };
+        let used = vec![false; cassette.interactions.len()];
+        Ok(Arc::new(CassetteTransport {
+            path: self.path,
+            match_rules: self.match_rules,
+            mode,
+            tape: Mutex::new(Tape { cassette, used }),
+        }))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    fn load(path: &Path) -> io::Result<Self> {
+        let raw = fs::read(path)?;
+        Ok(serde_json::from_slice(&raw)?)
+    }
+
+    /// Writes to a temporary file and renames it, so an interrupted run never leaves a torn
+    /// cassette behind.
+    fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(tmp, path)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// Scheme, host, port and path, without the query.
+    url: String,
+    #[serde(default)]
+    query: Vec<(String, String)>,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default, flatten)]
+    body: Option<RecordedBody>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    body: RecordedBody,
+}
+
+/// UTF-8 bodies are stored as text so cassettes stay readable and editable; anything else as
+/// base64.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedBody {
+    body: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    base64: bool,
+}
+
+impl RecordedBody {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self {
+                body: text.to_string(),
+                base64: false,
+            },
+            Err(_) => Self {
+                body: STANDARD.encode(bytes),
+                base64: true,
+            },
+        }
+    }
+
+    fn bytes(&self) -> Bytes {
+        if self.base64 {
+            Bytes::from(STANDARD.decode(&self.body).unwrap_or_default())
+        } else {
+            Bytes::from(self.body.clone())
+        }
+    }
+
+    fn same_as(&self, other: &Self) -> bool {
+        if self.base64 || other.base64 {
+            return self == other;
+        }
+        match (
+            serde_json::from_str::<serde_json::Value>(&self.body),
+            serde_json::from_str::<serde_json::Value>(&other.body),
+        ) {
+            (Ok(left), Ok(right)) => left == right,
+            _ => self.body == other.body,
+        }
+    }
+}
+
+impl RecordedRequest {
+    /// The request as it is written to, and compared with, the cassette.
+    fn from_request(request: &HttpRequest) -> Self {
+        let (url, query) = match url::Url::parse(&request.url) {
+            Ok(mut parsed) => {
+                let mut query: Vec<(String, String)> = parsed
+                    .query_pairs()
+                    .map(|(name, value)| {
+                        let value = if is_sensitive_query_key(&name)
+                            || VOLATILE_QUERY_KEYS.contains(&name.as_ref())
+                        {
+                            SCRUBBED.to_string()
+                        } else {
+                            value.into_owned()
+                        };
+                        (name.into_owned(), value)
+                    })
+                    .collect();
+                query.sort();
+                parsed.set_query(None);
+                parsed.set_fragment(None);
+                (parsed.to_string(), query)
+            }
+            Err(_) => (request.url.clone(), Vec::new()),
+        };
+        Self {
+            method: request.method.to_string(),
+            url,
+            query,
+            headers: header_pairs(&request.headers, &[AUTHORIZATION]),
+            body: request.body.as_deref().map(RecordedBody::new),
+        }
+    }
+
+    fn matches(&self, recorded: &Self, rules: &MatchRules) -> bool {
+        let (host, path) = split_url(&self.url);
+        let (recorded_host, recorded_path) = split_url(&recorded.url);
+        let query = |pairs: &[(String, String)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .filter(|(name, _)| !rules.ignored_query_params.contains(name))
+                .cloned()
+                .collect()
+        };
+        let header = |headers: &[(String, String)], name: &str| -> Vec<String> {
+            headers
+                .iter()
+                .filter(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+                .collect()
+        };
+        let body = match (&self.body, &recorded.body) {
+            (Some(body), Some(recorded)) => body.same_as(recorded),
+            (None, None) => true,
+            _ => false,
+        };
+
+        (!rules.method || self.method == recorded.method)
+            && (!rules.host || host == recorded_host)
+            && (!rules.path || path == recorded_path)
+            && (!rules.query || query(&self.query) == query(&recorded.query))
+            && (!rules.body || body)
+            && rules
+                .headers
+                .iter()
+                .all(|name| header(&self.headers, name) == header(&recorded.headers, name))
+    }
+}
+
+/// Splits `https://host/path` into `https://host` and `/path`.
+fn split_url(url: &str) -> (&str, &str) {
+    let path_start = url
+        .find("://")
+        .and_then(|scheme_end| {
+            let rest = scheme_end + 3;
+            url[rest..].find('/').map(|slash| rest + slash)
+        })
+        .unwrap_or(url.len());
+    url.split_at(path_start)
+}
+
+/// Headers as name/value pairs, with the values of `scrubbed` headers replaced.
+fn header_pairs(headers: &HeaderMap, scrubbed: &[HeaderName]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = if scrubbed.contains(name) {
+                SCRUBBED.to_string()
+            } else {
+                value.to_str().ok()?.to_string()
+            };
+            Some((name.as_str().to_string(), value))
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record { inner: Arc<dyn HttpTransport> },
+    Replay,
+}
+
+/// The cassette and which of its interactions have been replayed.
+#[derive(Debug)]
+struct Tape {
+    cassette: Cassette,
+    used: Vec<bool>,
+}
+
+#[derive(Debug)]
+struct CassetteTransport {
+    path: PathBuf,
+    match_rules: MatchRules,
+    mode: Mode,
+    tape: Mutex<Tape>,
+}
+
+impl CassetteTransport {
+    async fn record(
+        &self,
+        inner: &Arc<dyn HttpTransport>,
+        request: HttpRequest,
+    ) -> std::result::Result<HttpResponse, TransportError> {
+        let recorded = RecordedRequest::from_request(&request);
+        let response = inner.send(request).await?;
+        let interaction = Interaction {
+            request: recorded,
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                headers: header_pairs(&response.headers, &[SET_COOKIE]),
+                body: RecordedBody::new(&response.body),
+            },
+        };
+
+        self.tape
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .cassette
+            .interactions
+            .push(interaction);
+        Ok(response)
+    }
+
+    fn replay(&self, request: &HttpRequest) -> std::result::Result<HttpResponse, TransportError> {
+        let live = RecordedRequest::from_request(request);
+        let mut tape = self
+            .tape
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Tape { cassette, used } = &mut *tape;
+
+        let found = cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .find(|(index, interaction)| {
+                !(self.match_rules.once && used[*index])
+                    && live.matches(&interaction.request, &self.match_rules)
+            });
+        let Some((index, interaction)) = found else {
+            let remaining = used.iter().filter(|used| !**used).count();
+            return Err(TransportError::new(
+                TransportErrorKind::InvalidRequest,
+                format!(
+                    "no interaction in cassette {} matches {} {}{} ({remaining} of {} unused)",
+                    self.path.display(),
+                    live.method,
+                    live.url,
+                    query_suffix(&live.query),
+                    used.len(),
+                ),
+            ));
+        };
+        used[index] = true;
+
+        let recorded = &interaction.response;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &recorded.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        Ok(HttpResponse {
+            status: StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::OK),
+            headers,
+            body: recorded.body.bytes(),
+            extensions: Extensions::new(),
+        })
+    }
+}
+
+fn query_suffix(query: &[(String, String)]) -> String {
+    if query.is_empty() {
+        return String::new();
+    }
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(query)
+        .finish();
+    format!("?{query}")
+}
+
+/// Recording writes the cassette once, here, instead of rewriting it on every request.
+impl Drop for CassetteTransport {
+    fn drop(&mut self) {
+        if !matches!(self.mode, Mode::Record { .. }) {
+            return;
+        }
+        let tape = self
+            .tape
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Err(error) = tape.cassette.save(&self.path) {
+            // A lost recording should fail the test that made it, unless it is failing already.
+            if !std::thread::panicking() {
+                panic!("cannot write cassette {}: {error}", self.path.display());
+            }
+        }
+    }
+}
+
+impl HttpTransport for CassetteTransport {
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> BoxFuture<'_, std::result::Result<HttpResponse, TransportError>> {
+        Box::pin(async move {
+            match &self.mode {
+                Mode::Record { inner } => self.record(inner, request).await,
+                Mode::Replay => self.replay(&request),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CassetteConfig, MatchRules};
+    use crate::transport::{HttpRequest, HttpResponse, HttpTransport, TransportError};
+    use bytes::Bytes;
+    use futures_util::future::BoxFuture;
+    use http::header::AUTHORIZATION;
+    use http::{Extensions, HeaderMap, HeaderValue, Method, StatusCode};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Echo;
+
+    impl HttpTransport for Echo {
+        fn send(
+            &self,
+            request: HttpRequest,
+        ) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+            Box::pin(async move {
+                let mut headers = HeaderMap::new();
+                headers.insert("x-discogs-ratelimit-remaining", HeaderValue::from(59));
+                Ok(HttpResponse {
+                    status: StatusCode::CREATED,
+                    headers,
+                    body: Bytes::from(format!("{{\"method\":\"{}\"}}", request.method)),
+                    extensions: Extensions::new(),
+                })
+            })
+        }
+    }
+
+    fn request(url: &str, body: &str) -> HttpRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_static("OAuth oauth_token=\"secret-token\""),
+        );
+        HttpRequest {
+            method: Method::POST,
+            url: url.to_string(),
+            headers,
+            body: Some(Bytes::from(body.to_string())),
+            timeout: None,
+            extensions: Extensions::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn records_scrubbed_and_replays_each_interaction_once() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("cassettes/orders.json");
+
+        let recorder = CassetteConfig::record(&path)
+            .into_transport(Some(Arc::new(Echo)))
+            .expect("recorder");
+        recorder
+            .send(request(
+                "https://api.discogs.com/orders?b=2&oauth_nonce=n1&a=1",
+                r#"{"status":"Shipped","shipping":5}"#,
+            ))
+            .await
+            .expect("recorded response");
+        assert!(!path.exists(), "nothing is written while recording");
+        drop(recorder);
+
+        let written = std::fs::read_to_string(&path).expect("cassette file");
+        assert!(!written.contains("secret-token"));
+        assert!(!written.contains("n1"));
+        assert!(written.contains("x-discogs-ratelimit-remaining"));
+
+        let player = CassetteConfig::replay(&path)
+            .into_transport(None)
+            .expect("player");
+        let replayed = player
+            .send(request(
+                "https://api.discogs.com/orders?a=1&oauth_nonce=n2&b=2",
+                r#"{"shipping":5,"status":"Shipped"}"#,
+            ))
+            .await
+            .expect("replayed response");
+        assert_eq!(replayed.status, StatusCode::CREATED);
+        assert_eq!(replayed.headers["x-discogs-ratelimit-remaining"], "59");
+        assert_eq!(replayed.body, r#"{"method":"POST"}"#);
+
+        let error = player
+            .send(request("https://api.discogs.com/orders?a=1&b=2", "{}"))
+            .await
+            .expect_err("the only interaction is used up");
+        assert!(
+            error.to_string().starts_with("no interaction in cassette"),
+            "{error}"
+        );
+
+        let lenient = CassetteConfig::replay(&path)
+            .match_rules(MatchRules {
+                body: false,
+                ignored_query_params: vec!["b".to_string()],
+                once: false,
+                ..MatchRules::default()
+            })
+            .into_transport(None)
+            .expect("player");
+        for body in ["{}", "[]"] {
+            lenient
+                .send(request(
+                    "https://api.discogs.com/orders?a=1&b=3&oauth_nonce=n3",
+                    body,
+                ))
+                .await
+                .expect("matched without body and b");
+        }
+    }
+}
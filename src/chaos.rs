@@ -0,0 +1,270 @@
+//! Fault injection for resilience tests, installed with
+//! [`DiscogsClientBuilder::chaos`](crate::DiscogsClientBuilder::chaos).
+//!
+//! Faults are injected between the client and its transport, i.e. below the retry layer, so
+//! every attempt of a call is subject to them and [`RetryConfig`](crate::RetryConfig) sees them
+//! like real failures.
+
+use crate::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportErrorKind,
+};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use http::header::{CONTENT_TYPE, RETRY_AFTER};
+use http::{Extensions, HeaderMap, HeaderValue, StatusCode};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// One injected failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Waits before sending the request, which then goes through unchanged.
+    Latency(Duration),
+    /// Answers `429 Too Many Requests` without sending the request.
+    RateLimited { retry_after: Option<Duration> },
+    /// Answers with this status, e.g. `503`, without sending the request.
+    ServerError(StatusCode),
+    /// Sends the request but cuts the response body in half.
+    TruncatedBody,
+    /// Sends the request but replaces the response body with invalid JSON.
+    MalformedJson,
+    /// Fails with a [`TransportErrorKind::Connect`] error without sending the request.
+    ConnectionReset,
+    /// Never answers, so the client's timeout fires. The request is not sent.
+    Timeout,
+}
+
+/// Which faults to inject: a scripted sequence, seeded probabilities, or both, with the script
+/// played first.
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    script: VecDeque<Option<Fault>>,
+    rules: Vec<ChaosRule>,
+    seed: u64,
+}
+
+#[derive(Debug, Clone)]
+struct ChaosRule {
+    fault: Fault,
+    probability: f64,
+    burst: u32,
+}
+
+impl ChaosConfig {
+    /// Injects nothing until faults are added.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            script: VecDeque::new(),
+            rules: Vec::new(),
+            seed,
+        }
+    }
+
+    /// Faults for the next requests in order; `None` lets a request through untouched. Once the
+    /// script is used up, the probabilistic faults apply.
+    pub fn scripted(faults: impl IntoIterator<Item = Option<Fault>>) -> Self {
+        Self {
+            script: faults.into_iter().collect(),
+            ..Self::seeded(0)
+        }
+    }
+
+    /// Injects `fault` into each request with `probability` (0.0 to 1.0).
+    pub fn inject(self, fault: Fault, probability: f64) -> Self {
+        self.inject_burst(fault, probability, 1)
+    }
+
+    /// Like [`inject`](Self::inject), but once triggered, the fault hits `length` requests in a
+    /// row, e.g. a 429 storm or a 5xx outage.
+    pub fn inject_burst(mut self, fault: Fault, probability: f64, length: u32) -> Self {
+        self.rules.push(ChaosRule {
+            fault,
+            probability: probability.clamp(0.0, 1.0),
+            burst: length.max(1),
+        });
+        self
+    }
+
+    /// The same seed yields the same faults for the same sequence of requests.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub(crate) fn into_transport(self, inner: Arc<dyn HttpTransport>) -> Arc<dyn HttpTransport> {
+        Arc::new(self.wrap(inner))
+    }
+
+    fn wrap(self, inner: Arc<dyn HttpTransport>) -> ChaosTransport {
+        ChaosTransport {
+            inner,
+            state: Mutex::new(ChaosState {
+                rng: StdRng::seed_from_u64(self.seed),
+                pending: self.script,
+            }),
+            rules: self.rules,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ChaosState {
+    rng: StdRng,
+    /// The rest of the script, followed by the rest of a running burst.
+    pending: VecDeque<Option<Fault>>,
+}
+
+#[derive(Debug)]
+struct ChaosTransport {
+    inner: Arc<dyn HttpTransport>,
+    state: Mutex<ChaosState>,
+    rules: Vec<ChaosRule>,
+}
+
+impl ChaosTransport {
+    /// The fault for the next request. Rules are checked in the order they were added and the
+    /// first one that fires wins.
+    fn next_fault(&self) -> Option<Fault> {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(fault) = state.pending.pop_front() {
+            return fault;
+        }
+        for rule in &self.rules {
+            if state.rng.random_bool(rule.probability) {
+                for _ in 1..rule.burst {
+                    state.pending.push_back(Some(rule.fault.clone()));
+                }
+                return Some(rule.fault.clone());
+            }
+        }
+        None
+    }
+}
+
+impl HttpTransport for ChaosTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        Box::pin(async move {
+            let fault = match self.next_fault() {
+                Some(fault) => fault,
+                None => return self.inner.send(request).await,
+            };
+            match fault {
+                Fault::Latency(delay) => {
+                    tokio::time::sleep(delay).await;
+                    self.inner.send(request).await
+                }
+                Fault::RateLimited { retry_after } => {
+                    let mut response = synthetic(
+                        StatusCode::TOO_MANY_REQUESTS,
+                        r#"{"message": "You are making requests too quickly."}"#,
+                    );
+                    if let Some(retry_after) = retry_after {
+                        response
+                            .headers
+                            .insert(RETRY_AFTER, HeaderValue::from(retry_after.as_secs()));
+                    }
+                    Ok(response)
+                }
+                Fault::ServerError(status) => Ok(synthetic(
+                    status,
+                    r#"{"message": "An internal server error occurred."}"#,
+                )),
+                Fault::TruncatedBody => {
+                    let mut response = self.inner.send(request).await?;
+                    response.body = response.body.slice(..response.body.len() / 2);
+                    Ok(response)
+                }
+                Fault::MalformedJson => {
+                    let mut response = self.inner.send(request).await?;
+                    response.body = Bytes::from_static(b"{\"message\": <html>");
+                    Ok(response)
+                }
+                Fault::ConnectionReset => Err(TransportError::new(
+                    TransportErrorKind::Connect,
+                    "connection reset by peer (injected)",
+                )),
+                Fault::Timeout => {
+                    match request.timeout {
+                        Some(timeout) => tokio::time::sleep(timeout).await,
+                        None => std::future::pending().await,
+                    }
+                    Err(TransportError::timeout())
+                }
+            }
+        })
+    }
+}
+
+fn synthetic(status: StatusCode, body: &'static str) -> HttpResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    HttpResponse {
+        status,
+        headers,
+        body: Bytes::from_static(body.as_bytes()),
+        extensions: Extensions::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChaosConfig, Fault};
+    use crate::transport::{HttpRequest, HttpResponse, HttpTransport, TransportError};
+    use futures_util::future::BoxFuture;
+    use http::StatusCode;
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Unused;
+
+    impl HttpTransport for Unused {
+        fn send(&self, _: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+            unreachable!("faults are only drawn here")
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_faults_and_bursts_run_in_a_row() {
+        let bad_gateway = Some(Fault::ServerError(StatusCode::BAD_GATEWAY));
+        let faults = |seed| {
+            let chaos = ChaosConfig::seeded(seed)
+                .inject_burst(Fault::ServerError(StatusCode::BAD_GATEWAY), 0.1, 3)
+                .inject(Fault::ConnectionReset, 0.2)
+                .wrap(Arc::new(Unused));
+            (0..200).map(|_| chaos.next_fault()).collect::<Vec<_>>()
+        };
+
+        let first = faults(7);
+        assert_eq!(first, faults(7));
+        assert_ne!(first, faults(8));
+
+        let start = first
+            .iter()
+            .position(|fault| *fault == bad_gateway)
+            .expect("a burst within 200 requests");
+        assert!(
+            first[start..start + 3]
+                .iter()
+                .all(|fault| *fault == bad_gateway)
+        );
+        assert!(first.contains(&Some(Fault::ConnectionReset)));
+        assert!(first.contains(&None));
+    }
+
+    #[test]
+    fn script_runs_before_probabilities() {
+        let chaos = ChaosConfig::scripted([Some(Fault::Timeout), None])
+            .inject(Fault::MalformedJson, 1.0)
+            .wrap(Arc::new(Unused));
+
+        assert_eq!(chaos.next_fault(), Some(Fault::Timeout));
+        assert_eq!(chaos.next_fault(), None);
+        assert_eq!(chaos.next_fault(), Some(Fault::MalformedJson));
+    }
+}
@@ -1,33 +1,41 @@
 use crate::auth::{Auth, AuthLevel, OutputFormat};
+use crate::cache::{CacheConfig, CacheLayer, CacheStore};
+#[cfg(feature = "testing")]
+use crate::cassette::CassetteConfig;
+#[cfg(feature = "testing")]
+use crate::chaos::ChaosConfig;
+use crate::coalesce::CoalesceLayer;
+use crate::endpoints::route;
 use crate::endpoints::{
-    collection::CollectionApi, database::DatabaseApi, inventory::InventoryApi,
-    marketplace::MarketplaceApi, user::UserApi, user_list::ListApi, wantlist::WantlistApi,
+    Endpoint, Route, collection::CollectionApi, database::DatabaseApi, inventory::InventoryApi,
+    marketplace::MarketplaceApi, raw::RawRequest, user::UserApi, user_list::ListApi,
+    wantlist::WantlistApi,
 };
-use crate::error::{DiscogsError, Result};
-use crate::models::{AboutResponse, ApiResponse, Identity, RateLimit};
-use reqwest::header::{ACCEPT, AUTHORIZATION, USER_AGENT};
-use reqwest::{Method, Response, StatusCode};
+use crate::error::{DiscogsError, ErrorContext, Result, redact_url};
+use crate::links::{Link, Resource};
+use crate::middleware::{AttemptCounter, AuthLayer, Middleware, Next, RequiredAuth, RetryLayer};
+use crate::models::{AboutResponse, ApiResponse, Identity, RateLimit, ResponseMeta};
+use crate::oauth::{Clock, NonceSource, RandomNonce, SystemClock};
+use crate::pagination::Paginated;
+use crate::rate_limiter::{LowRemaining, RateLimiter, RateLimiterConfig};
+use crate::retry::{RetryConfig, RetryPolicy};
+use crate::secret::Secret;
+use crate::telemetry::{AttemptSpan, CallSpan};
+use crate::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportErrorKind, default_transport,
+};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use http::header::{
+    ACCEPT, AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, USER_AGENT,
+};
+use http::{Extensions, Method, StatusCode};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use std::borrow::Cow;
 use std::sync::Arc;
-use std::time::Duration;
-
-#[derive(Debug, Clone)]
-pub struct RetryConfig {
-    pub max_retries: u32,
-    pub base_delay: Duration,
-    pub backoff_factor: f64,
-}
-
-impl Default for RetryConfig {
-    fn default() -> Self {
-        Self {
-            max_retries: 0,
-            base_delay: Duration::from_millis(2_000),
-            backoff_factor: 2.7,
-        }
-    }
-}
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
 #[derive(Debug, Clone)]
 struct ClientConfig {
@@ -35,18 +43,82 @@ struct ClientConfig {
     user_agent: String,
     output_format: OutputFormat,
     auth: Auth,
-    retry: RetryConfig,
+    retry: Arc<dyn RetryPolicy>,
+    rate_limiter: Arc<RateLimiter>,
+    timeout: Duration,
+    headers: HeaderMap,
+    layers: Arc<[Arc<dyn Middleware>]>,
+    clock: Arc<dyn Clock>,
+    nonce_source: Arc<dyn NonceSource>,
+}
+
+/// The future returned by endpoint methods. It owns a clone of the client, so it is `Send` and
+/// `'static` and can be stored or handed to `tokio::spawn`.
+pub type ApiFuture<T> = BoxFuture<'static, Result<ApiResponse<T>>>;
+
+/// Overrides for the calls made through [`DiscogsClient::with_options`] or an endpoint group's
+/// `with_options`. Unset values keep the client's configuration.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    timeout: Option<Duration>,
+    output_format: Option<OutputFormat>,
+    headers: HeaderMap,
+    retry: Option<Arc<dyn RetryPolicy>>,
+    auth: Option<Auth>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = Some(output_format);
+        self
+    }
+
+    /// Sends `value` for `name`, replacing the client's own header of that name.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: impl RetryPolicy + 'static) -> Self {
+        self.retry = Some(Arc::new(policy));
+        self
+    }
+
+    /// Signs the calls with `auth` instead of the client's credentials. The auth level check
+    /// uses these credentials too.
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
 }
 
 #[derive(Clone)]
 pub struct DiscogsClient {
     config: Arc<ClientConfig>,
-    http: reqwest::Client,
+    transport: Arc<dyn HttpTransport>,
 }
 
 pub struct DiscogsClientBuilder {
     config: ClientConfig,
-    timeout: Duration,
+    transport: Option<Arc<dyn HttpTransport>>,
+    rate_limiter: Option<RateLimiterConfig>,
+    low_remaining: Option<LowRemaining>,
+    layers: Vec<Arc<dyn Middleware>>,
+    coalesce: bool,
+    cache: Option<CacheLayer>,
+    #[cfg(feature = "testing")]
+    cassette: Option<CassetteConfig>,
+    #[cfg(feature = "testing")]
+    chaos: Option<ChaosConfig>,
 }
 
 impl DiscogsClientBuilder {
@@ -60,7 +132,7 @@ impl DiscogsClientBuilder {
         self
     }
 
-    pub fn user_token(mut self, token: impl Into<String>) -> Self {
+    pub fn user_token(mut self, token: impl Into<Secret>) -> Self {
         self.config.auth = Auth::UserToken {
             token: token.into(),
         };
@@ -73,22 +145,140 @@ impl DiscogsClientBuilder {
     }
 
     pub fn retry(mut self, retry: RetryConfig) -> Self {
-        self.config.retry = retry;
+        self.config.retry = Arc::new(retry);
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: impl RetryPolicy + 'static) -> Self {
+        self.config.retry = Arc::new(policy);
+        self
+    }
+
+    /// Clock for OAuth timestamps. Inject a fixed one to reproduce signatures in tests.
+    pub fn oauth_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.config.clock = Arc::new(clock);
+        self
+    }
+
+    /// Source of OAuth nonces. Inject a fixed one to reproduce signatures in tests.
+    pub fn oauth_nonce_source(mut self, nonce_source: impl NonceSource + 'static) -> Self {
+        self.config.nonce_source = Arc::new(nonce_source);
         self
     }
 
     pub fn timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = timeout;
+        self.config.timeout = timeout;
+        self
+    }
+
+    /// Sends requests through `transport` instead of the default `reqwest` client.
+    pub fn transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Paces requests client-side so bursts stay inside the Discogs moving window.
+    ///
+    /// Off by default: pacing makes calls wait without telling the caller, and until the first
+    /// response the limit (25 or 60 per minute depending on auth) is only a guess. Without it,
+    /// `429`s surface as errors or go through the retry policy, which honours `Retry-After`.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiterConfig) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Calls `callback` whenever `x-discogs-ratelimit-remaining` drops below `threshold`.
+    pub fn on_rate_limit_low(
+        mut self,
+        threshold: u32,
+        callback: impl Fn(&RateLimit) + Send + Sync + 'static,
+    ) -> Self {
+        self.low_remaining = Some(LowRemaining {
+            threshold,
+            callback: Arc::new(callback),
+        });
+        self
+    }
+
+    /// Appends a middleware layer. Layers run in the order they are added, after the built-in
+    /// auth check and before the coalescing, cache and retry layers, so a response they see may
+    /// have been served from the cache or shared with a coalesced request.
+    pub fn layer(mut self, layer: impl Middleware + 'static) -> Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+
+    /// Merges concurrent identical GET requests (same URL, query, credentials and output
+    /// format) into one network call whose response every caller receives.
+    pub fn coalesce_requests(mut self) -> Self {
+        self.coalesce = true;
+        self
+    }
+
+    /// Caches GET responses in `store`. Hits skip the retry layer and the rate limiter.
+    pub fn cache(mut self, store: impl CacheStore + 'static, config: CacheConfig) -> Self {
+        self.cache = Some(CacheLayer::new(Arc::new(store), config));
+        self
+    }
+
+    /// Records every exchange to a cassette file, or answers requests from one, for tests that
+    /// must not depend on the network. Recording goes through the configured transport.
+    #[cfg(feature = "testing")]
+    pub fn cassette(mut self, cassette: CassetteConfig) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Injects latency, 429s, 5xx responses, broken bodies, connection resets and timeouts
+    /// into requests below the retry layer, to exercise retry and error handling.
+    #[cfg(feature = "testing")]
+    pub fn chaos(mut self, chaos: ChaosConfig) -> Self {
+        self.chaos = Some(chaos);
         self
     }
 
     pub fn build(self) -> Result<DiscogsClient> {
-        let http = reqwest::Client::builder().timeout(self.timeout).build()?;
+        #[cfg(feature = "testing")]
+        let transport = {
+            let transport = match (self.cassette, self.transport) {
+                (Some(cassette), transport) => cassette.into_transport(transport)?,
+                (None, Some(transport)) => transport,
+                (None, None) => default_transport()?,
+            };
+            match self.chaos {
+                Some(chaos) => chaos.into_transport(transport),
+                None => transport,
+            }
+        };
+        #[cfg(not(feature = "testing"))]
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => default_transport()?,
+        };
+        let mut config = self.config;
+        config.rate_limiter = Arc::new(RateLimiter::new(self.rate_limiter, self.low_remaining));
+        let mut layers: Vec<Arc<dyn Middleware>> = Vec::with_capacity(self.layers.len() + 4);
+        layers.push(Arc::new(AuthLayer));
+        layers.extend(self.layers);
+        if self.coalesce {
+            layers.push(Arc::new(CoalesceLayer::default()));
+        }
+        if let Some(cache) = self.cache {
+            layers.push(Arc::new(cache));
+        }
+        layers.push(Arc::new(RetryLayer));
+        config.layers = layers.into();
         Ok(DiscogsClient {
-            config: Arc::new(self.config),
-            http,
+            config: Arc::new(config),
+            transport,
         })
     }
+
+    /// Builds a [`blocking::DiscogsClient`](crate::blocking::DiscogsClient) for synchronous code.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<crate::blocking::DiscogsClient> {
+        crate::blocking::DiscogsClient::new(self.build()?)
+    }
 }
 
 impl DiscogsClient {
@@ -99,9 +289,24 @@ impl DiscogsClient {
                 user_agent: user_agent.into(),
                 output_format: OutputFormat::Discogs,
                 auth: Auth::None,
-                retry: RetryConfig::default(),
+                retry: Arc::new(RetryConfig::default()),
+                rate_limiter: Arc::new(RateLimiter::new(None, None)),
+                timeout: Duration::from_secs(30),
+                headers: HeaderMap::new(),
+                layers: Arc::new([]),
+                clock: Arc::new(SystemClock),
+                nonce_source: Arc::new(RandomNonce),
             },
-            timeout: Duration::from_secs(30),
+            transport: None,
+            rate_limiter: None,
+            low_remaining: None,
+            layers: Vec::new(),
+            coalesce: false,
+            cache: None,
+            #[cfg(feature = "testing")]
+            cassette: None,
+            #[cfg(feature = "testing")]
+            chaos: None,
         }
     }
 
@@ -115,13 +320,13 @@ impl DiscogsClient {
 
     pub fn with_user_token(
         user_agent: impl Into<String>,
-        token: impl Into<String>,
+        token: impl Into<Secret>,
     ) -> Result<DiscogsClient> {
         Self::builder(user_agent).user_token(token).build()
     }
 
     pub fn with_default_user_agent_and_user_token(
-        token: impl Into<String>,
+        token: impl Into<Secret>,
     ) -> Result<DiscogsClient> {
         Self::with_default_user_agent().user_token(token).build()
     }
@@ -130,201 +335,470 @@ impl DiscogsClient {
         self.config.auth.level()
     }
 
-    pub async fn about(&self) -> Result<ApiResponse<AboutResponse>> {
-        self.request_json::<AboutResponse, (), ()>(Method::GET, "/", None, None, AuthLevel::None)
-            .await
+    /// Returns a client sharing this one's connection pool and rate limiter but retrying with
+    /// `policy`, e.g. `client.with_retry_policy(NoRetry).marketplace().add_listing(..)`.
+    pub fn with_retry_policy(&self, policy: impl RetryPolicy + 'static) -> DiscogsClient {
+        let mut config = (*self.config).clone();
+        config.retry = Arc::new(policy);
+        DiscogsClient {
+            config: Arc::new(config),
+            transport: Arc::clone(&self.transport),
+        }
+    }
+
+    /// Returns a client sharing this one's connection pool and rate limiter with `options`
+    /// applied, e.g. `client.with_options(RequestOptions::new().timeout(..)).inventory()`.
+    pub fn with_options(&self, options: RequestOptions) -> DiscogsClient {
+        let mut config = (*self.config).clone();
+        if let Some(timeout) = options.timeout {
+            config.timeout = timeout;
+        }
+        if let Some(output_format) = options.output_format {
+            config.output_format = output_format;
+        }
+        if let Some(retry) = options.retry {
+            config.retry = retry;
+        }
+        if let Some(auth) = options.auth {
+            config.auth = auth;
+        }
+        config.headers.extend(options.headers);
+        DiscogsClient {
+            config: Arc::new(config),
+            transport: Arc::clone(&self.transport),
+        }
+    }
+
+    /// Latest rate-limit headers seen by this client or any of its clones.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.config.rate_limiter.latest()
+    }
+
+    /// Watch channel updated with every rate-limit header set the client receives.
+    pub fn rate_limit_watch(&self) -> watch::Receiver<Option<RateLimit>> {
+        self.config.rate_limiter.subscribe()
+    }
+
+    pub fn about(&self) -> ApiFuture<AboutResponse> {
+        self.request_json::<AboutResponse, (), ()>(
+            Method::GET,
+            route!("about", "/"),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn get_identity(&self) -> Result<ApiResponse<Identity>> {
+    pub fn get_identity(&self) -> ApiFuture<Identity> {
         self.request_json::<Identity, (), ()>(
             Method::GET,
-            "/oauth/identity",
+            route!("get_identity", "/oauth/identity"),
             None,
             None,
             AuthLevel::User,
         )
-        .await
     }
 
     pub fn database(&self) -> DatabaseApi<'_> {
-        DatabaseApi::new(self)
+        DatabaseApi::new(Cow::Borrowed(self))
     }
 
     pub fn marketplace(&self) -> MarketplaceApi<'_> {
-        MarketplaceApi::new(self)
+        MarketplaceApi::new(Cow::Borrowed(self))
     }
 
     pub fn inventory(&self) -> InventoryApi<'_> {
-        InventoryApi::new(self)
+        InventoryApi::new(Cow::Borrowed(self))
     }
 
     pub fn user(&self) -> UserApi<'_> {
-        UserApi::new(self)
+        UserApi::new(Cow::Borrowed(self))
     }
 
     pub fn collection(&self) -> CollectionApi<'_> {
-        CollectionApi::new(self)
+        CollectionApi::new(Cow::Borrowed(self))
     }
 
     pub fn wantlist(&self) -> WantlistApi<'_> {
-        WantlistApi::new(self)
+        WantlistApi::new(Cow::Borrowed(self))
     }
 
     pub fn list(&self) -> ListApi<'_> {
-        ListApi::new(self)
+        ListApi::new(Cow::Borrowed(self))
+    }
+
+    /// Fetches the model a typed link points at, e.g. `client.follow(&credit.resource_url)`.
+    pub fn follow<T: Resource + 'static>(&self, link: &Link<T>) -> ApiFuture<T> {
+        let route = Route {
+            endpoint: T::ENDPOINT,
+            path: link.as_str().to_string(),
+        };
+        self.request_json::<T, (), ()>(Method::GET, route, None, None, T::REQUIRED_AUTH)
+    }
+
+    /// Fetches the page after `page` from its `pagination.urls.next` link, or `None` on the last
+    /// page. The request reuses the endpoint name and required auth level recorded in
+    /// `page.meta`.
+    pub async fn next_page<R>(&self, page: &ApiResponse<R>) -> Result<Option<ApiResponse<R>>>
+    where
+        R: Paginated + DeserializeOwned + 'static,
+    {
+        let next = page
+            .data
+            .pagination()
+            .urls
+            .as_ref()
+            .and_then(|urls| urls.next.as_deref());
+        let Some(next) = next else {
+            return Ok(None);
+        };
+        let route = Route {
+            endpoint: page.meta.endpoint.unwrap_or(Endpoint {
+                name: "next_page",
+                template: "{next}",
+            }),
+            path: next.to_string(),
+        };
+        self.request_json::<R, (), ()>(Method::GET, route, None, None, page.meta.required_auth)
+            .await
+            .map(Some)
+    }
+
+    /// Starts a request to any path, e.g. one the crate has no endpoint method for yet.
+    /// `path` is relative to the base URL unless it is absolute.
+    pub fn raw(&self, method: Method, path: impl Into<String>) -> RawRequest<'_> {
+        RawRequest::new(Cow::Borrowed(self), method, path.into())
     }
 
-    pub(crate) async fn request_json<T, Q, B>(
+    // The request is built before the returned future is polled, so the future owns everything it
+    // needs and does not borrow the client, the query or the body.
+    pub(crate) fn request_json<T, Q, B>(
         &self,
         method: Method,
-        path: &str,
+        route: Route,
         query: Option<&Q>,
         body: Option<&B>,
         required_auth: AuthLevel,
-    ) -> Result<ApiResponse<T>>
+    ) -> ApiFuture<T>
     where
-        T: DeserializeOwned,
+        T: DeserializeOwned + 'static,
         Q: Serialize + ?Sized,
         B: Serialize + ?Sized,
     {
-        let response = self
-            .send_with_retry(method, path, query, body, required_auth)
-            .await?;
-
-        let rate_limit = parse_rate_limit(&response);
-        let data = response.json::<T>().await?;
-        Ok(ApiResponse { data, rate_limit })
+        let call = self.send_with_retry(method, route, query, body, required_auth);
+        Box::pin(async move {
+            let response = call.await?;
+            let data = serde_json::from_slice::<T>(&response.data).map_err(|error| {
+                DiscogsError::from(error).with_context(ErrorContext {
+                    method: response.meta.method.clone(),
+                    url: response.meta.url.clone(),
+                    attempts: response.meta.attempts,
+                    elapsed: response.meta.elapsed,
+                    rate_limit: response.rate_limit.clone(),
+                })
+            })?;
+            Ok(ApiResponse {
+                data,
+                rate_limit: response.rate_limit,
+                meta: response.meta,
+            })
+        })
     }
 
-    pub(crate) async fn request_empty<Q, B>(
+    pub(crate) fn request_empty<Q, B>(
         &self,
         method: Method,
-        path: &str,
+        route: Route,
         query: Option<&Q>,
         body: Option<&B>,
         required_auth: AuthLevel,
-    ) -> Result<ApiResponse<()>>
+    ) -> ApiFuture<()>
     where
         Q: Serialize + ?Sized,
         B: Serialize + ?Sized,
     {
-        let response = self
-            .send_with_retry(method, path, query, body, required_auth)
-            .await?;
-
-        let rate_limit = parse_rate_limit(&response);
-        Ok(ApiResponse {
-            data: (),
-            rate_limit,
+        let call = self.send_with_retry(method, route, query, body, required_auth);
+        Box::pin(async move {
+            let response = call.await?;
+            Ok(ApiResponse {
+                data: (),
+                rate_limit: response.rate_limit,
+                meta: response.meta,
+            })
         })
     }
 
-    pub(crate) async fn request_bytes<Q, B>(
+    pub(crate) fn request_bytes<Q, B>(
         &self,
         method: Method,
-        path: &str,
+        route: Route,
         query: Option<&Q>,
         body: Option<&B>,
         required_auth: AuthLevel,
-    ) -> Result<ApiResponse<bytes::Bytes>>
+    ) -> ApiFuture<Bytes>
     where
         Q: Serialize + ?Sized,
         B: Serialize + ?Sized,
     {
-        let response = self
-            .send_with_retry(method, path, query, body, required_auth)
-            .await?;
-
-        let rate_limit = parse_rate_limit(&response);
-        let data = response.bytes().await?;
-        Ok(ApiResponse { data, rate_limit })
+        self.send_with_retry(method, route, query, body, required_auth)
     }
 
-    async fn send_with_retry<Q, B>(
+    fn send_with_retry<Q, B>(
         &self,
         method: Method,
-        path: &str,
+        route: Route,
         query: Option<&Q>,
         body: Option<&B>,
         required_auth: AuthLevel,
-    ) -> Result<Response>
+    ) -> ApiFuture<Bytes>
     where
         Q: Serialize + ?Sized,
         B: Serialize + ?Sized,
     {
-        // Enforce auth level before sending any request to avoid unnecessary network round trips.
-        self.ensure_auth(required_auth)?;
-
-        let mut attempt: u32 = 0;
-        loop {
-            let mut request = self
-                .http
-                .request(method.clone(), self.absolute_url(path))
-                .header(USER_AGENT, &self.config.user_agent)
-                .header(ACCEPT, self.config.output_format.accept_header_value());
-
-            if let Some(auth_header) = self.config.auth.authorization_header() {
-                request = request.header(AUTHORIZATION, auth_header);
+        let request = self.build_request(method.clone(), &route.path, query, body);
+        let client = self.clone();
+        Box::pin(async move {
+            let mut request = match request {
+                Ok(request) => request,
+                Err(error) => {
+                    let url = client
+                        .absolute_url(&route.path)
+                        .unwrap_or_else(|_| route.path.clone());
+                    return Err(error.with_context(ErrorContext {
+                        method,
+                        url: redact_url(&url),
+                        attempts: 0,
+                        elapsed: Duration::ZERO,
+                        rate_limit: client.rate_limit(),
+                    }));
+                }
+            };
+            let started = Instant::now();
+            let attempts = AttemptCounter::default();
+            request.extensions.insert(route.endpoint);
+            request.extensions.insert(RequiredAuth(required_auth));
+            request.extensions.insert(attempts.clone());
+            let method = request.method.clone();
+            let url = request.url.clone();
+
+            let span = CallSpan::new(&route.endpoint, &method, required_auth);
+            let result = span
+                .instrument(Next::new(client.clone()).run(request))
+                .await;
+            span.finish(result.as_ref(), attempts.get());
+
+            let context = |rate_limit: Option<RateLimit>| ErrorContext {
+                method: method.clone(),
+                url: redact_url(&url),
+                attempts: attempts.get(),
+                elapsed: started.elapsed(),
+                rate_limit: rate_limit.or_else(|| client.rate_limit()),
+            };
+            match result {
+                Ok(response) if response.status.is_success() => {
+                    // The header map is moved, not copied, into the metadata.
+                    let rate_limit = parse_rate_limit(&response.headers);
+                    let meta = ResponseMeta {
+                        status: response.status,
+                        headers: Arc::new(response.headers),
+                        method,
+                        url: redact_url(&url),
+                        attempts: attempts.get(),
+                        elapsed: started.elapsed(),
+                        endpoint: Some(route.endpoint),
+                        required_auth,
+                    };
+                    Ok(ApiResponse {
+                        data: response.body,
+                        rate_limit,
+                        meta,
+                    })
+                }
+                Ok(response) => {
+                    let rate_limit = parse_rate_limit(&response.headers);
+                    Err(DiscogsError::from_response(&response).with_context(context(rate_limit)))
+                }
+                Err(error) => Err(error.with_context(context(None))),
             }
+        })
+    }
 
-            if let Some(query) = query {
-                request = request.query(query);
-            }
-            if let Some(body) = body {
-                request = request.json(body);
+    fn build_request<Q, B>(
+        &self,
+        method: Method,
+        path: &str,
+        query: Option<&Q>,
+        body: Option<&B>,
+    ) -> Result<HttpRequest>
+    where
+        Q: Serialize + ?Sized,
+        B: Serialize + ?Sized,
+    {
+        let mut url = self.absolute_url(path)?;
+        if let Some(query) = query {
+            let encoded = serde_urlencoded::to_string(query).map_err(|error| {
+                TransportError::with_source(TransportErrorKind::InvalidRequest, error)
+            })?;
+            if !encoded.is_empty() {
+                url.push(if url.contains('?') { '&' } else { '?' });
+                url.push_str(&encoded);
             }
+        }
 
-            let response = request.send().await?;
-            let status = response.status();
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, header_value(&self.config.user_agent)?);
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static(self.config.output_format.accept_header_value()),
+        );
+        // Extra headers from `RequestOptions` win over the defaults above.
+        for (name, value) in &self.config.headers {
+            headers.insert(name, value.clone());
+        }
 
-            // Discogs can return 429 under burst traffic; retry with configurable exponential backoff.
-            if status == StatusCode::TOO_MANY_REQUESTS && attempt < self.config.retry.max_retries {
-                let delay = retry_delay(&self.config.retry, attempt);
-                tokio::time::sleep(delay).await;
-                attempt += 1;
-                continue;
+        let body = match body {
+            Some(body) => {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                Some(Bytes::from(serde_json::to_vec(body)?))
             }
+            None => None,
+        };
 
-            if status.is_success() {
-                return Ok(response);
-            }
+        let mut request = HttpRequest {
+            method,
+            url,
+            headers,
+            body,
+            timeout: Some(self.config.timeout),
+            extensions: Extensions::new(),
+        };
+        self.sign(&mut request)?;
+        Ok(request)
+    }
 
-            return Err(http_error(response).await);
+    /// Sets the `Authorization` header from the client's credentials. OAuth signatures carry a
+    /// fresh nonce and timestamp, so [`dispatch`](Self::dispatch) signs every retry again.
+    fn sign(&self, request: &mut HttpRequest) -> Result<()> {
+        // An `Authorization` header from `RequestOptions` replaces the credentials.
+        if self.config.headers.contains_key(AUTHORIZATION) {
+            return Ok(());
+        }
+        let form = form_fields(request);
+        let form: Vec<(&str, &str)> = form
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        if let Some(auth_header) = self.config.auth.authorization_header_with(
+            &request.method,
+            &request.url,
+            &form,
+            self.config.clock.as_ref(),
+            self.config.nonce_source.as_ref(),
+        ) {
+            let mut auth_header = header_value(&auth_header)?;
+            auth_header.set_sensitive(true);
+            request.headers.insert(AUTHORIZATION, auth_header);
         }
+        Ok(())
     }
 
-    fn ensure_auth(&self, required: AuthLevel) -> Result<()> {
-        let current = self.config.auth.level();
-        if current < required {
-            return Err(DiscogsError::AuthRequired { required, current });
+    /// Sends one attempt, enforcing the configured timeout regardless of the transport. This is
+    /// the end of the middleware chain.
+    pub(crate) async fn dispatch(&self, mut request: HttpRequest) -> Result<HttpResponse> {
+        let attempt = request
+            .extensions
+            .get::<AttemptCounter>()
+            .map_or(1, AttemptCounter::increment);
+        // Retries replay the first attempt's request; an OAuth server rejects a reused nonce.
+        if attempt > 1 && matches!(self.config.auth, Auth::OAuth { .. }) {
+            self.sign(&mut request)?;
         }
-        Ok(())
+        let span = AttemptSpan::new(&request, attempt);
+        let response = span
+            .instrument(async {
+                self.config.rate_limiter.acquire().await;
+                match tokio::time::timeout(self.config.timeout, self.transport.send(request)).await
+                {
+                    Ok(result) => result.map_err(DiscogsError::from),
+                    Err(_) => Err(TransportError::timeout().into()),
+                }
+            })
+            .await
+            .inspect_err(|error| span.record_error(error))?;
+        span.record_response(&response);
+
+        let rate_limit = parse_rate_limit(&response.headers);
+        if response.status == StatusCode::TOO_MANY_REQUESTS && rate_limit.is_none() {
+            self.config.rate_limiter.exhaust();
+        }
+        self.config.rate_limiter.observe(rate_limit.as_ref());
+        Ok(response)
+    }
+
+    pub(crate) fn layers(&self) -> &[Arc<dyn Middleware>] {
+        &self.config.layers
+    }
+
+    /// The credentials requests are signed with, e.g. to persist tokens obtained through
+    /// [`OAuthFlow`](crate::OAuthFlow).
+    pub fn auth(&self) -> &Auth {
+        &self.config.auth
+    }
+
+    pub(crate) fn retry_policy(&self) -> Arc<dyn RetryPolicy> {
+        Arc::clone(&self.config.retry)
     }
 
-    fn absolute_url(&self, path: &str) -> String {
+    /// Resolves `path` against the base URL. Absolute URLs, e.g. followed links, must point
+    /// at the base URL so that credentials are never sent to another host.
+    fn absolute_url(&self, path: &str) -> Result<String> {
+        let base = self.config.base_url.trim_end_matches('/');
         if path.starts_with("http://") || path.starts_with("https://") {
-            return path.to_string();
+            let within_base = path.strip_prefix(base).is_some_and(|rest| {
+                rest.is_empty() || rest.starts_with('/') || rest.starts_with('?')
+            });
+            if !within_base {
+                return Err(TransportError::new(
+                    TransportErrorKind::InvalidRequest,
+                    format!(
+                        "{} does not point at the configured base URL {base}",
+                        redact_url(path)
+                    ),
+                )
+                .into());
+            }
+            return Ok(path.to_string());
         }
 
-        let base = self.config.base_url.trim_end_matches('/');
         let path = if path.starts_with('/') {
             path.to_string()
         } else {
             format!("/{path}")
         };
-        format!("{base}{path}")
+        Ok(format!("{base}{path}"))
     }
 }
 
-fn retry_delay(config: &RetryConfig, attempt: u32) -> Duration {
-    let base_ms = config.base_delay.as_millis() as f64;
-    let factor = config.backoff_factor.powi(attempt as i32);
-    let delay_ms = (base_ms * factor).round() as u64;
-    Duration::from_millis(delay_ms.max(1))
+/// The decoded fields of an `application/x-www-form-urlencoded` body, empty for anything else.
+fn form_fields(request: &HttpRequest) -> Vec<(String, String)> {
+    let is_form = request
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+    match &request.body {
+        Some(body) if is_form => url::form_urlencoded::parse(body).into_owned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn header_value(value: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|error| {
+        TransportError::with_source(TransportErrorKind::InvalidRequest, error).into()
+    })
 }
 
-fn parse_rate_limit(response: &Response) -> Option<RateLimit> {
-    let headers = response.headers();
+pub(crate) fn parse_rate_limit(headers: &HeaderMap) -> Option<RateLimit> {
     // Rate-limit headers may be absent on some responses, so parsing is intentionally optional.
     let limit = headers
         .get("x-discogs-ratelimit")?
@@ -352,25 +826,16 @@ fn parse_rate_limit(response: &Response) -> Option<RateLimit> {
     })
 }
 
-async fn http_error(response: Response) -> DiscogsError {
-    let status = response.status();
-    let message = match response.json::<serde_json::Value>().await {
-        Ok(json) => json
-            .get("message")
-            .and_then(|value| value.as_str())
-            .map(ToOwned::to_owned)
-            .unwrap_or_else(|| json.to_string()),
-        Err(_) => "unknown error".to_string(),
-    };
-
-    DiscogsError::Http { status, message }
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{DiscogsClient, RetryConfig, retry_delay};
-    use crate::auth::AuthLevel;
-    use std::time::Duration;
+    use super::DiscogsClient;
+    use crate::auth::{Auth, AuthLevel};
+    use crate::retry::{RetryConfig, retry_delay};
+    use crate::transport::HttpRequest;
+    use bytes::Bytes;
+    use http::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
+    use http::{Extensions, Method};
+    use std::time::{Duration, UNIX_EPOCH};
 
     #[test]
     fn retry_delay_grows_exponentially() {
@@ -402,4 +867,34 @@ mod tests {
 
         assert_eq!(client.auth_level(), AuthLevel::User);
     }
+
+    #[test]
+    fn oauth_signatures_cover_form_bodies() {
+        let client = DiscogsClient::builder("test-agent")
+            .auth(Auth::oauth("ck", "cs", "token", "token-secret"))
+            .oauth_clock(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+            .oauth_nonce_source("fixednonce".to_string())
+            .build()
+            .expect("build client");
+        let signed = |content_type: &'static str, body: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            let mut request = HttpRequest {
+                method: Method::POST,
+                url: "https://api.discogs.com/users/tester/wants".to_string(),
+                headers,
+                body: Some(Bytes::from_static(body.as_bytes())),
+                timeout: None,
+                extensions: Extensions::new(),
+            };
+            client.sign(&mut request).expect("sign request");
+            request.headers[AUTHORIZATION].clone()
+        };
+
+        let form = "application/x-www-form-urlencoded";
+        assert_ne!(signed(form, "notes=a"), signed(form, "notes=b"));
+        // JSON bodies are not part of the signature base string.
+        let json = "application/json";
+        assert_eq!(signed(json, "{\"a\":1}"), signed(json, "{\"a\":2}"));
+    }
 }
@@ -0,0 +1,143 @@
+//! Opt-in single-flight for GET requests, installed with
+//! [`DiscogsClientBuilder::coalesce_requests`](crate::DiscogsClientBuilder::coalesce_requests).
+//!
+//! Reference: <https://www.discogs.com/developers/#page:home,header:home-rate-limiting>
+
+use crate::cache::cache_key;
+use crate::error::{DiscogsError, Result};
+use crate::middleware::{Middleware, Next};
+use crate::transport::{HttpRequest, HttpResponse, TransportError, TransportErrorKind};
+use futures_util::future::BoxFuture;
+use http::Method;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+type Waiters = Vec<oneshot::Sender<Result<HttpResponse>>>;
+
+/// Middleware merging concurrent GET requests with the same cache key into one call.
+///
+/// The first request for a key is sent; identical requests arriving while it is in flight wait
+/// for its response (headers, and with them the rate-limit state, included) instead of spending
+/// their own quota.
+#[derive(Debug, Default)]
+pub(crate) struct CoalesceLayer {
+    in_flight: Arc<Mutex<HashMap<String, Waiters>>>,
+}
+
+/// Removes the in-flight entry even when the leading request is cancelled, so waiters fall back
+/// to sending their own request instead of hanging.
+struct Flight {
+    in_flight: Arc<Mutex<HashMap<String, Waiters>>>,
+    key: String,
+    completed: bool,
+}
+
+impl Flight {
+    fn complete(mut self, result: &Result<HttpResponse>) {
+        self.completed = true;
+        for waiter in self.take() {
+            let _ = waiter.send(share(result));
+        }
+    }
+
+    fn take(&self) -> Waiters {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&self.key)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Flight {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.take();
+        }
+    }
+}
+
+impl Middleware for CoalesceLayer {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next,
+    ) -> BoxFuture<'a, Result<HttpResponse>> {
+        Box::pin(async move {
+            if request.method != Method::GET {
+                return next.run(request).await;
+            }
+
+            let key = cache_key(&request, next.client().auth());
+            let waiting = {
+                let mut in_flight = self
+                    .in_flight
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                match in_flight.get_mut(&key) {
+                    Some(waiters) => {
+                        let (sender, receiver) = oneshot::channel();
+                        waiters.push(sender);
+                        Some(receiver)
+                    }
+                    None => {
+                        in_flight.insert(key.clone(), Vec::new());
+                        None
+                    }
+                }
+            };
+
+            if let Some(receiver) = waiting {
+                return match receiver.await {
+                    Ok(result) => result,
+                    Err(_) => next.run(request).await,
+                };
+            }
+
+            let flight = Flight {
+                in_flight: Arc::clone(&self.in_flight),
+                key,
+                completed: false,
+            };
+            let result = next.run(request).await;
+            flight.complete(&result);
+            result
+        })
+    }
+}
+
+/// A copy of the leader's result for one waiter. Errors that cannot be cloned are passed on as
+/// transport errors with the same message.
+fn share(result: &Result<HttpResponse>) -> Result<HttpResponse> {
+    let error = match result {
+        Ok(response) => return Ok(response.clone()),
+        Err(error) => error,
+    };
+    Err(match error {
+        DiscogsError::AuthRequired {
+            required,
+            current,
+            context,
+        } => DiscogsError::AuthRequired {
+            required: *required,
+            current: *current,
+            context: context.clone(),
+        },
+        DiscogsError::NotFound(error) => DiscogsError::NotFound(error.clone()),
+        DiscogsError::Unauthorized(error) => DiscogsError::Unauthorized(error.clone()),
+        DiscogsError::Forbidden(error) => DiscogsError::Forbidden(error.clone()),
+        DiscogsError::RateLimited { error, retry_after } => DiscogsError::RateLimited {
+            error: error.clone(),
+            retry_after: *retry_after,
+        },
+        DiscogsError::Validation { error, details } => DiscogsError::Validation {
+            error: error.clone(),
+            details: details.clone(),
+        },
+        DiscogsError::ServerError(error) => DiscogsError::ServerError(error.clone()),
+        DiscogsError::Http(error) => DiscogsError::Http(error.clone()),
+        DiscogsError::Transport(error) => DiscogsError::Transport(error.clone()),
+        other => TransportError::new(TransportErrorKind::Other, other.to_string()).into(),
+    })
+}
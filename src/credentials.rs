@@ -0,0 +1,875 @@
+//! Named credential profiles and a registry of per-account clients.
+//!
+//! <https://www.discogs.com/developers/#page:authentication>
+
+use crate::auth::Auth;
+use crate::client::{DiscogsClient, DiscogsClientBuilder};
+use crate::error::{DiscogsError, Result};
+use crate::oauth::SignatureMethod;
+use crate::secret::Secret;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::Rng;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use zeroize::{Zeroize, Zeroizing};
+
+const MAGIC: &[u8] = b"DCRED1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Saves and loads [`Auth`] values by profile name.
+pub trait CredentialStore: fmt::Debug + Send + Sync {
+    /// Names of the stored profiles, sorted.
+    fn profiles(&self) -> io::Result<Vec<String>>;
+    fn load(&self, profile: &str) -> io::Result<Option<Auth>>;
+    fn save(&self, profile: &str, auth: &Auth) -> io::Result<()>;
+    fn remove(&self, profile: &str) -> io::Result<()>;
+}
+
+impl<T> CredentialStore for Arc<T>
+where
+    T: CredentialStore + ?Sized,
+{
+    fn profiles(&self) -> io::Result<Vec<String>> {
+        (**self).profiles()
+    }
+
+    fn load(&self, profile: &str) -> io::Result<Option<Auth>> {
+        (**self).load(profile)
+    }
+
+    fn save(&self, profile: &str, auth: &Auth) -> io::Result<()> {
+        (**self).save(profile, auth)
+    }
+
+    fn remove(&self, profile: &str) -> io::Result<()> {
+        (**self).remove(profile)
+    }
+}
+
+/// Flat on-disk form of an [`Auth`]; which fields are set decides the variant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Profile {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_secret"
+    )]
+    token: Option<Secret>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    consumer_key: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_secret"
+    )]
+    consumer_secret: Option<Secret>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_secret"
+    )]
+    access_token: Option<Secret>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_secret"
+    )]
+    access_token_secret: Option<Secret>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature_method: Option<String>,
+}
+
+impl Profile {
+    fn from_auth(auth: &Auth) -> Self {
+        match auth {
+            Auth::None => Self::default(),
+            Auth::UserToken { token } => Self {
+                token: Some(token.clone()),
+                ..Self::default()
+            },
+            Auth::Discogs {
+                consumer_key,
+                consumer_secret,
+            } => Self {
+                consumer_key: Some(consumer_key.clone()),
+                consumer_secret: Some(consumer_secret.clone()),
+                ..Self::default()
+            },
+            Auth::OAuth {
+                consumer_key,
+                consumer_secret,
+                access_token,
+                access_token_secret,
+                signature_method,
+            } => Self {
+                consumer_key: Some(consumer_key.clone()),
+                consumer_secret: Some(consumer_secret.clone()),
+                access_token: Some(access_token.clone()),
+                access_token_secret: Some(access_token_secret.clone()),
+                signature_method: Some(signature_method.as_str().to_string()),
+                ..Self::default()
+            },
+        }
+    }
+
+    fn into_auth(self, profile: &str) -> io::Result<Auth> {
+        let signature_method = match self.signature_method.as_deref() {
+            None => SignatureMethod::default(),
+            Some(name) if name.eq_ignore_ascii_case("HMAC-SHA1") => SignatureMethod::HmacSha1,
+            Some(name) if name.eq_ignore_ascii_case("PLAINTEXT") => SignatureMethod::Plaintext,
+            Some(name) => {
+                return Err(invalid_data(format!(
+                    "profile `{profile}` has unknown signature method `{name}`"
+                )));
+            }
+        };
+
+        match self {
+            Self {
+                token: Some(token),
+                consumer_key: None,
+                consumer_secret: None,
+                access_token: None,
+                access_token_secret: None,
+                ..
+            } => Ok(Auth::UserToken { token }),
+            Self {
+                token: None,
+                consumer_key: Some(consumer_key),
+                consumer_secret: Some(consumer_secret),
+                access_token: None,
+                access_token_secret: None,
+                ..
+            } => Ok(Auth::Discogs {
+                consumer_key,
+                consumer_secret,
+            }),
+            Self {
+                token: None,
+                consumer_key: Some(consumer_key),
+                consumer_secret: Some(consumer_secret),
+                access_token: Some(access_token),
+                access_token_secret: Some(access_token_secret),
+                ..
+            } => Ok(Auth::OAuth {
+                consumer_key,
+                consumer_secret,
+                access_token,
+                access_token_secret,
+                signature_method,
+            }),
+            Self {
+                token: None,
+                consumer_key: None,
+                consumer_secret: None,
+                access_token: None,
+                access_token_secret: None,
+                ..
+            } => Ok(Auth::None),
+            _ => Err(invalid_data(format!(
+                "profile `{profile}` needs either a token, a consumer key and secret, or both plus an access token and secret"
+            ))),
+        }
+    }
+}
+
+/// A single file of profiles encrypted with ChaCha20-Poly1305 under a key derived from a
+/// passphrase with Argon2id.
+///
+/// The file holds a magic tag, the salt, a fresh nonce per write and the ciphertext. A wrong
+/// passphrase is reported as [`io::ErrorKind::InvalidData`].
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    salt: [u8; SALT_LEN],
+    cipher: ChaCha20Poly1305,
+    // Serializes read-modify-write cycles within the process.
+    lock: Mutex<()>,
+}
+
+impl fmt::Debug for EncryptedFileStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedFileStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EncryptedFileStore {
+    /// Opens `path`, creating it on the first save. An existing file must decrypt with
+    /// `passphrase`.
+    pub fn open(path: impl Into<PathBuf>, passphrase: &str) -> io::Result<Self> {
+        let path = path.into();
+        let salt = match fs::read(&path) {
+            Ok(bytes) => {
+                let (salt, _, _) = split_file(&bytes)?;
+                salt
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                let mut salt = [0u8; SALT_LEN];
+                rand::rng().fill(&mut salt);
+                salt
+            }
+            Err(error) => return Err(error),
+        };
+
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|error| invalid_data(error.to_string()))?;
+        let store = Self {
+            path,
+            salt,
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+            lock: Mutex::new(()),
+        };
+        key.zeroize();
+
+        store.read()?;
+        Ok(store)
+    }
+
+    fn read(&self) -> io::Result<BTreeMap<String, Profile>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(error) => return Err(error),
+        };
+        let (_, nonce, ciphertext) = split_file(&bytes)?;
+        let plaintext = Zeroizing::new(
+            self.cipher
+                .decrypt(Nonce::from_slice(&nonce), ciphertext)
+                .map_err(|_| invalid_data("wrong passphrase or corrupted credential file"))?,
+        );
+        serde_json::from_slice(&plaintext).map_err(io::Error::from)
+    }
+
+    fn write(&self, profiles: &BTreeMap<String, Profile>) -> io::Result<()> {
+        let plaintext = Zeroizing::new(serde_json::to_vec(profiles)?);
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rng().fill(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| invalid_data("failed to encrypt credentials"))?;
+
+        let mut contents =
+            Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+        contents.extend_from_slice(MAGIC);
+        contents.extend_from_slice(&self.salt);
+        contents.extend_from_slice(&nonce);
+        contents.extend_from_slice(&ciphertext);
+        write_private(&self.path, &contents)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BTreeMap<String, Profile>)) -> io::Result<()> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut profiles = self.read()?;
+        f(&mut profiles);
+        self.write(&profiles)
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn profiles(&self) -> io::Result<Vec<String>> {
+        Ok(self.read()?.into_keys().collect())
+    }
+
+    fn load(&self, profile: &str) -> io::Result<Option<Auth>> {
+        self.read()?
+            .remove(profile)
+            .map(|stored| stored.into_auth(profile))
+            .transpose()
+    }
+
+    fn save(&self, profile: &str, auth: &Auth) -> io::Result<()> {
+        self.update(|profiles| {
+            profiles.insert(profile.to_string(), Profile::from_auth(auth));
+        })
+    }
+
+    fn remove(&self, profile: &str) -> io::Result<()> {
+        self.update(|profiles| {
+            profiles.remove(profile);
+        })
+    }
+}
+
+/// Plaintext TOML profiles, one table per profile:
+///
+/// ```toml
+/// [profiles.shop-a]
+/// token = "..."
+///
+/// [profiles.shop-b]
+/// consumer_key = "..."
+/// consumer_secret = "..."
+/// access_token = "..."
+/// access_token_secret = "..."
+/// ```
+///
+/// Stores opened from a file write changes back to it; stores parsed from a string keep them in
+/// memory.
+#[derive(Debug)]
+pub struct TomlCredentialStore {
+    path: Option<PathBuf>,
+    profiles: RwLock<BTreeMap<String, Profile>>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct TomlFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl TomlCredentialStore {
+    /// Reads `path`; a missing file is an empty store.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = match fs::read_to_string(&path) {
+            Ok(text) => parse_toml(&Zeroizing::new(text))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => TomlFile::default(),
+            Err(error) => return Err(error),
+        };
+        Ok(Self {
+            path: Some(path),
+            profiles: RwLock::new(file.profiles),
+        })
+    }
+
+    pub fn from_toml_str(text: &str) -> io::Result<Self> {
+        Ok(Self {
+            path: None,
+            profiles: RwLock::new(parse_toml(text)?.profiles),
+        })
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BTreeMap<String, Profile>)) -> io::Result<()> {
+        let mut profiles = self
+            .profiles
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut profiles);
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let text = Zeroizing::new(
+            toml::to_string(&TomlFile {
+                profiles: profiles.clone(),
+            })
+            .map_err(|error| invalid_data(error.to_string()))?,
+        );
+        write_private(path, text.as_bytes())
+    }
+}
+
+impl CredentialStore for TomlCredentialStore {
+    fn profiles(&self) -> io::Result<Vec<String>> {
+        let profiles = self
+            .profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(profiles.keys().cloned().collect())
+    }
+
+    fn load(&self, profile: &str) -> io::Result<Option<Auth>> {
+        let profiles = self
+            .profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        profiles
+            .get(profile)
+            .cloned()
+            .map(|stored| stored.into_auth(profile))
+            .transpose()
+    }
+
+    fn save(&self, profile: &str, auth: &Auth) -> io::Result<()> {
+        self.update(|profiles| {
+            profiles.insert(profile.to_string(), Profile::from_auth(auth));
+        })
+    }
+
+    fn remove(&self, profile: &str) -> io::Result<()> {
+        self.update(|profiles| {
+            profiles.remove(profile);
+        })
+    }
+}
+
+/// Read-only profiles from environment variables, captured when the store is created.
+///
+/// The `default` profile reads `DISCOGS_TOKEN`, `DISCOGS_CONSUMER_KEY`,
+/// `DISCOGS_CONSUMER_SECRET`, `DISCOGS_ACCESS_TOKEN` and `DISCOGS_ACCESS_TOKEN_SECRET`. A profile
+/// added with [`EnvCredentialStore::profile`] reads the same names with its upper-cased name
+/// inserted, e.g. `DISCOGS_SHOP_A_TOKEN` for `shop-a`.
+///
+/// Values are held as [`Secret`]s, so `{:?}` shows the variable names but never their values.
+#[derive(Debug, Clone)]
+pub struct EnvCredentialStore {
+    vars: BTreeMap<String, Secret>,
+    profiles: Vec<String>,
+}
+
+impl Default for EnvCredentialStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvCredentialStore {
+    pub fn new() -> Self {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Uses `vars` instead of the process environment.
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            vars: vars
+                .into_iter()
+                .filter(|(name, _)| name.starts_with("DISCOGS_"))
+                .map(|(name, value)| (name, Secret::from(value)))
+                .collect(),
+            profiles: vec!["default".to_string()],
+        }
+    }
+
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.profiles.contains(&name) {
+            self.profiles.push(name);
+            self.profiles.sort();
+        }
+        self
+    }
+
+    fn var(&self, profile: &str, suffix: &str) -> Option<Secret> {
+        let name = if profile == "default" {
+            format!("DISCOGS_{suffix}")
+        } else {
+            let profile: String = profile
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            format!("DISCOGS_{profile}_{suffix}")
+        };
+        self.vars
+            .get(&name)
+            .filter(|value| !value.is_empty())
+            .cloned()
+    }
+
+    fn read(&self, profile: &str) -> Profile {
+        Profile {
+            token: self.var(profile, "TOKEN"),
+            // Neither is secret; `Auth` keeps them as plain strings.
+            consumer_key: self
+                .var(profile, "CONSUMER_KEY")
+                .map(|key| key.expose().to_string()),
+            consumer_secret: self.var(profile, "CONSUMER_SECRET"),
+            access_token: self.var(profile, "ACCESS_TOKEN"),
+            access_token_secret: self.var(profile, "ACCESS_TOKEN_SECRET"),
+            signature_method: self
+                .var(profile, "SIGNATURE_METHOD")
+                .map(|method| method.expose().to_string()),
+        }
+    }
+}
+
+impl CredentialStore for EnvCredentialStore {
+    fn profiles(&self) -> io::Result<Vec<String>> {
+        Ok(self
+            .profiles
+            .iter()
+            .filter(|profile| {
+                self.var(profile, "TOKEN").is_some() || self.var(profile, "CONSUMER_KEY").is_some()
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn load(&self, profile: &str) -> io::Result<Option<Auth>> {
+        if !self.profiles.iter().any(|name| name == profile) {
+            return Ok(None);
+        }
+        match self.read(profile).into_auth(profile)? {
+            Auth::None => Ok(None),
+            auth => Ok(Some(auth)),
+        }
+    }
+
+    fn save(&self, _profile: &str, _auth: &Auth) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "environment credentials are read-only",
+        ))
+    }
+
+    fn remove(&self, _profile: &str) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "environment credentials are read-only",
+        ))
+    }
+}
+
+type Configure = dyn Fn(DiscogsClientBuilder) -> DiscogsClientBuilder + Send + Sync;
+
+/// Hands out one [`DiscogsClient`] per account.
+///
+/// Every client is built separately, so each has its own rate limiter and Discogs quota
+/// tracking; a busy account never slows down another.
+pub struct ClientRegistry {
+    user_agent: String,
+    configure: Option<Arc<Configure>>,
+    clients: RwLock<BTreeMap<String, DiscogsClient>>,
+}
+
+impl fmt::Debug for ClientRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientRegistry")
+            .field("user_agent", &self.user_agent)
+            .field("profiles", &self.profiles())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientRegistry {
+    pub fn new(user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            configure: None,
+            clients: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Applied to every client builder after the user agent and credentials are set, e.g. to
+    /// add a rate limiter, a cache or middleware.
+    pub fn configure(
+        mut self,
+        configure: impl Fn(DiscogsClientBuilder) -> DiscogsClientBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.configure = Some(Arc::new(configure));
+        self
+    }
+
+    /// Builds a client for `profile`, replacing any previous one.
+    pub fn insert(&self, profile: impl Into<String>, auth: Auth) -> Result<DiscogsClient> {
+        let client = self.build(auth)?;
+        self.clients
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(profile.into(), client.clone());
+        Ok(client)
+    }
+
+    /// Builds a client for every profile in `store` and returns their names.
+    ///
+    /// Fails on the first profile that cannot be loaded or built, including a listed profile
+    /// without credentials, and then leaves the registry unchanged.
+    pub fn load(&self, store: &dyn CredentialStore) -> Result<Vec<String>> {
+        let profiles = store.profiles().map_err(DiscogsError::CredentialStore)?;
+        let mut clients = Vec::with_capacity(profiles.len());
+        for profile in profiles {
+            let auth = store
+                .load(&profile)
+                .map_err(DiscogsError::CredentialStore)?
+                .ok_or_else(|| {
+                    DiscogsError::CredentialStore(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("profile `{profile}` has no credentials"),
+                    ))
+                })?;
+            clients.push((profile, self.build(auth)?));
+        }
+
+        let mut registered = self
+            .clients
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(clients
+            .into_iter()
+            .map(|(profile, client)| {
+                registered.insert(profile.clone(), client);
+                profile
+            })
+            .collect())
+    }
+
+    pub fn get(&self, profile: &str) -> Option<DiscogsClient> {
+        self.clients
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(profile)
+            .cloned()
+    }
+
+    pub fn remove(&self, profile: &str) -> Option<DiscogsClient> {
+        self.clients
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(profile)
+    }
+
+    pub fn profiles(&self) -> Vec<String> {
+        self.clients
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn build(&self, auth: Auth) -> Result<DiscogsClient> {
+        let mut builder = DiscogsClient::builder(self.user_agent.clone()).auth(auth);
+        if let Some(configure) = &self.configure {
+            builder = configure(builder);
+        }
+        builder.build()
+    }
+}
+
+fn split_file(bytes: &[u8]) -> io::Result<([u8; SALT_LEN], [u8; NONCE_LEN], &[u8])> {
+    let rest = bytes
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| invalid_data("not a discogs-rs credential file"))?;
+    if rest.len() < SALT_LEN + NONCE_LEN {
+        return Err(invalid_data("truncated credential file"));
+    }
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    Ok((
+        salt.try_into().expect("salt length checked"),
+        nonce.try_into().expect("nonce length checked"),
+        ciphertext,
+    ))
+}
+
+// Profiles are only ever written by the stores themselves.
+fn serialize_secret<S: Serializer>(
+    secret: &Option<Secret>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match secret {
+        Some(secret) => Secret::serialize_exposed(secret, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn parse_toml(text: &str) -> io::Result<TomlFile> {
+    toml::from_str(text).map_err(|error| invalid_data(error.to_string()))
+}
+
+// Same temp-file-and-rename approach as the disk cache, but readable by the owner only.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+
+    let tmp = path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = (|| {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientRegistry, CredentialStore, EncryptedFileStore, EnvCredentialStore,
+        TomlCredentialStore,
+    };
+    use crate::auth::Auth;
+    use std::io;
+
+    fn oauth() -> Auth {
+        Auth::oauth("ck", "cs", "at", "ats")
+    }
+
+    fn token(auth: Option<Auth>) -> Option<String> {
+        match auth {
+            Some(Auth::UserToken { token }) => Some(token.expose().to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn encrypted_store_round_trips_and_rejects_wrong_passphrase() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("credentials.bin");
+        let store = EncryptedFileStore::open(&path, "correct horse").expect("open store");
+        store
+            .save(
+                "shop-a",
+                &Auth::UserToken {
+                    token: "t-a".into(),
+                },
+            )
+            .expect("save");
+        store.save("shop-b", &oauth()).expect("save");
+
+        let raw = std::fs::read(&path).expect("read file");
+        assert!(!raw.windows(3).any(|window| window == b"t-a"));
+
+        let reopened = EncryptedFileStore::open(&path, "correct horse").expect("reopen store");
+        assert_eq!(reopened.profiles().expect("profiles"), ["shop-a", "shop-b"]);
+        assert_eq!(
+            token(reopened.load("shop-a").expect("load")).as_deref(),
+            Some("t-a")
+        );
+        assert!(matches!(
+            reopened.load("shop-b").expect("load"),
+            Some(Auth::OAuth { access_token, .. }) if access_token.expose() == "at"
+        ));
+
+        reopened.remove("shop-a").expect("remove");
+        assert_eq!(reopened.profiles().expect("profiles"), ["shop-b"]);
+
+        let error = EncryptedFileStore::open(&path, "wrong").expect_err("wrong passphrase");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn toml_profiles_map_to_auth_variants() {
+        let store = TomlCredentialStore::from_toml_str(
+            r#"
+            [profiles.shop-a]
+            token = "t-a"
+
+            [profiles.shop-b]
+            consumer_key = "ck"
+            consumer_secret = "cs"
+            access_token = "at"
+            access_token_secret = "ats"
+            signature_method = "PLAINTEXT"
+
+            [profiles.broken]
+            consumer_key = "ck"
+            "#,
+        )
+        .expect("parse toml");
+
+        assert_eq!(
+            token(store.load("shop-a").expect("load")).as_deref(),
+            Some("t-a")
+        );
+        assert!(matches!(
+            store.load("shop-b").expect("load"),
+            Some(Auth::OAuth {
+                signature_method: crate::oauth::SignatureMethod::Plaintext,
+                ..
+            })
+        ));
+        assert_eq!(
+            store.load("broken").expect_err("incomplete").kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(store.load("missing").expect("load").is_none());
+    }
+
+    #[test]
+    fn registry_load_fails_on_the_first_bad_profile() {
+        let store = TomlCredentialStore::from_toml_str(
+            r#"
+            [profiles.shop-a]
+            token = "t-a"
+
+            [profiles.shop-b]
+            token = "t-b"
+            signature_method = "RSA-SHA1"
+            "#,
+        )
+        .expect("parse toml");
+        let registry = ClientRegistry::new("test-agent");
+
+        let error = registry.load(&store).expect_err("shop-b is invalid");
+        assert!(error.to_string().contains("shop-b"), "{error}");
+        assert!(registry.profiles().is_empty());
+    }
+
+    #[test]
+    fn env_profiles_use_prefixed_variables() {
+        let store = EnvCredentialStore::from_vars([
+            ("DISCOGS_TOKEN".to_string(), "t-default".to_string()),
+            ("DISCOGS_SHOP_A_CONSUMER_KEY".to_string(), "ck".to_string()),
+            (
+                "DISCOGS_SHOP_A_CONSUMER_SECRET".to_string(),
+                "cs".to_string(),
+            ),
+            ("HOME".to_string(), "/root".to_string()),
+        ])
+        .profile("shop-a")
+        .profile("shop-b");
+
+        assert_eq!(store.profiles().expect("profiles"), ["default", "shop-a"]);
+        assert_eq!(
+            token(store.load("default").expect("load")).as_deref(),
+            Some("t-default")
+        );
+        assert!(matches!(
+            store.load("shop-a").expect("load"),
+            Some(Auth::Discogs { .. })
+        ));
+        assert!(store.load("shop-b").expect("load").is_none());
+        assert_eq!(
+            store.save("x", &oauth()).expect_err("read-only").kind(),
+            io::ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn env_store_debug_hides_values() {
+        let store = EnvCredentialStore::from_vars([
+            ("DISCOGS_TOKEN".to_string(), "t-secret".to_string()),
+            (
+                "DISCOGS_CONSUMER_SECRET".to_string(),
+                "cs-secret".to_string(),
+            ),
+            (
+                "DISCOGS_ACCESS_TOKEN_SECRET".to_string(),
+                "ats-secret".to_string(),
+            ),
+        ])
+        .profile("shop-a");
+
+        let debug = format!("{store:?}");
+        assert!(debug.contains("DISCOGS_TOKEN"));
+        assert!(debug.contains("shop-a"));
+        for value in ["t-secret", "cs-secret", "ats-secret"] {
+            assert!(!debug.contains(value), "{debug}");
+        }
+    }
+}
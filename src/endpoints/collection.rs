@@ -3,124 +3,133 @@
 //! Reference: <https://www.discogs.com/developers/#page:user-collection>
 
 use crate::auth::AuthLevel;
-use crate::client::DiscogsClient;
-use crate::error::Result;
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::route;
 use crate::models::{
-    AddCollectionReleaseResponse, AddFolderRequest, ApiResponse, CollectionFields,
-    CollectionFolder, CollectionFolders, CollectionReleases, CollectionReleasesQuery,
-    CollectionValue, EditCollectionReleaseRequest, EditInstanceNoteRequest,
+    AddCollectionReleaseResponse, AddFolderRequest, CollectionFields, CollectionFolder,
+    CollectionFolders, CollectionReleases, CollectionReleasesQuery, CollectionValue,
+    EditCollectionReleaseRequest, EditInstanceNoteRequest,
 };
-use reqwest::Method;
+use crate::pagination::Paginator;
+use http::Method;
+use std::borrow::Cow;
 
+#[derive(Clone)]
 pub struct CollectionApi<'a> {
-    client: &'a DiscogsClient,
+    client: Cow<'a, DiscogsClient>,
 }
 
 impl<'a> CollectionApi<'a> {
-    pub(crate) fn new(client: &'a DiscogsClient) -> Self {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>) -> Self {
         Self { client }
     }
 
-    pub async fn get_folders(&self, username: &str) -> Result<ApiResponse<CollectionFolders>> {
-        self.client
-            .request_json::<CollectionFolders, (), ()>(
-                Method::GET,
-                &format!(
-                    "/users/{}/collection/folders",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    /// The same endpoints with `options` applied to every call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self::new(Cow::Owned(self.client.with_options(options)))
     }
 
-    pub async fn get_folder(
-        &self,
-        username: &str,
-        folder_id: u64,
-    ) -> Result<ApiResponse<CollectionFolder>> {
+    /// A handle owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> CollectionApi<'static> {
+        CollectionApi::new(Cow::Owned(self.client.into_owned()))
+    }
+
+    pub fn get_folders(&self, username: &str) -> ApiFuture<CollectionFolders> {
+        self.client.request_json::<CollectionFolders, (), ()>(
+            Method::GET,
+            route!(
+                "collection.get_folders",
+                "/users/{username}/collection/folders",
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn get_folder(&self, username: &str, folder_id: u64) -> ApiFuture<CollectionFolder> {
         let required = if folder_id == 0 {
             AuthLevel::None
         } else {
             AuthLevel::User
         };
 
-        self.client
-            .request_json::<CollectionFolder, (), ()>(
-                Method::GET,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                required,
-            )
-            .await
+        self.client.request_json::<CollectionFolder, (), ()>(
+            Method::GET,
+            route!(
+                "collection.get_folder",
+                "/users/{username}/collection/folders/{folder_id}",
+                folder_id,
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            required,
+        )
     }
 
-    pub async fn add_folder(
+    pub fn add_folder(
         &self,
         username: &str,
         request: &AddFolderRequest,
-    ) -> Result<ApiResponse<CollectionFolder>> {
+    ) -> ApiFuture<CollectionFolder> {
         self.client
             .request_json::<CollectionFolder, (), AddFolderRequest>(
                 Method::POST,
-                &format!(
-                    "/users/{}/collection/folders",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "collection.add_folder",
+                    "/users/{username}/collection/folders",
+                    username = crate::endpoints::encode_path(username)
                 ),
                 None,
                 Some(request),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn set_folder_name(
+    pub fn set_folder_name(
         &self,
         username: &str,
         folder_id: u64,
         request: &AddFolderRequest,
-    ) -> Result<ApiResponse<CollectionFolder>> {
+    ) -> ApiFuture<CollectionFolder> {
         self.client
             .request_json::<CollectionFolder, (), AddFolderRequest>(
                 Method::POST,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "collection.set_folder_name",
+                    "/users/{username}/collection/folders/{folder_id}",
+                    folder_id,
+                    username = crate::endpoints::encode_path(username)
                 ),
                 None,
                 Some(request),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn delete_folder(&self, username: &str, folder_id: u64) -> Result<ApiResponse<()>> {
-        self.client
-            .request_empty::<(), ()>(
-                Method::DELETE,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn delete_folder(&self, username: &str, folder_id: u64) -> ApiFuture<()> {
+        self.client.request_empty::<(), ()>(
+            Method::DELETE,
+            route!(
+                "collection.delete_folder",
+                "/users/{username}/collection/folders/{folder_id}",
+                folder_id,
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 
-    pub async fn get_releases(
+    pub fn get_releases(
         &self,
         username: &str,
         folder_id: u64,
         query: Option<&CollectionReleasesQuery>,
-    ) -> Result<ApiResponse<CollectionReleases>> {
+    ) -> ApiFuture<CollectionReleases> {
         let required = if folder_id == 0 {
             AuthLevel::None
         } else {
@@ -130,116 +139,123 @@ impl<'a> CollectionApi<'a> {
         self.client
             .request_json::<CollectionReleases, CollectionReleasesQuery, ()>(
                 Method::GET,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}/releases",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "collection.get_releases",
+                    "/users/{username}/collection/folders/{folder_id}/releases",
+                    folder_id,
+                    username = crate::endpoints::encode_path(username)
                 ),
                 query,
                 None,
                 required,
             )
-            .await
     }
 
-    pub async fn get_release_instances(
+    pub fn get_releases_paginated(
+        &self,
+        username: &str,
+        folder_id: u64,
+        query: Option<&CollectionReleasesQuery>,
+    ) -> Paginator<'a, CollectionReleases> {
+        let required = if folder_id == 0 {
+            AuthLevel::None
+        } else {
+            AuthLevel::User
+        };
+
+        Paginator::new(
+            &self.client,
+            route!(
+                "collection.get_releases",
+                "/users/{username}/collection/folders/{folder_id}/releases",
+                folder_id,
+                username = crate::endpoints::encode_path(username)
+            ),
+            query,
+            required,
+        )
+    }
+
+    pub fn get_release_instances(
         &self,
         username: &str,
         release_id: u64,
-    ) -> Result<ApiResponse<CollectionReleases>> {
-        self.client
-            .request_json::<CollectionReleases, (), ()>(
-                Method::GET,
-                &format!(
-                    "/users/{}/collection/releases/{release_id}",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    ) -> ApiFuture<CollectionReleases> {
+        self.client.request_json::<CollectionReleases, (), ()>(
+            Method::GET,
+            route!(
+                "collection.get_release_instances",
+                "/users/{username}/collection/releases/{release_id}",
+                release_id,
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn add_release(
+    pub fn add_release(
         &self,
         username: &str,
         release_id: u64,
         folder_id: Option<u64>,
-    ) -> Result<ApiResponse<AddCollectionReleaseResponse>> {
+    ) -> ApiFuture<AddCollectionReleaseResponse> {
         let folder_id = folder_id.unwrap_or(1);
         self.client
             .request_json::<AddCollectionReleaseResponse, (), serde_json::Value>(
                 Method::POST,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}/releases/{release_id}",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "collection.add_release",
+                    "/users/{username}/collection/folders/{folder_id}/releases/{release_id}",
+                    folder_id,
+                    release_id,
+                    username = crate::endpoints::encode_path(username)
                 ),
                 None,
                 Some(&serde_json::json!({})),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn edit_release(
+    pub fn edit_release(
         &self,
         username: &str,
         folder_id: u64,
         release_id: u64,
         instance_id: u64,
         request: &EditCollectionReleaseRequest,
-    ) -> Result<ApiResponse<()>> {
+    ) -> ApiFuture<()> {
         self.client
-            .request_empty::<(), EditCollectionReleaseRequest>(
-                Method::POST,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                Some(request),
-                AuthLevel::User,
-            )
-            .await
+            .request_empty::<(), EditCollectionReleaseRequest>(Method::POST, route!("collection.edit_release", "/users/{username}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}", folder_id, release_id, instance_id, username = crate::endpoints::encode_path(username)), None, Some(request), AuthLevel::User)
     }
 
-    pub async fn remove_release(
+    pub fn remove_release(
         &self,
         username: &str,
         folder_id: u64,
         release_id: u64,
         instance_id: u64,
-    ) -> Result<ApiResponse<()>> {
+    ) -> ApiFuture<()> {
         self.client
-            .request_empty::<(), ()>(
-                Method::DELETE,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+            .request_empty::<(), ()>(Method::DELETE, route!("collection.remove_release", "/users/{username}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}", folder_id, release_id, instance_id, username = crate::endpoints::encode_path(username)), None, None, AuthLevel::User)
     }
 
-    pub async fn get_fields(&self, username: &str) -> Result<ApiResponse<CollectionFields>> {
-        self.client
-            .request_json::<CollectionFields, (), ()>(
-                Method::GET,
-                &format!(
-                    "/users/{}/collection/fields",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    pub fn get_fields(&self, username: &str) -> ApiFuture<CollectionFields> {
+        self.client.request_json::<CollectionFields, (), ()>(
+            Method::GET,
+            route!(
+                "collection.get_fields",
+                "/users/{username}/collection/fields",
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn edit_instance_note(
+    pub fn edit_instance_note(
         &self,
         username: &str,
         folder_id: u64,
@@ -247,37 +263,26 @@ impl<'a> CollectionApi<'a> {
         instance_id: u64,
         field_id: u64,
         value: &str,
-    ) -> Result<ApiResponse<()>> {
+    ) -> ApiFuture<()> {
         let body = EditInstanceNoteRequest {
             value: value.to_string(),
         };
 
         self.client
-            .request_empty::<(), EditInstanceNoteRequest>(
-                Method::POST,
-                &format!(
-                    "/users/{}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}/fields/{field_id}",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                Some(&body),
-                AuthLevel::User,
-            )
-            .await
+            .request_empty::<(), EditInstanceNoteRequest>(Method::POST, route!("collection.edit_instance_note", "/users/{username}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}/fields/{field_id}", folder_id, release_id, instance_id, field_id, username = crate::endpoints::encode_path(username)), None, Some(&body), AuthLevel::User)
     }
 
-    pub async fn get_value(&self, username: &str) -> Result<ApiResponse<CollectionValue>> {
-        self.client
-            .request_json::<CollectionValue, (), ()>(
-                Method::GET,
-                &format!(
-                    "/users/{}/collection/value",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn get_value(&self, username: &str) -> ApiFuture<CollectionValue> {
+        self.client.request_json::<CollectionValue, (), ()>(
+            Method::GET,
+            route!(
+                "collection.get_value",
+                "/users/{username}/collection/value",
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 }
@@ -3,98 +3,155 @@
 //! Reference: <https://www.discogs.com/developers/#page:database>
 
 use crate::auth::AuthLevel;
-use crate::client::DiscogsClient;
-use crate::error::Result;
+use crate::batch::{BatchExecutor, BatchReport};
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::route;
 use crate::models::{
     ApiResponse, Artist, ArtistReleases, ArtistReleasesQuery, Currency, Label, LabelReleases,
-    Master, MasterVersions, MasterVersionsQuery, Release, ReleaseCommunityRating, ReleaseRating,
-    ReleaseStats, SearchQuery, SearchResults,
+    Master, MasterVersions, MasterVersionsQuery, PaginationParams, Release, ReleaseCommunityRating,
+    ReleaseRating, ReleaseStats, SearchQuery, SearchResults,
 };
-use reqwest::Method;
+use crate::pagination::Paginator;
+use futures_util::future::BoxFuture;
+use http::Method;
 use serde::Serialize;
+use std::borrow::Cow;
 
+#[derive(Clone)]
 pub struct DatabaseApi<'a> {
-    client: &'a DiscogsClient,
+    client: Cow<'a, DiscogsClient>,
 }
 
 impl<'a> DatabaseApi<'a> {
-    pub(crate) fn new(client: &'a DiscogsClient) -> Self {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>) -> Self {
         Self { client }
     }
 
-    pub async fn get_artist(&self, artist_id: u64) -> Result<ApiResponse<Artist>> {
-        self.client
-            .request_json::<Artist, (), ()>(
-                Method::GET,
-                &format!("/artists/{artist_id}"),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    /// The same endpoints with `options` applied to every call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self::new(Cow::Owned(self.client.with_options(options)))
+    }
+
+    /// A handle owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> DatabaseApi<'static> {
+        DatabaseApi::new(Cow::Owned(self.client.into_owned()))
+    }
+
+    pub fn get_artist(&self, artist_id: u64) -> ApiFuture<Artist> {
+        self.client.request_json::<Artist, (), ()>(
+            Method::GET,
+            route!("database.get_artist", "/artists/{artist_id}", artist_id),
+            None,
+            None,
+            AuthLevel::None,
+        )
+    }
+
+    /// Fetches many artists with at most `concurrency` requests in flight.
+    pub fn get_artists(
+        &self,
+        artist_ids: impl IntoIterator<Item = u64>,
+        concurrency: usize,
+    ) -> BoxFuture<'static, BatchReport<u64, ApiResponse<Artist>>> {
+        let artist_ids: Vec<u64> = artist_ids.into_iter().collect();
+        let api = self.clone().into_owned();
+        Box::pin(async move {
+            BatchExecutor::new(concurrency)
+                .run(artist_ids, |artist_id| api.get_artist(artist_id))
+                .await
+        })
     }
 
-    pub async fn get_artist_releases(
+    pub fn get_artist_releases(
         &self,
         artist_id: u64,
         query: Option<&ArtistReleasesQuery>,
-    ) -> Result<ApiResponse<ArtistReleases>> {
+    ) -> ApiFuture<ArtistReleases> {
         self.client
             .request_json::<ArtistReleases, ArtistReleasesQuery, ()>(
                 Method::GET,
-                &format!("/artists/{artist_id}/releases"),
+                route!(
+                    "database.get_artist_releases",
+                    "/artists/{artist_id}/releases",
+                    artist_id
+                ),
                 query,
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 
-    pub async fn get_release(
+    pub fn get_artist_releases_paginated(
         &self,
-        release_id: u64,
-        currency: Option<Currency>,
-    ) -> Result<ApiResponse<Release>> {
+        artist_id: u64,
+        query: Option<&ArtistReleasesQuery>,
+    ) -> Paginator<'a, ArtistReleases> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "database.get_artist_releases",
+                "/artists/{artist_id}/releases",
+                artist_id
+            ),
+            query,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn get_release(&self, release_id: u64, currency: Option<Currency>) -> ApiFuture<Release> {
         #[derive(Serialize)]
         struct CurrencyQuery {
             curr_abbr: Currency,
         }
 
         let query = currency.map(|curr_abbr| CurrencyQuery { curr_abbr });
-        self.client
-            .request_json::<Release, CurrencyQuery, ()>(
-                Method::GET,
-                &format!("/releases/{release_id}"),
-                query.as_ref(),
-                None,
-                AuthLevel::None,
-            )
-            .await
+        self.client.request_json::<Release, CurrencyQuery, ()>(
+            Method::GET,
+            route!("database.get_release", "/releases/{release_id}", release_id),
+            query.as_ref(),
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn get_release_rating(
+    /// Fetches many releases with at most `concurrency` requests in flight. Use
+    /// [`BatchExecutor`] directly for cancellation, progress or a currency.
+    pub fn get_releases(
         &self,
-        release_id: u64,
-        username: &str,
-    ) -> Result<ApiResponse<ReleaseRating>> {
+        release_ids: impl IntoIterator<Item = u64>,
+        concurrency: usize,
+    ) -> BoxFuture<'static, BatchReport<u64, ApiResponse<Release>>> {
+        let release_ids: Vec<u64> = release_ids.into_iter().collect();
+        let api = self.clone().into_owned();
+        Box::pin(async move {
+            BatchExecutor::new(concurrency)
+                .run(release_ids, |release_id| api.get_release(release_id, None))
+                .await
+        })
+    }
+
+    pub fn get_release_rating(&self, release_id: u64, username: &str) -> ApiFuture<ReleaseRating> {
         let username = crate::endpoints::encode_path(username);
-        self.client
-            .request_json::<ReleaseRating, (), ()>(
-                Method::GET,
-                &format!("/releases/{release_id}/rating/{username}"),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+        self.client.request_json::<ReleaseRating, (), ()>(
+            Method::GET,
+            route!(
+                "database.get_release_rating",
+                "/releases/{release_id}/rating/{username}",
+                release_id,
+                username
+            ),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn set_release_rating(
+    pub fn set_release_rating(
         &self,
         release_id: u64,
         username: &str,
         rating: u8,
-    ) -> Result<ApiResponse<ReleaseRating>> {
+    ) -> ApiFuture<ReleaseRating> {
         #[derive(Serialize)]
         struct RatingBody {
             rating: u8,
@@ -102,126 +159,190 @@ impl<'a> DatabaseApi<'a> {
 
         let rating = rating.clamp(1, 5);
         let username = crate::endpoints::encode_path(username);
-        self.client
-            .request_json::<ReleaseRating, (), RatingBody>(
-                Method::PUT,
-                &format!("/releases/{release_id}/rating/{username}"),
-                None,
-                Some(&RatingBody { rating }),
-                AuthLevel::User,
-            )
-            .await
+        self.client.request_json::<ReleaseRating, (), RatingBody>(
+            Method::PUT,
+            route!(
+                "database.set_release_rating",
+                "/releases/{release_id}/rating/{username}",
+                release_id,
+                username
+            ),
+            None,
+            Some(&RatingBody { rating }),
+            AuthLevel::User,
+        )
     }
 
-    pub async fn delete_release_rating(
-        &self,
-        release_id: u64,
-        username: &str,
-    ) -> Result<ApiResponse<()>> {
+    pub fn delete_release_rating(&self, release_id: u64, username: &str) -> ApiFuture<()> {
         let username = crate::endpoints::encode_path(username);
-        self.client
-            .request_empty::<(), ()>(
-                Method::DELETE,
-                &format!("/releases/{release_id}/rating/{username}"),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+        self.client.request_empty::<(), ()>(
+            Method::DELETE,
+            route!(
+                "database.delete_release_rating",
+                "/releases/{release_id}/rating/{username}",
+                release_id,
+                username
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 
-    pub async fn get_release_community_rating(
+    pub fn get_release_community_rating(
         &self,
         release_id: u64,
-    ) -> Result<ApiResponse<ReleaseCommunityRating>> {
-        self.client
-            .request_json::<ReleaseCommunityRating, (), ()>(
-                Method::GET,
-                &format!("/releases/{release_id}/rating"),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    ) -> ApiFuture<ReleaseCommunityRating> {
+        self.client.request_json::<ReleaseCommunityRating, (), ()>(
+            Method::GET,
+            route!(
+                "database.get_release_community_rating",
+                "/releases/{release_id}/rating",
+                release_id
+            ),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn get_release_stats(&self, release_id: u64) -> Result<ApiResponse<ReleaseStats>> {
-        self.client
-            .request_json::<ReleaseStats, (), ()>(
-                Method::GET,
-                &format!("/releases/{release_id}/stats"),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    pub fn get_release_stats(&self, release_id: u64) -> ApiFuture<ReleaseStats> {
+        self.client.request_json::<ReleaseStats, (), ()>(
+            Method::GET,
+            route!(
+                "database.get_release_stats",
+                "/releases/{release_id}/stats",
+                release_id
+            ),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn get_master(&self, master_id: u64) -> Result<ApiResponse<Master>> {
-        self.client
-            .request_json::<Master, (), ()>(
-                Method::GET,
-                &format!("/masters/{master_id}"),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    pub fn get_master(&self, master_id: u64) -> ApiFuture<Master> {
+        self.client.request_json::<Master, (), ()>(
+            Method::GET,
+            route!("database.get_master", "/masters/{master_id}", master_id),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn get_master_versions(
+    /// Fetches many master releases with at most `concurrency` requests in flight.
+    pub fn get_masters(
+        &self,
+        master_ids: impl IntoIterator<Item = u64>,
+        concurrency: usize,
+    ) -> BoxFuture<'static, BatchReport<u64, ApiResponse<Master>>> {
+        let master_ids: Vec<u64> = master_ids.into_iter().collect();
+        let api = self.clone().into_owned();
+        Box::pin(async move {
+            BatchExecutor::new(concurrency)
+                .run(master_ids, |master_id| api.get_master(master_id))
+                .await
+        })
+    }
+
+    pub fn get_master_versions(
         &self,
         master_id: u64,
         query: Option<&MasterVersionsQuery>,
-    ) -> Result<ApiResponse<MasterVersions>> {
+    ) -> ApiFuture<MasterVersions> {
         self.client
             .request_json::<MasterVersions, MasterVersionsQuery, ()>(
                 Method::GET,
-                &format!("/masters/{master_id}/versions"),
+                route!(
+                    "database.get_master_versions",
+                    "/masters/{master_id}/versions",
+                    master_id
+                ),
                 query,
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 
-    pub async fn get_label(&self, label_id: u64) -> Result<ApiResponse<Label>> {
-        self.client
-            .request_json::<Label, (), ()>(
-                Method::GET,
-                &format!("/labels/{label_id}"),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    pub fn get_master_versions_paginated(
+        &self,
+        master_id: u64,
+        query: Option<&MasterVersionsQuery>,
+    ) -> Paginator<'a, MasterVersions> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "database.get_master_versions",
+                "/masters/{master_id}/versions",
+                master_id
+            ),
+            query,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn get_label_releases(
+    pub fn get_label(&self, label_id: u64) -> ApiFuture<Label> {
+        self.client.request_json::<Label, (), ()>(
+            Method::GET,
+            route!("database.get_label", "/labels/{label_id}", label_id),
+            None,
+            None,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn get_label_releases(
         &self,
         label_id: u64,
-        query: Option<&crate::models::PaginationParams>,
-    ) -> Result<ApiResponse<LabelReleases>> {
+        query: Option<&PaginationParams>,
+    ) -> ApiFuture<LabelReleases> {
         self.client
-            .request_json::<LabelReleases, crate::models::PaginationParams, ()>(
+            .request_json::<LabelReleases, PaginationParams, ()>(
                 Method::GET,
-                &format!("/labels/{label_id}/releases"),
+                route!(
+                    "database.get_label_releases",
+                    "/labels/{label_id}/releases",
+                    label_id
+                ),
                 query,
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 
-    pub async fn search(&self, query: Option<&SearchQuery>) -> Result<ApiResponse<SearchResults>> {
-        self.client
-            .request_json::<SearchResults, SearchQuery, ()>(
-                Method::GET,
-                "/database/search",
-                query,
-                None,
-                AuthLevel::Consumer,
-            )
-            .await
+    pub fn get_label_releases_paginated(
+        &self,
+        label_id: u64,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, LabelReleases> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "database.get_label_releases",
+                "/labels/{label_id}/releases",
+                label_id
+            ),
+            query,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn search(&self, query: Option<&SearchQuery>) -> ApiFuture<SearchResults> {
+        self.client.request_json::<SearchResults, SearchQuery, ()>(
+            Method::GET,
+            route!("database.search", "/database/search"),
+            query,
+            None,
+            AuthLevel::Consumer,
+        )
+    }
+
+    pub fn search_paginated(&self, query: Option<&SearchQuery>) -> Paginator<'a, SearchResults> {
+        Paginator::new(
+            &self.client,
+            route!("database.search", "/database/search"),
+            query,
+            AuthLevel::Consumer,
+        )
     }
 }
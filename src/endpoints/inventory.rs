@@ -3,69 +3,92 @@
 //! Reference: <https://www.discogs.com/developers/#page:inventory-export>
 
 use crate::auth::AuthLevel;
-use crate::client::DiscogsClient;
-use crate::error::Result;
-use crate::models::{ApiResponse, InventoryExport, InventoryExports, PaginationParams};
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::route;
+use crate::models::{InventoryExport, InventoryExports, PaginationParams};
+use crate::pagination::Paginator;
 use bytes::Bytes;
-use reqwest::Method;
+use http::Method;
+use std::borrow::Cow;
 
+#[derive(Clone)]
 pub struct InventoryApi<'a> {
-    client: &'a DiscogsClient,
+    client: Cow<'a, DiscogsClient>,
 }
 
 impl<'a> InventoryApi<'a> {
-    pub(crate) fn new(client: &'a DiscogsClient) -> Self {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>) -> Self {
         Self { client }
     }
 
-    pub async fn export_inventory(&self) -> Result<ApiResponse<()>> {
-        self.client
-            .request_empty::<(), serde_json::Value>(
-                Method::POST,
-                "/inventory/export",
-                None,
-                Some(&serde_json::json!({})),
-                AuthLevel::User,
-            )
-            .await
+    /// The same endpoints with `options` applied to every call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self::new(Cow::Owned(self.client.with_options(options)))
     }
 
-    pub async fn get_exports(
-        &self,
-        query: Option<&PaginationParams>,
-    ) -> Result<ApiResponse<InventoryExports>> {
+    /// A handle owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> InventoryApi<'static> {
+        InventoryApi::new(Cow::Owned(self.client.into_owned()))
+    }
+
+    pub fn export_inventory(&self) -> ApiFuture<()> {
+        self.client.request_empty::<(), serde_json::Value>(
+            Method::POST,
+            route!("inventory.export_inventory", "/inventory/export"),
+            None,
+            Some(&serde_json::json!({})),
+            AuthLevel::User,
+        )
+    }
+
+    pub fn get_exports(&self, query: Option<&PaginationParams>) -> ApiFuture<InventoryExports> {
         self.client
             .request_json::<InventoryExports, PaginationParams, ()>(
                 Method::GET,
-                "/inventory/export",
+                route!("inventory.get_exports", "/inventory/export"),
                 query,
                 None,
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn get_export(&self, export_id: u64) -> Result<ApiResponse<InventoryExport>> {
-        self.client
-            .request_json::<InventoryExport, (), ()>(
-                Method::GET,
-                &format!("/inventory/export/{export_id}"),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn get_exports_paginated(
+        &self,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, InventoryExports> {
+        Paginator::new(
+            &self.client,
+            route!("inventory.get_exports", "/inventory/export"),
+            query,
+            AuthLevel::User,
+        )
     }
 
-    pub async fn download_export(&self, export_id: u64) -> Result<ApiResponse<Bytes>> {
-        self.client
-            .request_bytes::<(), ()>(
-                Method::GET,
-                &format!("/inventory/export/{export_id}/download"),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn get_export(&self, export_id: u64) -> ApiFuture<InventoryExport> {
+        self.client.request_json::<InventoryExport, (), ()>(
+            Method::GET,
+            route!(
+                "inventory.get_export",
+                "/inventory/export/{export_id}",
+                export_id
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
+    }
+
+    pub fn download_export(&self, export_id: u64) -> ApiFuture<Bytes> {
+        self.client.request_bytes::<(), ()>(
+            Method::GET,
+            route!(
+                "inventory.download_export",
+                "/inventory/export/{export_id}/download",
+                export_id
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 }
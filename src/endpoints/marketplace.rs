@@ -3,212 +3,237 @@
 //! Reference: <https://www.discogs.com/developers/#page:marketplace>
 
 use crate::auth::AuthLevel;
-use crate::client::DiscogsClient;
-use crate::error::Result;
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::route;
 use crate::models::{
-    AddOrderMessageRequest, ApiResponse, CreateListingRequest, CreateListingResponse, Currency,
+    AddOrderMessageRequest, CreateListingRequest, CreateListingResponse, Currency,
     EditOrderRequest, Listing, MarketplaceReleaseStats, Order, OrderMessage, OrderMessagesResponse,
     OrdersQuery, OrdersResponse, PaginationParams, Price,
 };
-use reqwest::Method;
+use crate::pagination::Paginator;
+use http::Method;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 
+#[derive(Clone)]
 pub struct MarketplaceApi<'a> {
-    client: &'a DiscogsClient,
+    client: Cow<'a, DiscogsClient>,
 }
 
 impl<'a> MarketplaceApi<'a> {
-    pub(crate) fn new(client: &'a DiscogsClient) -> Self {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>) -> Self {
         Self { client }
     }
 
-    pub async fn get_listing(
-        &self,
-        listing_id: u64,
-        currency: Option<Currency>,
-    ) -> Result<ApiResponse<Listing>> {
+    /// The same endpoints with `options` applied to every call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self::new(Cow::Owned(self.client.with_options(options)))
+    }
+
+    /// A handle owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> MarketplaceApi<'static> {
+        MarketplaceApi::new(Cow::Owned(self.client.into_owned()))
+    }
+
+    pub fn get_listing(&self, listing_id: u64, currency: Option<Currency>) -> ApiFuture<Listing> {
         #[derive(serde::Serialize)]
         struct CurrencyQuery {
             curr_abbr: Currency,
         }
 
         let query = currency.map(|curr_abbr| CurrencyQuery { curr_abbr });
-        self.client
-            .request_json::<Listing, CurrencyQuery, ()>(
-                Method::GET,
-                &format!("/marketplace/listings/{listing_id}"),
-                query.as_ref(),
-                None,
-                AuthLevel::None,
-            )
-            .await
+        self.client.request_json::<Listing, CurrencyQuery, ()>(
+            Method::GET,
+            route!(
+                "marketplace.get_listing",
+                "/marketplace/listings/{listing_id}",
+                listing_id
+            ),
+            query.as_ref(),
+            None,
+            AuthLevel::None,
+        )
     }
 
-    pub async fn add_listing(
-        &self,
-        request: &CreateListingRequest,
-    ) -> Result<ApiResponse<CreateListingResponse>> {
+    pub fn add_listing(&self, request: &CreateListingRequest) -> ApiFuture<CreateListingResponse> {
         self.client
             .request_json::<CreateListingResponse, (), CreateListingRequest>(
                 Method::POST,
-                "/marketplace/listings",
+                route!("marketplace.add_listing", "/marketplace/listings"),
                 None,
                 Some(request),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn edit_listing(
-        &self,
-        listing_id: u64,
-        request: &CreateListingRequest,
-    ) -> Result<ApiResponse<()>> {
-        self.client
-            .request_empty::<(), CreateListingRequest>(
-                Method::POST,
-                &format!("/marketplace/listings/{listing_id}"),
-                None,
-                Some(request),
-                AuthLevel::User,
-            )
-            .await
+    pub fn edit_listing(&self, listing_id: u64, request: &CreateListingRequest) -> ApiFuture<()> {
+        self.client.request_empty::<(), CreateListingRequest>(
+            Method::POST,
+            route!(
+                "marketplace.edit_listing",
+                "/marketplace/listings/{listing_id}",
+                listing_id
+            ),
+            None,
+            Some(request),
+            AuthLevel::User,
+        )
     }
 
-    pub async fn delete_listing(&self, listing_id: u64) -> Result<ApiResponse<()>> {
-        self.client
-            .request_empty::<(), ()>(
-                Method::DELETE,
-                &format!("/marketplace/listings/{listing_id}"),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn delete_listing(&self, listing_id: u64) -> ApiFuture<()> {
+        self.client.request_empty::<(), ()>(
+            Method::DELETE,
+            route!(
+                "marketplace.delete_listing",
+                "/marketplace/listings/{listing_id}",
+                listing_id
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 
-    pub async fn get_orders(
+    pub fn get_orders(&self, query: Option<&OrdersQuery>) -> ApiFuture<OrdersResponse> {
+        self.client.request_json::<OrdersResponse, OrdersQuery, ()>(
+            Method::GET,
+            route!("marketplace.get_orders", "/marketplace/orders"),
+            query,
+            None,
+            AuthLevel::User,
+        )
+    }
+
+    pub fn get_orders_paginated(
         &self,
         query: Option<&OrdersQuery>,
-    ) -> Result<ApiResponse<OrdersResponse>> {
-        self.client
-            .request_json::<OrdersResponse, OrdersQuery, ()>(
-                Method::GET,
-                "/marketplace/orders",
-                query,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    ) -> Paginator<'a, OrdersResponse> {
+        Paginator::new(
+            &self.client,
+            route!("marketplace.get_orders", "/marketplace/orders"),
+            query,
+            AuthLevel::User,
+        )
     }
 
-    pub async fn get_order(&self, order_id: &str) -> Result<ApiResponse<Order>> {
-        self.client
-            .request_json::<Order, (), ()>(
-                Method::GET,
-                &format!(
-                    "/marketplace/orders/{}",
-                    crate::endpoints::encode_path(order_id)
-                ),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn get_order(&self, order_id: &str) -> ApiFuture<Order> {
+        self.client.request_json::<Order, (), ()>(
+            Method::GET,
+            route!(
+                "marketplace.get_order",
+                "/marketplace/orders/{order_id}",
+                order_id = crate::endpoints::encode_path(order_id)
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 
-    pub async fn edit_order(
-        &self,
-        order_id: &str,
-        request: &EditOrderRequest,
-    ) -> Result<ApiResponse<Order>> {
-        self.client
-            .request_json::<Order, (), EditOrderRequest>(
-                Method::POST,
-                &format!(
-                    "/marketplace/orders/{}",
-                    crate::endpoints::encode_path(order_id)
-                ),
-                None,
-                Some(request),
-                AuthLevel::User,
-            )
-            .await
+    pub fn edit_order(&self, order_id: &str, request: &EditOrderRequest) -> ApiFuture<Order> {
+        self.client.request_json::<Order, (), EditOrderRequest>(
+            Method::POST,
+            route!(
+                "marketplace.edit_order",
+                "/marketplace/orders/{order_id}",
+                order_id = crate::endpoints::encode_path(order_id)
+            ),
+            None,
+            Some(request),
+            AuthLevel::User,
+        )
     }
 
-    pub async fn get_order_messages(
+    pub fn get_order_messages(
         &self,
         order_id: &str,
         query: Option<&PaginationParams>,
-    ) -> Result<ApiResponse<OrderMessagesResponse>> {
+    ) -> ApiFuture<OrderMessagesResponse> {
         self.client
             .request_json::<OrderMessagesResponse, PaginationParams, ()>(
                 Method::GET,
-                &format!(
-                    "/marketplace/orders/{}/messages",
-                    crate::endpoints::encode_path(order_id)
+                route!(
+                    "marketplace.get_order_messages",
+                    "/marketplace/orders/{order_id}/messages",
+                    order_id = crate::endpoints::encode_path(order_id)
                 ),
                 query,
                 None,
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn add_order_message(
+    pub fn get_order_messages_paginated(
+        &self,
+        order_id: &str,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, OrderMessagesResponse> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "marketplace.get_order_messages",
+                "/marketplace/orders/{order_id}/messages",
+                order_id = crate::endpoints::encode_path(order_id)
+            ),
+            query,
+            AuthLevel::User,
+        )
+    }
+
+    pub fn add_order_message(
         &self,
         order_id: &str,
         request: &AddOrderMessageRequest,
-    ) -> Result<ApiResponse<OrderMessage>> {
+    ) -> ApiFuture<OrderMessage> {
         self.client
             .request_json::<OrderMessage, (), AddOrderMessageRequest>(
                 Method::POST,
-                &format!(
-                    "/marketplace/orders/{}/messages",
-                    crate::endpoints::encode_path(order_id)
+                route!(
+                    "marketplace.add_order_message",
+                    "/marketplace/orders/{order_id}/messages",
+                    order_id = crate::endpoints::encode_path(order_id)
                 ),
                 None,
                 Some(request),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn get_fee(
-        &self,
-        amount: f64,
-        currency: Option<Currency>,
-    ) -> Result<ApiResponse<Price>> {
-        let path = match currency {
-            Some(currency) => format!("/marketplace/fee/{:.2}/{}", amount, currency.as_code()),
-            None => format!("/marketplace/fee/{:.2}", amount),
+    pub fn get_fee(&self, amount: f64, currency: Option<Currency>) -> ApiFuture<Price> {
+        let amount = format!("{amount:.2}");
+        let route = match currency {
+            Some(currency) => route!(
+                "marketplace.get_fee",
+                "/marketplace/fee/{amount}/{currency}",
+                amount,
+                currency = currency.as_code()
+            ),
+            None => route!("marketplace.get_fee", "/marketplace/fee/{amount}", amount),
         };
 
         self.client
-            .request_json::<Price, (), ()>(Method::GET, &path, None, None, AuthLevel::None)
-            .await
+            .request_json::<Price, (), ()>(Method::GET, route, None, None, AuthLevel::None)
     }
 
-    pub async fn get_price_suggestions(
-        &self,
-        release_id: u64,
-    ) -> Result<ApiResponse<BTreeMap<String, Price>>> {
-        self.client
-            .request_json::<BTreeMap<String, Price>, (), ()>(
-                Method::GET,
-                &format!("/marketplace/price_suggestions/{release_id}"),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn get_price_suggestions(&self, release_id: u64) -> ApiFuture<BTreeMap<String, Price>> {
+        self.client.request_json::<BTreeMap<String, Price>, (), ()>(
+            Method::GET,
+            route!(
+                "marketplace.get_price_suggestions",
+                "/marketplace/price_suggestions/{release_id}",
+                release_id
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 
-    pub async fn get_release_stats(
+    pub fn get_release_stats(
         &self,
         release_id: u64,
         currency: Option<Currency>,
-    ) -> Result<ApiResponse<MarketplaceReleaseStats>> {
+    ) -> ApiFuture<MarketplaceReleaseStats> {
         #[derive(serde::Serialize)]
         struct CurrencyQuery {
             curr_abbr: Currency,
@@ -218,11 +243,14 @@ impl<'a> MarketplaceApi<'a> {
         self.client
             .request_json::<MarketplaceReleaseStats, CurrencyQuery, ()>(
                 Method::GET,
-                &format!("/marketplace/stats/{release_id}"),
+                route!(
+                    "marketplace.get_release_stats",
+                    "/marketplace/stats/{release_id}",
+                    release_id
+                ),
                 query.as_ref(),
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 }
@@ -4,6 +4,7 @@ pub mod collection;
 pub mod database;
 pub mod inventory;
 pub mod marketplace;
+pub mod raw;
 pub mod user;
 pub mod user_list;
 pub mod wantlist;
@@ -11,3 +12,43 @@ pub mod wantlist;
 pub(crate) fn encode_path(value: impl AsRef<str>) -> String {
     url::form_urlencoded::byte_serialize(value.as_ref().as_bytes()).collect()
 }
+
+/// Identifies the endpoint method behind a request, e.g. `database.get_release` with the
+/// template `/releases/{release_id}`.
+///
+/// It is available to middleware as a request extension, and tracing and metrics group calls
+/// by it rather than by raw URLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    pub name: &'static str,
+    pub template: &'static str,
+}
+
+/// An [`Endpoint`] together with the concrete path for one call.
+#[derive(Debug, Clone)]
+pub(crate) struct Route {
+    pub(crate) endpoint: Endpoint,
+    pub(crate) path: String,
+}
+
+/// Builds a [`Route`] from an endpoint name and a path template whose placeholders are filled
+/// from the listed arguments: `route!("user.get_profile", "/users/{username}", username = encode_path(username))`.
+macro_rules! route {
+    (@value $arg:ident) => {
+        $arg
+    };
+    (@value $arg:ident $value:expr) => {
+        $value
+    };
+    ($name:literal, $template:literal $(, $arg:ident $(= $value:expr)?)* $(,)?) => {
+        $crate::endpoints::Route {
+            endpoint: $crate::endpoints::Endpoint {
+                name: $name,
+                template: $template,
+            },
+            path: format!($template $(, $arg = $crate::endpoints::route!(@value $arg $($value)?))*),
+        }
+    };
+}
+
+pub(crate) use route;
@@ -0,0 +1,174 @@
+//! Untyped requests for endpoints and fields the crate does not model yet.
+
+use crate::auth::AuthLevel;
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::{Endpoint, Route};
+use crate::error::{DiscogsError, Result};
+use crate::transport::{TransportError, TransportErrorKind};
+use bytes::Bytes;
+use http::Method;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+
+/// A request to an arbitrary path, built with [`DiscogsClient::raw`].
+///
+/// It goes through the same auth check, middleware, retries, rate limiting and error handling as
+/// the typed endpoints. Requests declare no required auth level unless [`auth`](Self::auth) is
+/// called, so a missing credential surfaces as the API's `401` rather than
+/// [`DiscogsError::AuthRequired`].
+pub struct RawRequest<'a> {
+    client: Cow<'a, DiscogsClient>,
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Option<serde_json::Value>,
+    required_auth: AuthLevel,
+    endpoint: Endpoint,
+    error: Option<DiscogsError>,
+}
+
+impl<'a> RawRequest<'a> {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>, method: Method, path: String) -> Self {
+        Self {
+            client,
+            method,
+            path,
+            query: Vec::new(),
+            body: None,
+            required_auth: AuthLevel::None,
+            endpoint: Endpoint {
+                name: "raw",
+                template: "{path}",
+            },
+            error: None,
+        }
+    }
+
+    /// Applies `options` to this request.
+    pub fn with_options(mut self, options: RequestOptions) -> Self {
+        self.client = Cow::Owned(self.client.with_options(options));
+        self
+    }
+
+    /// A request owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> RawRequest<'static> {
+        RawRequest {
+            client: Cow::Owned(self.client.into_owned()),
+            method: self.method,
+            path: self.path,
+            query: self.query,
+            body: self.body,
+            required_auth: self.required_auth,
+            endpoint: self.endpoint,
+            error: self.error,
+        }
+    }
+
+    /// Appends one query parameter.
+    pub fn param(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.query.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// Appends the fields of `query`, e.g. one of the crate's query structs or a map.
+    pub fn query<Q: Serialize + ?Sized>(mut self, query: &Q) -> Self {
+        match serde_urlencoded::to_string(query) {
+            Ok(encoded) => self
+                .query
+                .extend(url::form_urlencoded::parse(encoded.as_bytes()).into_owned()),
+            Err(error) => self.fail(TransportError::with_source(
+                TransportErrorKind::InvalidRequest,
+                error,
+            )),
+        }
+        self
+    }
+
+    /// Sends `body` as JSON.
+    pub fn json<B: Serialize + ?Sized>(mut self, body: &B) -> Self {
+        match serde_json::to_value(body) {
+            Ok(body) => self.body = Some(body),
+            Err(error) => self.fail(error),
+        }
+        self
+    }
+
+    /// The auth level the endpoint needs; calls from a client with less fail with
+    /// [`DiscogsError::AuthRequired`] before reaching the network. Defaults to
+    /// [`AuthLevel::None`].
+    pub fn auth(mut self, required: AuthLevel) -> Self {
+        self.required_auth = required;
+        self
+    }
+
+    /// Names the call for middleware, tracing and metrics. Defaults to `raw` with the template
+    /// `{path}`.
+    pub fn endpoint(mut self, name: &'static str, template: &'static str) -> Self {
+        self.endpoint = Endpoint { name, template };
+        self
+    }
+
+    /// Sends the request and deserializes the response body into `T`.
+    pub fn send<T: DeserializeOwned + 'static>(mut self) -> ApiFuture<T> {
+        if let Err(error) = self.check() {
+            return Box::pin(async move { Err(error) });
+        }
+        self.client.request_json(
+            self.method.clone(),
+            self.route(),
+            Some(&self.query),
+            self.body.as_ref(),
+            self.required_auth,
+        )
+    }
+
+    /// Sends the request and returns the response body as untyped JSON.
+    pub fn send_value(self) -> ApiFuture<serde_json::Value> {
+        self.send()
+    }
+
+    /// Sends the request and ignores the response body, e.g. for `204 No Content`.
+    pub fn send_empty(mut self) -> ApiFuture<()> {
+        if let Err(error) = self.check() {
+            return Box::pin(async move { Err(error) });
+        }
+        self.client.request_empty(
+            self.method.clone(),
+            self.route(),
+            Some(&self.query),
+            self.body.as_ref(),
+            self.required_auth,
+        )
+    }
+
+    /// Sends the request and returns the response body unparsed.
+    pub fn send_bytes(mut self) -> ApiFuture<Bytes> {
+        if let Err(error) = self.check() {
+            return Box::pin(async move { Err(error) });
+        }
+        self.client.request_bytes(
+            self.method.clone(),
+            self.route(),
+            Some(&self.query),
+            self.body.as_ref(),
+            self.required_auth,
+        )
+    }
+
+    fn fail(&mut self, error: impl Into<DiscogsError>) {
+        // Keep the first error; it is reported when the request is sent.
+        self.error.get_or_insert(error.into());
+    }
+
+    fn check(&mut self) -> Result<()> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+
+    fn route(&self) -> Route {
+        Route {
+            endpoint: self.endpoint,
+            path: self.path.clone(),
+        }
+    }
+}
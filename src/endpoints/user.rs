@@ -5,139 +5,227 @@
 //! - <https://www.discogs.com/developers/#page:user-lists>
 
 use crate::auth::AuthLevel;
-use crate::client::DiscogsClient;
-use crate::endpoints::{collection::CollectionApi, user_list::ListApi, wantlist::WantlistApi};
-use crate::error::Result;
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::{
+    collection::CollectionApi, route, user_list::ListApi, wantlist::WantlistApi,
+};
 use crate::models::{
-    ApiResponse, EditProfileRequest, Identity, PaginationParams, UserContributions,
-    UserContributionsQuery, UserInventory, UserInventoryQuery, UserLists, UserProfile,
-    UserSubmissions,
+    EditProfileRequest, Identity, PaginationParams, UserContributions, UserContributionsQuery,
+    UserInventory, UserInventoryQuery, UserLists, UserProfile, UserSubmissions,
 };
-use reqwest::Method;
+use crate::pagination::Paginator;
+use http::Method;
+use std::borrow::Cow;
 
+#[derive(Clone)]
 pub struct UserApi<'a> {
-    client: &'a DiscogsClient,
+    client: Cow<'a, DiscogsClient>,
 }
 
 impl<'a> UserApi<'a> {
-    pub(crate) fn new(client: &'a DiscogsClient) -> Self {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>) -> Self {
         Self { client }
     }
 
-    pub async fn get_profile(&self, username: &str) -> Result<ApiResponse<UserProfile>> {
-        self.client
-            .request_json::<UserProfile, (), ()>(
-                Method::GET,
-                &format!("/users/{}", crate::endpoints::encode_path(username)),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    /// The same endpoints with `options` applied to every call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self::new(Cow::Owned(self.client.with_options(options)))
     }
 
-    pub async fn edit_profile(
+    /// A handle owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> UserApi<'static> {
+        UserApi::new(Cow::Owned(self.client.into_owned()))
+    }
+
+    pub fn get_profile(&self, username: &str) -> ApiFuture<UserProfile> {
+        self.client.request_json::<UserProfile, (), ()>(
+            Method::GET,
+            route!(
+                "user.get_profile",
+                "/users/{username}",
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn edit_profile(
         &self,
         username: &str,
         request: &EditProfileRequest,
-    ) -> Result<ApiResponse<UserProfile>> {
+    ) -> ApiFuture<UserProfile> {
         self.client
             .request_json::<UserProfile, (), EditProfileRequest>(
                 Method::POST,
-                &format!("/users/{}", crate::endpoints::encode_path(username)),
+                route!(
+                    "user.edit_profile",
+                    "/users/{username}",
+                    username = crate::endpoints::encode_path(username)
+                ),
                 None,
                 Some(request),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn get_inventory(
+    pub fn get_inventory(
         &self,
         username: &str,
         query: Option<&UserInventoryQuery>,
-    ) -> Result<ApiResponse<UserInventory>> {
+    ) -> ApiFuture<UserInventory> {
         self.client
             .request_json::<UserInventory, UserInventoryQuery, ()>(
                 Method::GET,
-                &format!(
-                    "/users/{}/inventory",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "user.get_inventory",
+                    "/users/{username}/inventory",
+                    username = crate::endpoints::encode_path(username)
                 ),
                 query,
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 
-    pub async fn get_identity(&self) -> Result<ApiResponse<Identity>> {
-        self.client.get_identity().await
+    pub fn get_inventory_paginated(
+        &self,
+        username: &str,
+        query: Option<&UserInventoryQuery>,
+    ) -> Paginator<'a, UserInventory> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "user.get_inventory",
+                "/users/{username}/inventory",
+                username = crate::endpoints::encode_path(username)
+            ),
+            query,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn get_identity(&self) -> ApiFuture<Identity> {
+        self.client.get_identity()
     }
 
     pub fn collection(&self) -> CollectionApi<'a> {
-        CollectionApi::new(self.client)
+        CollectionApi::new(self.client.clone())
     }
 
     pub fn wantlist(&self) -> WantlistApi<'a> {
-        WantlistApi::new(self.client)
+        WantlistApi::new(self.client.clone())
     }
 
     pub fn list(&self) -> ListApi<'a> {
-        ListApi::new(self.client)
+        ListApi::new(self.client.clone())
     }
 
-    pub async fn get_contributions(
+    pub fn get_contributions(
         &self,
         username: &str,
         query: Option<&UserContributionsQuery>,
-    ) -> Result<ApiResponse<UserContributions>> {
+    ) -> ApiFuture<UserContributions> {
         self.client
             .request_json::<UserContributions, UserContributionsQuery, ()>(
                 Method::GET,
-                &format!(
-                    "/users/{}/contributions",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "user.get_contributions",
+                    "/users/{username}/contributions",
+                    username = crate::endpoints::encode_path(username)
                 ),
                 query,
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 
-    pub async fn get_submissions(
+    pub fn get_contributions_paginated(
+        &self,
+        username: &str,
+        query: Option<&UserContributionsQuery>,
+    ) -> Paginator<'a, UserContributions> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "user.get_contributions",
+                "/users/{username}/contributions",
+                username = crate::endpoints::encode_path(username)
+            ),
+            query,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn get_submissions(
         &self,
         username: &str,
         query: Option<&PaginationParams>,
-    ) -> Result<ApiResponse<UserSubmissions>> {
+    ) -> ApiFuture<UserSubmissions> {
         self.client
             .request_json::<UserSubmissions, PaginationParams, ()>(
                 Method::GET,
-                &format!(
-                    "/users/{}/submissions",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "user.get_submissions",
+                    "/users/{username}/submissions",
+                    username = crate::endpoints::encode_path(username)
                 ),
                 query,
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 
-    pub async fn get_lists(
+    pub fn get_submissions_paginated(
         &self,
         username: &str,
         query: Option<&PaginationParams>,
-    ) -> Result<ApiResponse<UserLists>> {
-        self.client
-            .request_json::<UserLists, PaginationParams, ()>(
-                Method::GET,
-                &format!("/users/{}/lists", crate::endpoints::encode_path(username)),
-                query,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    ) -> Paginator<'a, UserSubmissions> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "user.get_submissions",
+                "/users/{username}/submissions",
+                username = crate::endpoints::encode_path(username)
+            ),
+            query,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn get_lists(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> ApiFuture<UserLists> {
+        self.client.request_json::<UserLists, PaginationParams, ()>(
+            Method::GET,
+            route!(
+                "user.get_lists",
+                "/users/{username}/lists",
+                username = crate::endpoints::encode_path(username)
+            ),
+            query,
+            None,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn get_lists_paginated(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, UserLists> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "user.get_lists",
+                "/users/{username}/lists",
+                username = crate::endpoints::encode_path(username)
+            ),
+            query,
+            AuthLevel::None,
+        )
     }
 }
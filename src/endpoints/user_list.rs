@@ -3,29 +3,39 @@
 //! Reference: <https://www.discogs.com/developers/#page:user-lists>
 
 use crate::auth::AuthLevel;
-use crate::client::DiscogsClient;
-use crate::error::Result;
-use crate::models::{ApiResponse, DiscogsList};
-use reqwest::Method;
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::route;
+use crate::models::DiscogsList;
+use http::Method;
+use std::borrow::Cow;
 
+#[derive(Clone)]
 pub struct ListApi<'a> {
-    client: &'a DiscogsClient,
+    client: Cow<'a, DiscogsClient>,
 }
 
 impl<'a> ListApi<'a> {
-    pub(crate) fn new(client: &'a DiscogsClient) -> Self {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>) -> Self {
         Self { client }
     }
 
-    pub async fn get_items(&self, list_id: u64) -> Result<ApiResponse<DiscogsList>> {
-        self.client
-            .request_json::<DiscogsList, (), ()>(
-                Method::GET,
-                &format!("/lists/{list_id}"),
-                None,
-                None,
-                AuthLevel::None,
-            )
-            .await
+    /// The same endpoints with `options` applied to every call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self::new(Cow::Owned(self.client.with_options(options)))
+    }
+
+    /// A handle owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> ListApi<'static> {
+        ListApi::new(Cow::Owned(self.client.into_owned()))
+    }
+
+    pub fn get_items(&self, list_id: u64) -> ApiFuture<DiscogsList> {
+        self.client.request_json::<DiscogsList, (), ()>(
+            Method::GET,
+            route!("list.get_items", "/lists/{list_id}", list_id),
+            None,
+            None,
+            AuthLevel::None,
+        )
     }
 }
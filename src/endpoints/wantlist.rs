@@ -3,90 +3,123 @@
 //! Reference: <https://www.discogs.com/developers/#page:user-wantlist>
 
 use crate::auth::AuthLevel;
-use crate::client::DiscogsClient;
-use crate::error::Result;
-use crate::models::{
-    ApiResponse, PaginationParams, WantlistEntries, WantlistEntry, WantlistUpdateRequest,
-};
-use reqwest::Method;
+use crate::client::{ApiFuture, DiscogsClient, RequestOptions};
+use crate::endpoints::route;
+use crate::models::{PaginationParams, WantlistEntries, WantlistEntry, WantlistUpdateRequest};
+use crate::pagination::Paginator;
+use http::Method;
+use std::borrow::Cow;
 
+#[derive(Clone)]
 pub struct WantlistApi<'a> {
-    client: &'a DiscogsClient,
+    client: Cow<'a, DiscogsClient>,
 }
 
 impl<'a> WantlistApi<'a> {
-    pub(crate) fn new(client: &'a DiscogsClient) -> Self {
+    pub(crate) fn new(client: Cow<'a, DiscogsClient>) -> Self {
         Self { client }
     }
 
-    pub async fn get_releases(
+    /// The same endpoints with `options` applied to every call.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self::new(Cow::Owned(self.client.with_options(options)))
+    }
+
+    /// A handle owning a clone of the client, so it can be stored or moved into a spawned task.
+    pub fn into_owned(self) -> WantlistApi<'static> {
+        WantlistApi::new(Cow::Owned(self.client.into_owned()))
+    }
+
+    pub fn get_releases(
         &self,
         username: &str,
         query: Option<&PaginationParams>,
-    ) -> Result<ApiResponse<WantlistEntries>> {
+    ) -> ApiFuture<WantlistEntries> {
         self.client
             .request_json::<WantlistEntries, PaginationParams, ()>(
                 Method::GET,
-                &format!("/users/{}/wants", crate::endpoints::encode_path(username)),
+                route!(
+                    "wantlist.get_releases",
+                    "/users/{username}/wants",
+                    username = crate::endpoints::encode_path(username)
+                ),
                 query,
                 None,
                 AuthLevel::None,
             )
-            .await
     }
 
-    pub async fn add_release(
+    pub fn get_releases_paginated(
+        &self,
+        username: &str,
+        query: Option<&PaginationParams>,
+    ) -> Paginator<'a, WantlistEntries> {
+        Paginator::new(
+            &self.client,
+            route!(
+                "wantlist.get_releases",
+                "/users/{username}/wants",
+                username = crate::endpoints::encode_path(username)
+            ),
+            query,
+            AuthLevel::None,
+        )
+    }
+
+    pub fn add_release(
         &self,
         username: &str,
         release_id: u64,
         request: &WantlistUpdateRequest,
-    ) -> Result<ApiResponse<WantlistEntry>> {
+    ) -> ApiFuture<WantlistEntry> {
         self.client
             .request_json::<WantlistEntry, (), WantlistUpdateRequest>(
                 Method::PUT,
-                &format!(
-                    "/users/{}/wants/{release_id}",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "wantlist.add_release",
+                    "/users/{username}/wants/{release_id}",
+                    release_id,
+                    username = crate::endpoints::encode_path(username)
                 ),
                 None,
                 Some(request),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn edit_notes(
+    pub fn edit_notes(
         &self,
         username: &str,
         release_id: u64,
         request: &WantlistUpdateRequest,
-    ) -> Result<ApiResponse<WantlistEntry>> {
+    ) -> ApiFuture<WantlistEntry> {
         self.client
             .request_json::<WantlistEntry, (), WantlistUpdateRequest>(
                 Method::POST,
-                &format!(
-                    "/users/{}/wants/{release_id}",
-                    crate::endpoints::encode_path(username)
+                route!(
+                    "wantlist.edit_notes",
+                    "/users/{username}/wants/{release_id}",
+                    release_id,
+                    username = crate::endpoints::encode_path(username)
                 ),
                 None,
                 Some(request),
                 AuthLevel::User,
             )
-            .await
     }
 
-    pub async fn remove_release(&self, username: &str, release_id: u64) -> Result<ApiResponse<()>> {
-        self.client
-            .request_empty::<(), ()>(
-                Method::DELETE,
-                &format!(
-                    "/users/{}/wants/{release_id}",
-                    crate::endpoints::encode_path(username)
-                ),
-                None,
-                None,
-                AuthLevel::User,
-            )
-            .await
+    pub fn remove_release(&self, username: &str, release_id: u64) -> ApiFuture<()> {
+        self.client.request_empty::<(), ()>(
+            Method::DELETE,
+            route!(
+                "wantlist.remove_release",
+                "/users/{username}/wants/{release_id}",
+                release_id,
+                username = crate::endpoints::encode_path(username)
+            ),
+            None,
+            None,
+            AuthLevel::User,
+        )
     }
 }
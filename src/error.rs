@@ -1,4 +1,8 @@
-use reqwest::StatusCode;
+use crate::models::RateLimit;
+use crate::transport::{HttpResponse, TransportError, TransportErrorKind};
+use http::{Method, StatusCode};
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, DiscogsError>;
@@ -9,17 +13,420 @@ pub enum DiscogsError {
     AuthRequired {
         required: crate::auth::AuthLevel,
         current: crate::auth::AuthLevel,
+        /// The rejected call, when the check ran for one.
+        context: Option<Box<ErrorContext>>,
     },
 
-    #[error("http error {status}: {message}")]
-    Http { status: StatusCode, message: String },
+    /// `404 Not Found`, e.g. an unknown release id.
+    #[error("not found: {0}")]
+    NotFound(ApiError),
 
+    /// `401 Unauthorized`: missing or rejected credentials.
+    #[error("unauthorized: {0}")]
+    Unauthorized(ApiError),
+
+    /// `403 Forbidden`: the credentials are valid but lack access to the resource.
+    #[error("forbidden: {0}")]
+    Forbidden(ApiError),
+
+    /// `429 Too Many Requests`.
+    #[error("rate limited: {error}")]
+    RateLimited {
+        error: ApiError,
+        /// Parsed `Retry-After` header, when Discogs sent one.
+        retry_after: Option<Duration>,
+    },
+
+    /// `422 Unprocessable Entity` with the field details Discogs reported.
+    #[error("validation failed: {error}")]
+    Validation {
+        error: ApiError,
+        details: Vec<ValidationDetail>,
+    },
+
+    /// Any `5xx` status.
+    #[error("server error: {0}")]
+    ServerError(ApiError),
+
+    /// Any other non-success status.
+    #[error("http error {0}")]
+    Http(ApiError),
+
+    #[error("transport error: {0}")]
+    Transport(#[from] TransportError),
+
+    #[cfg(feature = "reqwest")]
     #[error("request failed: {0}")]
     Request(#[from] reqwest::Error),
 
-    #[error("json parse failed: {0}")]
-    Json(#[from] serde_json::Error),
+    #[error("json parse failed: {source}")]
+    Json {
+        source: serde_json::Error,
+        /// The request whose body failed to decode or encode, when the error came from a call.
+        context: Option<Box<ErrorContext>>,
+    },
 
     #[error("invalid OAuth response: {0}")]
     InvalidOAuthResponse(String),
+
+    #[cfg(feature = "credentials")]
+    #[error("credential store error: {0}")]
+    CredentialStore(std::io::Error),
+}
+
+impl From<serde_json::Error> for DiscogsError {
+    fn from(source: serde_json::Error) -> Self {
+        Self::Json {
+            source,
+            context: None,
+        }
+    }
+}
+
+/// A non-success response from Discogs.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    /// The `message` field of a JSON error body, otherwise a short excerpt of the body.
+    pub message: String,
+    /// The raw response body, preserved even when it is not JSON (e.g. CDN error pages).
+    pub body: String,
+    pub context: Option<Box<ErrorContext>>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.status, self.message)?;
+        if let Some(context) = &self.context {
+            write!(f, " ({context})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Which request failed and the rate-limit state at the time.
+#[derive(Debug, Clone)]
+pub struct ErrorContext {
+    pub method: Method,
+    /// Request URL with credential-like query parameters replaced by `REDACTED`.
+    pub url: String,
+    /// Requests actually sent, retries included. Zero when a middleware layer answered.
+    pub attempts: u32,
+    pub elapsed: Duration,
+    /// Rate-limit headers of the failing response, or the latest ones the client saw.
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}, {} attempt(s) in {:?}",
+            self.method, self.url, self.attempts, self.elapsed
+        )?;
+        if let Some(rate_limit) = &self.rate_limit {
+            write!(
+                f,
+                ", rate limit {}/{} remaining",
+                rate_limit.remaining, rate_limit.limit
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// One entry of the `detail` list in a `422` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationDetail {
+    /// Dotted path of the offending field, when Discogs reported one.
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl DiscogsError {
+    /// Maps a non-success response to the matching variant.
+    pub(crate) fn from_response(response: &HttpResponse) -> Self {
+        let status = response.status;
+        let body = String::from_utf8_lossy(&response.body).into_owned();
+        let json = serde_json::from_slice::<serde_json::Value>(&response.body).ok();
+        let message = match &json {
+            Some(json) => json
+                .get("message")
+                .and_then(|value| value.as_str())
+                .map(ToOwned::to_owned)
+                .unwrap_or_else(|| json.to_string()),
+            None => excerpt(&body, status),
+        };
+        let error = ApiError {
+            status,
+            message,
+            body,
+            context: None,
+        };
+
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound(error),
+            StatusCode::UNAUTHORIZED => Self::Unauthorized(error),
+            StatusCode::FORBIDDEN => Self::Forbidden(error),
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited {
+                error,
+                retry_after: crate::retry::parse_retry_after(&response.headers),
+            },
+            StatusCode::UNPROCESSABLE_ENTITY => Self::Validation {
+                error,
+                details: json.as_ref().map(validation_details).unwrap_or_default(),
+            },
+            status if status.is_server_error() => Self::ServerError(error),
+            _ => Self::Http(error),
+        }
+    }
+
+    /// The response details for errors that came from a Discogs response.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::NotFound(error)
+            | Self::Unauthorized(error)
+            | Self::Forbidden(error)
+            | Self::RateLimited { error, .. }
+            | Self::Validation { error, .. }
+            | Self::ServerError(error)
+            | Self::Http(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Method, URL, attempts, elapsed time and rate-limit state of the failed request.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::Transport(error) => error.context(),
+            Self::AuthRequired { context, .. } | Self::Json { context, .. } => context.as_deref(),
+            _ => self.api_error()?.context.as_deref(),
+        }
+    }
+
+    pub(crate) fn with_context(mut self, context: ErrorContext) -> Self {
+        match &mut self {
+            Self::NotFound(error)
+            | Self::Unauthorized(error)
+            | Self::Forbidden(error)
+            | Self::RateLimited { error, .. }
+            | Self::Validation { error, .. }
+            | Self::ServerError(error)
+            | Self::Http(error) => error.context = Some(Box::new(context)),
+            Self::Transport(error) => error.set_context(context),
+            Self::AuthRequired { context: slot, .. } | Self::Json { context: slot, .. } => {
+                *slot = Some(Box::new(context))
+            }
+            _ => {}
+        }
+        self
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        self.api_error().map(|error| error.status)
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(_))
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Self::RateLimited { .. })
+    }
+
+    /// Whether trying the same request again later may succeed: rate limiting, server errors
+    /// and transport failures other than malformed requests.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::ServerError(_) => true,
+            Self::Transport(error) => error.kind() != TransportErrorKind::InvalidRequest,
+            #[cfg(feature = "reqwest")]
+            Self::Request(error) => error.is_timeout() || error.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// How long Discogs asked to wait before retrying, if it said so.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+const SENSITIVE_QUERY_KEYS: [&str; 4] = ["token", "key", "secret", "signature"];
+
+/// Matches the names in [`SENSITIVE_QUERY_KEYS`] on their own or as an `_`-separated suffix
+/// (`oauth_token`, `consumer_key`), so parameters like `keyword` stay readable.
+pub(crate) fn is_sensitive_query_key(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    SENSITIVE_QUERY_KEYS.iter().any(|key| {
+        lower
+            .strip_suffix(key)
+            .is_some_and(|rest| rest.is_empty() || rest.ends_with('_'))
+    })
+}
+
+/// Replaces the values of credential-like query parameters so URLs can be logged.
+pub(crate) fn redact_url(url: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    if parsed.query().is_none() {
+        return url.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(name, value)| {
+            if is_sensitive_query_key(&name) {
+                (name.into_owned(), "REDACTED".to_string())
+            } else {
+                (name.into_owned(), value.into_owned())
+            }
+        })
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    parsed.to_string()
+}
+
+fn excerpt(body: &str, status: StatusCode) -> String {
+    let text = body.trim();
+    if text.is_empty() {
+        return status
+            .canonical_reason()
+            .unwrap_or("unknown error")
+            .to_string();
+    }
+    const MAX: usize = 200;
+    match text.char_indices().nth(MAX) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn validation_details(json: &serde_json::Value) -> Vec<ValidationDetail> {
+    let Some(entries) = json.get("detail").and_then(|value| value.as_array()) else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            if let Some(message) = entry.as_str() {
+                return Some(ValidationDetail {
+                    field: None,
+                    message: message.to_string(),
+                });
+            }
+            let message = entry.get("msg").and_then(|value| value.as_str())?;
+            let field = entry.get("loc").and_then(|loc| loc.as_array()).map(|loc| {
+                loc.iter()
+                    .map(|part| match part {
+                        serde_json::Value::String(part) => part.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(".")
+            });
+            Some(ValidationDetail {
+                field,
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiscogsError, ValidationDetail, redact_url};
+    use crate::transport::HttpResponse;
+    use bytes::Bytes;
+    use http::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use http::{Extensions, StatusCode};
+    use std::time::Duration;
+
+    fn response(status: StatusCode, body: &'static str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(body.as_bytes()),
+            extensions: Extensions::new(),
+        }
+    }
+
+    #[test]
+    fn non_json_bodies_are_preserved() {
+        let error = DiscogsError::from_response(&response(
+            StatusCode::BAD_GATEWAY,
+            "<html>502 Bad Gateway</html>",
+        ));
+
+        assert!(matches!(error, DiscogsError::ServerError(_)));
+        assert!(error.is_retryable());
+        let api = error.api_error().expect("api error");
+        assert_eq!(api.body, "<html>502 Bad Gateway</html>");
+        assert_eq!(api.message, "<html>502 Bad Gateway</html>");
+    }
+
+    #[test]
+    fn validation_details_are_parsed() {
+        let error = DiscogsError::from_response(&response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            r#"{"message":"Invalid listing","detail":[{"loc":["body","price"],"msg":"must be positive"}]}"#,
+        ));
+
+        match error {
+            DiscogsError::Validation { error, details } => {
+                assert_eq!(error.message, "Invalid listing");
+                assert_eq!(
+                    details,
+                    vec![ValidationDetail {
+                        field: Some("body.price".to_string()),
+                        message: "must be positive".to_string(),
+                    }]
+                );
+            }
+            other => panic!("expected Validation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn rate_limited_carries_retry_hint() {
+        let mut rate_limited = response(StatusCode::TOO_MANY_REQUESTS, "");
+        rate_limited
+            .headers
+            .insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        let error = DiscogsError::from_response(&rate_limited);
+
+        assert!(error.is_rate_limited());
+        assert!(error.is_retryable());
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(30)));
+        assert_eq!(
+            error.api_error().map(|api| api.message.as_str()),
+            Some("Too Many Requests")
+        );
+        assert!(!DiscogsError::from_response(&response(StatusCode::NOT_FOUND, "")).is_retryable());
+    }
+
+    #[test]
+    fn redacted_urls_hide_credentials() {
+        assert_eq!(
+            redact_url(
+                "https://api.discogs.com/database/search?q=nirvana&token=abc&oauth_signature=x"
+            ),
+            "https://api.discogs.com/database/search?q=nirvana&token=REDACTED&oauth_signature=REDACTED"
+        );
+        assert_eq!(
+            redact_url(
+                "https://api.discogs.com/database/search?keyword=punk&consumer_key=k&oauth_token_secret=s"
+            ),
+            "https://api.discogs.com/database/search?keyword=punk&consumer_key=REDACTED&oauth_token_secret=REDACTED"
+        );
+        assert_eq!(
+            redact_url("https://api.discogs.com/releases/1"),
+            "https://api.discogs.com/releases/1"
+        );
+    }
 }
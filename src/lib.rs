@@ -15,14 +15,67 @@
 //! - <https://www.discogs.com/developers/#page:authentication>
 
 mod auth;
+mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cache;
+#[cfg(feature = "testing")]
+mod cassette;
+#[cfg(feature = "testing")]
+mod chaos;
 mod client;
+mod coalesce;
+#[cfg(feature = "credentials")]
+mod credentials;
 mod endpoints;
 mod error;
+mod links;
+mod middleware;
 mod models;
 mod oauth;
+mod oauth_flow;
+mod pagination;
+mod rate_limiter;
+mod retry;
+mod secret;
+mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod transport;
 
 pub use auth::{Auth, AuthLevel, OutputFormat};
-pub use client::{DiscogsClient, DiscogsClientBuilder, RetryConfig};
-pub use error::{DiscogsError, Result};
+pub use batch::{BatchExecutor, BatchProgress, BatchReport, CancelToken};
+pub use cache::{CacheConfig, CacheStore, CachedResponse, DiskCacheStore, MemoryCacheStore};
+#[cfg(feature = "testing")]
+pub use cassette::{CassetteConfig, CassetteMode, MatchRules};
+#[cfg(feature = "testing")]
+pub use chaos::{ChaosConfig, Fault};
+pub use client::{ApiFuture, DiscogsClient, DiscogsClientBuilder, RequestOptions};
+#[cfg(feature = "credentials")]
+pub use credentials::{
+    ClientRegistry, CredentialStore, EncryptedFileStore, EnvCredentialStore, TomlCredentialStore,
+};
+pub use endpoints::Endpoint;
+pub use endpoints::collection::CollectionApi;
+pub use endpoints::database::DatabaseApi;
+pub use endpoints::inventory::InventoryApi;
+pub use endpoints::marketplace::MarketplaceApi;
+pub use endpoints::user::UserApi;
+pub use endpoints::user_list::ListApi;
+pub use endpoints::wantlist::WantlistApi;
+pub use error::{ApiError, DiscogsError, ErrorContext, Result, ValidationDetail};
+pub use links::{Link, Resource, ResourceLink};
+pub use middleware::{FnMiddleware, Middleware, Next, RequiredAuth, middleware_fn};
 pub use models::*;
-pub use oauth::{AccessToken, DiscogsOAuthClient, RequestToken};
+pub use oauth::{
+    AccessToken, Clock, DiscogsOAuthClient, NonceSource, RandomNonce, RequestToken,
+    SignatureMethod, SystemClock,
+};
+pub use oauth_flow::{LoopbackAuthorization, OAuthFlow, OutOfBandAuthorization};
+pub use pagination::{Page, PageStream, Paginated, Paginator};
+pub use rate_limiter::RateLimiterConfig;
+pub use retry::{NoRetry, RetryConfig, RetryContext, RetryPolicy, RetryTrigger};
+pub use secret::Secret;
+#[cfg(feature = "reqwest")]
+pub use transport::ReqwestTransport;
+pub use transport::{HttpRequest, HttpResponse, HttpTransport, TransportError, TransportErrorKind};
@@ -0,0 +1,219 @@
+//! Typed `resource_url` links that [`DiscogsClient::follow`](crate::DiscogsClient::follow)
+//! resolves to the model they point at.
+
+use crate::auth::AuthLevel;
+use crate::endpoints::Endpoint;
+use crate::models::{Artist, DiscogsList, Label, Listing, Master, Order, Release, UserProfile};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// A model that can be fetched from its own `resource_url`.
+pub trait Resource: DeserializeOwned {
+    /// The endpoint method that returns this model, used to name followed calls in middleware,
+    /// tracing and metrics.
+    const ENDPOINT: Endpoint;
+    /// The auth level that endpoint needs.
+    const REQUIRED_AUTH: AuthLevel;
+}
+
+macro_rules! resource {
+    ($model:ty, $name:literal, $template:literal, $auth:ident) => {
+        impl Resource for $model {
+            const ENDPOINT: Endpoint = Endpoint {
+                name: $name,
+                template: $template,
+            };
+            const REQUIRED_AUTH: AuthLevel = AuthLevel::$auth;
+        }
+    };
+}
+
+resource!(Artist, "database.get_artist", "/artists/{artist_id}", None);
+resource!(
+    Release,
+    "database.get_release",
+    "/releases/{release_id}",
+    None
+);
+resource!(Master, "database.get_master", "/masters/{master_id}", None);
+resource!(Label, "database.get_label", "/labels/{label_id}", None);
+resource!(
+    Listing,
+    "marketplace.get_listing",
+    "/marketplace/listings/{listing_id}",
+    None
+);
+resource!(
+    Order,
+    "marketplace.get_order",
+    "/marketplace/orders/{order_id}",
+    User
+);
+resource!(UserProfile, "user.get_profile", "/users/{username}", None);
+resource!(DiscogsList, "list.get_items", "/lists/{list_id}", None);
+
+/// An API URL known to return a `T`. It (de)serializes as the plain URL string.
+pub struct Link<T> {
+    url: String,
+    resource: PhantomData<fn() -> T>,
+}
+
+impl<T> Link<T> {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            resource: PhantomData,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.url
+    }
+
+    pub fn into_string(self) -> String {
+        self.url
+    }
+
+    /// The trailing path segment, e.g. the id in `/artists/1`.
+    pub fn id(&self) -> &str {
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        path.trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+// Manual impls so that `T` itself needs none of these traits.
+impl<T> Clone for Link<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.url.clone())
+    }
+}
+
+impl<T> fmt::Debug for Link<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.url, f)
+    }
+}
+
+impl<T> fmt::Display for Link<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+impl<T> PartialEq for Link<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url
+    }
+}
+
+impl<T> Eq for Link<T> {}
+
+impl<T> Hash for Link<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.url.hash(state);
+    }
+}
+
+impl<T> PartialEq<str> for Link<T> {
+    fn eq(&self, other: &str) -> bool {
+        self.url == other
+    }
+}
+
+impl<T> PartialEq<&str> for Link<T> {
+    fn eq(&self, other: &&str) -> bool {
+        self.url == *other
+    }
+}
+
+impl<T> AsRef<str> for Link<T> {
+    fn as_ref(&self) -> &str {
+        &self.url
+    }
+}
+
+impl<T> From<String> for Link<T> {
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+impl<T> From<&str> for Link<T> {
+    fn from(url: &str) -> Self {
+        Self::new(url)
+    }
+}
+
+impl<T> Serialize for Link<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.url)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Link<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// A link whose target depends on a `type` field, as in search results and artist or label
+/// release listings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceLink {
+    Artist(Link<Artist>),
+    Release(Link<Release>),
+    Master(Link<Master>),
+    Label(Link<Label>),
+}
+
+impl ResourceLink {
+    /// Pairs `url` with a Discogs `type` value (`artist`, `release`, `master` or `label`).
+    pub fn new(resource_type: &str, url: &str) -> Option<Self> {
+        match resource_type {
+            "artist" => Some(Self::Artist(Link::new(url))),
+            "release" => Some(Self::Release(Link::new(url))),
+            "master" => Some(Self::Master(Link::new(url))),
+            "label" => Some(Self::Label(Link::new(url))),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Link, ResourceLink};
+    use crate::models::{Artist, ArtistCredit};
+
+    #[test]
+    fn links_round_trip_as_plain_urls() {
+        let credit: ArtistCredit = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "The Persuader",
+            "resource_url": "https://api.discogs.com/artists/1"
+        }))
+        .expect("credit");
+        assert_eq!(credit.resource_url, "https://api.discogs.com/artists/1");
+        assert_eq!(credit.resource_url.id(), "1");
+        assert_eq!(
+            serde_json::to_value(&credit).expect("serialize")["resource_url"],
+            "https://api.discogs.com/artists/1"
+        );
+
+        assert_eq!(
+            ResourceLink::new("artist", "https://api.discogs.com/artists/1"),
+            Some(ResourceLink::Artist(Link::<Artist>::new(
+                "https://api.discogs.com/artists/1"
+            )))
+        );
+        assert_eq!(
+            ResourceLink::new("user", "https://api.discogs.com/users/a"),
+            None
+        );
+    }
+}
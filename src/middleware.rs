@@ -0,0 +1,211 @@
+//! Request/response middleware around the transport.
+//!
+//! Every call runs through one ordered chain:
+//!
+//! 1. the built-in auth check, which rejects calls whose [`RequiredAuth`] exceeds the client's
+//!    credentials before anything touches the network,
+//! 2. user layers registered with [`DiscogsClientBuilder::layer`](crate::DiscogsClientBuilder::layer),
+//!    in registration order,
+//! 3. the coalescing layer, when enabled with
+//!    [`DiscogsClientBuilder::coalesce_requests`](crate::DiscogsClientBuilder::coalesce_requests),
+//!    which lets concurrent identical GET requests share one response,
+//! 4. the cache layer, when enabled with
+//!    [`DiscogsClientBuilder::cache`](crate::DiscogsClientBuilder::cache), which may answer from
+//!    the cache or revalidate,
+//! 5. the built-in retry layer, which replays attempts according to the client's
+//!    [`RetryPolicy`](crate::RetryPolicy),
+//! 6. the dispatcher, which signs OAuth retries again with a fresh nonce, waits for the rate
+//!    limiter and sends one attempt through the [`HttpTransport`](crate::HttpTransport).
+//!
+//! User layers see each logical call once, with `AUTHORIZATION`/`ACCEPT` already set, but the
+//! response they get back may come from the cache or from a coalesced request rather than from
+//! the network.
+
+use crate::auth::AuthLevel;
+use crate::client::DiscogsClient;
+use crate::error::{DiscogsError, Result};
+use crate::retry::{RetryContext, RetryTrigger, parse_retry_after};
+use crate::telemetry;
+use crate::transport::{HttpRequest, HttpResponse, TransportErrorKind};
+use futures_util::future::BoxFuture;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// A layer that can inspect or rewrite the outgoing request and the incoming response, or
+/// short-circuit the chain by returning its own response without calling [`Next::run`].
+pub trait Middleware: fmt::Debug + Send + Sync {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next,
+    ) -> BoxFuture<'a, Result<HttpResponse>>;
+}
+
+/// Request extension carrying the auth level the endpoint needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequiredAuth(pub AuthLevel);
+
+/// Request extension counting the attempts that reached the transport.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AttemptCounter(Arc<AtomicU32>);
+
+impl AttemptCounter {
+    /// Records one more attempt and returns its one-based number.
+    pub(crate) fn increment(&self) -> u32 {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The remainder of the chain after the current layer. Cheap to clone, so a layer may call it
+/// several times or move it into a spawned task.
+#[derive(Clone)]
+pub struct Next {
+    client: DiscogsClient,
+    index: usize,
+}
+
+impl Next {
+    pub(crate) fn new(client: DiscogsClient) -> Self {
+        Self { client, index: 0 }
+    }
+
+    pub fn run(mut self, request: HttpRequest) -> BoxFuture<'static, Result<HttpResponse>> {
+        Box::pin(async move {
+            match self.client.layers().get(self.index).cloned() {
+                Some(layer) => {
+                    self.index += 1;
+                    layer.handle(request, self).await
+                }
+                None => self.client.dispatch(request).await,
+            }
+        })
+    }
+
+    pub(crate) fn client(&self) -> &DiscogsClient {
+        &self.client
+    }
+}
+
+impl fmt::Debug for Next {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Next").field("index", &self.index).finish()
+    }
+}
+
+/// Wraps an async closure as a [`Middleware`].
+pub fn middleware_fn<F, Fut>(f: F) -> FnMiddleware<F>
+where
+    F: Fn(HttpRequest, Next) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<HttpResponse>> + Send + 'static,
+{
+    FnMiddleware(f)
+}
+
+pub struct FnMiddleware<F>(F);
+
+impl<F, Fut> Middleware for FnMiddleware<F>
+where
+    F: Fn(HttpRequest, Next) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<HttpResponse>> + Send + 'static,
+{
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next,
+    ) -> BoxFuture<'a, Result<HttpResponse>> {
+        Box::pin((self.0)(request, next))
+    }
+}
+
+impl<F> fmt::Debug for FnMiddleware<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FnMiddleware")
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct AuthLayer;
+
+impl Middleware for AuthLayer {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next,
+    ) -> BoxFuture<'a, Result<HttpResponse>> {
+        Box::pin(async move {
+            // Enforce auth level before sending any request to avoid unnecessary network round trips.
+            if let Some(RequiredAuth(required)) = request.extensions.get::<RequiredAuth>().copied()
+            {
+                let current = next.client().auth_level();
+                if current < required {
+                    return Err(DiscogsError::AuthRequired {
+                        required,
+                        current,
+                        context: None,
+                    });
+                }
+            }
+            next.run(request).await
+        })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RetryLayer;
+
+impl Middleware for RetryLayer {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next,
+    ) -> BoxFuture<'a, Result<HttpResponse>> {
+        Box::pin(async move {
+            let policy = next.client().retry_policy();
+            let mut attempt: u32 = 0;
+            loop {
+                let delay = match next.clone().run(request.clone()).await {
+                    Ok(response) if response.status.is_success() => return Ok(response),
+                    // Discogs can return 429 under burst traffic and transient 5xx from its CDN;
+                    // the policy decides which of those are safe to replay.
+                    Ok(response) => {
+                        let context = RetryContext {
+                            method: &request.method,
+                            attempt,
+                            trigger: RetryTrigger::Status(response.status),
+                            retry_after: parse_retry_after(&response.headers),
+                        };
+                        match policy.next_delay(&context) {
+                            Some(delay) => delay,
+                            None => return Ok(response),
+                        }
+                    }
+                    Err(DiscogsError::Transport(error))
+                        if error.kind() != TransportErrorKind::InvalidRequest =>
+                    {
+                        let context = RetryContext {
+                            method: &request.method,
+                            attempt,
+                            trigger: RetryTrigger::Transport,
+                            retry_after: None,
+                        };
+                        match policy.next_delay(&context) {
+                            Some(delay) => delay,
+                            None => return Err(error.into()),
+                        }
+                    }
+                    Err(error) => return Err(error),
+                };
+
+                telemetry::backoff(&request, attempt + 1, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        })
+    }
+}
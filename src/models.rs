@@ -1,5 +1,11 @@
+use crate::auth::AuthLevel;
+use crate::endpoints::Endpoint;
+use crate::links::{Link, ResourceLink};
+use http::{HeaderMap, Method, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub type ExtraFields = BTreeMap<String, serde_json::Value>;
 
@@ -14,6 +20,27 @@ pub struct RateLimit {
 pub struct ApiResponse<T> {
     pub data: T,
     pub rate_limit: Option<RateLimit>,
+    #[serde(skip)]
+    pub meta: ResponseMeta,
+}
+
+/// How a successful response was obtained. New fields may be added in minor releases.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ResponseMeta {
+    pub status: StatusCode,
+    /// Shared rather than copied, so cloning a response stays cheap.
+    pub headers: Arc<HeaderMap>,
+    pub method: Method,
+    /// Request URL with credential-like query parameters replaced by `REDACTED`.
+    pub url: String,
+    /// Requests actually sent, retries included. Zero when a middleware layer answered.
+    pub attempts: u32,
+    pub elapsed: Duration,
+    /// The endpoint that was called; `None` for responses that were deserialized.
+    pub endpoint: Option<Endpoint>,
+    /// The auth level that endpoint needs.
+    pub required_auth: AuthLevel,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,7 +86,7 @@ pub struct AboutResponse {
 pub struct Identity {
     pub id: u64,
     pub username: String,
-    pub resource_url: String,
+    pub resource_url: Link<UserProfile>,
     #[serde(default)]
     pub consumer_name: Option<String>,
     #[serde(flatten)]
@@ -85,7 +112,7 @@ pub struct UserReference {
     #[serde(default)]
     pub id: Option<u64>,
     pub username: String,
-    pub resource_url: String,
+    pub resource_url: Link<UserProfile>,
     #[serde(default)]
     pub avatar_url: Option<String>,
     #[serde(flatten)]
@@ -100,7 +127,7 @@ pub struct ArtistCredit {
     #[serde(default)]
     pub join: Option<String>,
     pub name: String,
-    pub resource_url: String,
+    pub resource_url: Link<Artist>,
     #[serde(default)]
     pub role: Option<String>,
     #[serde(default)]
@@ -122,7 +149,7 @@ pub struct Format {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LabelReference {
-    pub resource_url: String,
+    pub resource_url: Link<Label>,
     #[serde(default)]
     pub entity_type: Option<String>,
     #[serde(default)]
@@ -168,7 +195,7 @@ pub struct ArtistMember {
     pub active: bool,
     pub id: u64,
     pub name: String,
-    pub resource_url: String,
+    pub resource_url: Link<Artist>,
     #[serde(flatten)]
     pub extra: ExtraFields,
 }
@@ -181,7 +208,7 @@ pub struct Artist {
     pub namevariations: Option<Vec<String>>,
     #[serde(default)]
     pub profile: Option<String>,
-    pub resource_url: String,
+    pub resource_url: Link<Artist>,
     #[serde(default)]
     pub releases_url: Option<String>,
     #[serde(default)]
@@ -219,6 +246,13 @@ pub struct ArtistRelease {
     pub extra: ExtraFields,
 }
 
+impl ArtistRelease {
+    /// The `resource_url` typed by the `type` field: a release or a master.
+    pub fn link(&self) -> Option<ResourceLink> {
+        ResourceLink::new(self.r#type.as_deref()?, &self.resource_url)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArtistReleases {
     pub releases: Vec<ArtistRelease>,
@@ -263,7 +297,7 @@ pub struct ReleaseCompany {
     pub entity_type_name: Option<String>,
     pub id: u64,
     pub name: String,
-    pub resource_url: String,
+    pub resource_url: Link<Label>,
     #[serde(flatten)]
     pub extra: ExtraFields,
 }
@@ -287,7 +321,7 @@ pub struct ReleaseSeries {
     #[serde(default)]
     pub entity_type_name: Option<String>,
     pub id: u64,
-    pub resource_url: String,
+    pub resource_url: Link<Label>,
     #[serde(default)]
     pub thumbnail_url: Option<String>,
     #[serde(flatten)]
@@ -298,7 +332,7 @@ pub struct ReleaseSeries {
 pub struct Release {
     pub id: u64,
     pub title: String,
-    pub resource_url: String,
+    pub resource_url: Link<Release>,
     #[serde(default)]
     pub uri: Option<String>,
     #[serde(default)]
@@ -340,7 +374,7 @@ pub struct Release {
     #[serde(default)]
     pub master_id: Option<u64>,
     #[serde(default)]
-    pub master_url: Option<String>,
+    pub master_url: Option<Link<Master>>,
     #[serde(default)]
     pub notes: Option<String>,
     #[serde(default)]
@@ -398,11 +432,11 @@ pub struct ReleaseStats {
 pub struct Master {
     pub id: u64,
     pub title: String,
-    pub resource_url: String,
+    pub resource_url: Link<Master>,
     #[serde(default)]
     pub main_release: Option<u64>,
     #[serde(default)]
-    pub main_release_url: Option<String>,
+    pub main_release_url: Option<Link<Release>>,
     #[serde(default)]
     pub uri: Option<String>,
     #[serde(default)]
@@ -466,7 +500,7 @@ pub struct MasterVersion {
     pub major_formats: Option<Vec<String>>,
     #[serde(default)]
     pub catno: Option<String>,
-    pub resource_url: String,
+    pub resource_url: Link<Release>,
     pub id: u64,
     #[serde(flatten)]
     pub extra: ExtraFields,
@@ -482,7 +516,7 @@ pub struct MasterVersions {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LabelShort {
-    pub resource_url: String,
+    pub resource_url: Link<Label>,
     pub id: u64,
     pub name: String,
     #[serde(flatten)]
@@ -493,7 +527,7 @@ pub struct LabelShort {
 pub struct Label {
     pub id: u64,
     pub name: String,
-    pub resource_url: String,
+    pub resource_url: Link<Label>,
     #[serde(default)]
     pub uri: Option<String>,
     #[serde(default)]
@@ -523,7 +557,7 @@ pub struct LabelRelease {
     #[serde(default)]
     pub format: Option<String>,
     pub id: u64,
-    pub resource_url: String,
+    pub resource_url: Link<Release>,
     #[serde(default)]
     pub status: Option<DatabaseStatus>,
     #[serde(default)]
@@ -569,7 +603,7 @@ pub struct SearchResult {
     #[serde(default)]
     pub master_id: Option<u64>,
     #[serde(default)]
-    pub master_url: Option<String>,
+    pub master_url: Option<Link<Master>>,
     pub uri: String,
     pub title: String,
     #[serde(default)]
@@ -603,6 +637,13 @@ pub struct SearchResult {
     pub extra: ExtraFields,
 }
 
+impl SearchResult {
+    /// The `resource_url` typed by the `type` field.
+    pub fn link(&self) -> Option<ResourceLink> {
+        ResourceLink::new(&self.result_type, &self.resource_url)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResults {
     pub results: Vec<SearchResult>,
@@ -667,7 +708,7 @@ pub struct Price {
 pub struct ListingReleaseSummary {
     #[serde(default)]
     pub catalog_number: Option<String>,
-    pub resource_url: String,
+    pub resource_url: Link<Release>,
     #[serde(default)]
     pub year: Option<i32>,
     pub id: u64,
@@ -692,7 +733,7 @@ pub struct Listing {
     #[serde(default)]
     pub status: Option<SaleStatus>,
     #[serde(default)]
-    pub resource_url: Option<String>,
+    pub resource_url: Option<Link<Listing>>,
     #[serde(default)]
     pub uri: Option<String>,
     #[serde(default)]
@@ -720,7 +761,7 @@ pub struct Listing {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateListingResponse {
     pub listing_id: u64,
-    pub resource_url: String,
+    pub resource_url: Link<Listing>,
     #[serde(flatten)]
     pub extra: ExtraFields,
 }
@@ -743,7 +784,7 @@ pub struct OrderItem {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub id: String,
-    pub resource_url: String,
+    pub resource_url: Link<Order>,
     #[serde(default)]
     pub status: Option<OrderStatus>,
     #[serde(default)]
@@ -848,7 +889,7 @@ pub struct InventoryExports {
 pub struct UserListSummary {
     pub id: u64,
     pub name: String,
-    pub resource_url: String,
+    pub resource_url: Link<DiscogsList>,
     #[serde(default)]
     pub uri: Option<String>,
     #[serde(default)]
@@ -867,7 +908,7 @@ pub struct UserListSummary {
 pub struct UserProfile {
     pub id: u64,
     pub username: String,
-    pub resource_url: String,
+    pub resource_url: Link<UserProfile>,
     #[serde(default)]
     pub curr_abbr: Option<Currency>,
     #[serde(default)]
@@ -916,7 +957,7 @@ pub struct UserContributions {
 pub struct SubmissionArtist {
     pub id: u64,
     pub name: String,
-    pub resource_url: String,
+    pub resource_url: Link<Artist>,
     #[serde(default)]
     pub releases_url: Option<String>,
     #[serde(default)]
@@ -946,6 +987,14 @@ pub struct UserSubmissions {
     pub extra: ExtraFields,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Submission {
+    Artist(SubmissionArtist),
+    Label(Label),
+    Release(Box<Release>),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserLists {
     pub lists: Vec<UserListSummary>,
@@ -977,7 +1026,7 @@ pub struct BasicReleaseInfo {
     pub title: String,
     #[serde(default)]
     pub year: Option<i32>,
-    pub resource_url: String,
+    pub resource_url: Link<Release>,
     #[serde(default)]
     pub thumb: Option<String>,
     #[serde(default)]
@@ -1072,7 +1121,7 @@ pub struct CollectionValue {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WantlistBasicInformation {
-    pub resource_url: String,
+    pub resource_url: Link<Release>,
     pub id: u64,
     #[serde(default)]
     pub formats: Option<Vec<Format>>,
@@ -1121,7 +1170,7 @@ pub struct WantlistEntries {
 pub struct DiscogsList {
     pub list_id: u64,
     pub name: String,
-    pub resource_url: String,
+    pub resource_url: Link<DiscogsList>,
     #[serde(default)]
     pub description: Option<String>,
     #[serde(default)]
@@ -1,82 +1,240 @@
+//! OAuth 1.0a token exchange and request signing.
+//!
+//! <https://www.discogs.com/developers/#page:authentication,header:authentication-oauth-flow>
+
+use crate::auth::Auth;
+use crate::client::{DiscogsClient, DiscogsClientBuilder};
 use crate::error::{DiscogsError, Result};
+use crate::secret::Secret;
+use crate::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportErrorKind, default_transport,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use hmac::{Hmac, Mac};
+use http::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT};
+use http::{Extensions, Method};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
 use rand::{Rng, distr::Alphanumeric};
-use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use sha1::Sha1;
 use std::collections::BTreeMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::form_urlencoded;
 
-const API_BASE: &str = "https://api.discogs.com";
+const DEFAULT_API_BASE: &str = "https://api.discogs.com";
+const DEFAULT_AUTHORIZE_URL: &str = "https://discogs.com/oauth/authorize";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// RFC 5849 section 3.6: everything except unreserved characters is encoded.
+const OAUTH_ENCODE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// How OAuth requests are signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignatureMethod {
+    /// HMAC-SHA1 over the signature base string. Secrets never leave the client.
+    #[default]
+    HmacSha1,
+    /// Sends the consumer and token secrets as the signature. Only safe over TLS.
+    Plaintext,
+}
+
+impl SignatureMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SignatureMethod::HmacSha1 => "HMAC-SHA1",
+            SignatureMethod::Plaintext => "PLAINTEXT",
+        }
+    }
+}
+
+/// Source of `oauth_timestamp` values. A fixed [`SystemTime`] is a clock that never advances.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl<T: Clock + ?Sized> Clock for Arc<T> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+impl Clock for SystemTime {
+    fn now(&self) -> SystemTime {
+        *self
+    }
+}
+
+/// Source of `oauth_nonce` values. A `String` always yields itself.
+pub trait NonceSource: Debug + Send + Sync {
+    fn nonce(&self) -> String;
+}
+
+/// 64 random alphanumeric characters per request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomNonce;
+
+impl NonceSource for RandomNonce {
+    fn nonce(&self) -> String {
+        rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(64)
+            .map(char::from)
+            .collect()
+    }
+}
+
+impl<T: NonceSource + ?Sized> NonceSource for Arc<T> {
+    fn nonce(&self) -> String {
+        (**self).nonce()
+    }
+}
+
+impl NonceSource for String {
+    fn nonce(&self) -> String {
+        self.clone()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct DiscogsOAuthClient {
     consumer_key: String,
-    consumer_secret: String,
+    consumer_secret: Secret,
     user_agent: String,
-    http: reqwest::Client,
+    api_base: String,
+    authorize_base: String,
+    transport: Arc<dyn HttpTransport>,
+    timeout: Duration,
+    signature_method: SignatureMethod,
+    clock: Arc<dyn Clock>,
+    nonce_source: Arc<dyn NonceSource>,
 }
 
 #[derive(Debug, Clone)]
 pub struct RequestToken {
-    pub token: String,
-    pub token_secret: String,
+    pub token: Secret,
+    pub token_secret: Secret,
     pub callback_confirmed: bool,
     pub authorize_url: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct AccessToken {
-    pub access_token: String,
-    pub access_token_secret: String,
+    pub access_token: Secret,
+    pub access_token_secret: Secret,
 }
 
 impl DiscogsOAuthClient {
     pub fn new(
         consumer_key: impl Into<String>,
-        consumer_secret: impl Into<String>,
+        consumer_secret: impl Into<Secret>,
         user_agent: impl Into<String>,
     ) -> Result<Self> {
         Ok(Self {
             consumer_key: consumer_key.into(),
             consumer_secret: consumer_secret.into(),
             user_agent: user_agent.into(),
-            http: reqwest::Client::builder().build()?,
+            api_base: DEFAULT_API_BASE.to_string(),
+            authorize_base: DEFAULT_AUTHORIZE_URL.to_string(),
+            transport: default_transport()?,
+            timeout: DEFAULT_TIMEOUT,
+            signature_method: SignatureMethod::default(),
+            clock: Arc::new(SystemClock),
+            nonce_source: Arc::new(RandomNonce),
         })
     }
 
-    pub async fn request_token(&self, callback_url: &str) -> Result<RequestToken> {
-        let nonce = oauth_nonce();
-        let timestamp = oauth_timestamp_seconds();
-        let callback_encoded: String =
-            form_urlencoded::byte_serialize(callback_url.as_bytes()).collect();
-
-        let header_value = format!(
-            "OAuth oauth_consumer_key=\"{}\", oauth_nonce=\"{}\", oauth_signature=\"{}&\", oauth_signature_method=\"PLAINTEXT\", oauth_timestamp=\"{}\", oauth_callback=\"{}\"",
-            self.consumer_key,
-            self.nonce_safe(&nonce),
-            self.consumer_secret,
-            timestamp,
-            callback_encoded
-        );
+    /// Like [`DiscogsOAuthClient::new`], but sends requests through `transport`.
+    pub fn with_transport(
+        consumer_key: impl Into<String>,
+        consumer_secret: impl Into<Secret>,
+        user_agent: impl Into<String>,
+        transport: impl HttpTransport + 'static,
+    ) -> Self {
+        Self {
+            consumer_key: consumer_key.into(),
+            consumer_secret: consumer_secret.into(),
+            user_agent: user_agent.into(),
+            api_base: DEFAULT_API_BASE.to_string(),
+            authorize_base: DEFAULT_AUTHORIZE_URL.to_string(),
+            transport: Arc::new(transport),
+            timeout: DEFAULT_TIMEOUT,
+            signature_method: SignatureMethod::default(),
+            clock: Arc::new(SystemClock),
+            nonce_source: Arc::new(RandomNonce),
+        }
+    }
 
-        let response = self
-            .http
-            .get(format!("{API_BASE}/oauth/request_token"))
-            .header(USER_AGENT, &self.user_agent)
-            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
-            .header(AUTHORIZATION, header_value)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let message = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "unknown error".to_string());
-            return Err(DiscogsError::Http { status, message });
+    /// Sends token requests to `base_url` instead of `https://api.discogs.com`, e.g. a local
+    /// stand-in. Clients built by [`DiscogsOAuthClient::client_builder`] inherit it.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.api_base = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The page users are sent to for approval, instead of `https://discogs.com/oauth/authorize`.
+    /// [`RequestToken::authorize_url`] is this URL with the `oauth_token` query parameter added.
+    pub fn with_authorize_url(mut self, authorize_url: impl Into<String>) -> Self {
+        self.authorize_base = authorize_url.into();
+        self
+    }
+
+    /// How long each token request may take before it fails with a timeout. Defaults to 30
+    /// seconds, like [`DiscogsClientBuilder::timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_signature_method(mut self, signature_method: SignatureMethod) -> Self {
+        self.signature_method = signature_method;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_nonce_source(mut self, nonce_source: impl NonceSource + 'static) -> Self {
+        self.nonce_source = Arc::new(nonce_source);
+        self
+    }
+
+    pub async fn request_token(&self, callback_url: &str) -> Result<RequestToken> {
+        let url = format!("{}/oauth/request_token", self.api_base);
+        let header_value = OAuthCredentials {
+            consumer_key: &self.consumer_key,
+            consumer_secret: self.consumer_secret.expose(),
+            token: None,
+            token_secret: "",
+            signature_method: self.signature_method,
         }
+        .authorization_header(
+            &Method::GET,
+            &url,
+            &[("oauth_callback", callback_url)],
+            &[],
+            self.clock.as_ref(),
+            self.nonce_source.as_ref(),
+        );
 
-        let text = response.text().await?;
+        let response = self.send(Method::GET, url, &header_value).await?;
+        let text = String::from_utf8_lossy(&response.body).into_owned();
         // Discogs OAuth endpoints return URL-encoded key/value pairs instead of JSON.
         let values = parse_oauth_form(&text);
 
@@ -94,9 +252,9 @@ impl DiscogsOAuthClient {
             .unwrap_or(false);
 
         Ok(RequestToken {
-            authorize_url: format!("https://discogs.com/oauth/authorize?oauth_token={token}"),
-            token,
-            token_secret,
+            authorize_url: self.authorize_url(&token),
+            token: token.into(),
+            token_secret: token_secret.into(),
             callback_confirmed,
         })
     }
@@ -107,39 +265,25 @@ impl DiscogsOAuthClient {
         request_token_secret: &str,
         verifier: &str,
     ) -> Result<AccessToken> {
-        let nonce = oauth_nonce();
-        let timestamp = oauth_timestamp_seconds();
-
-        let header_value = format!(
-            "OAuth oauth_consumer_key=\"{}\", oauth_nonce=\"{}\", oauth_token=\"{}\", oauth_signature=\"{}&{}\", oauth_signature_method=\"PLAINTEXT\", oauth_timestamp=\"{}\", oauth_verifier=\"{}\"",
-            self.consumer_key,
-            self.nonce_safe(&nonce),
-            request_token,
-            self.consumer_secret,
-            request_token_secret,
-            timestamp,
-            verifier
-        );
-
-        let response = self
-            .http
-            .post(format!("{API_BASE}/oauth/access_token"))
-            .header(USER_AGENT, &self.user_agent)
-            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
-            .header(AUTHORIZATION, header_value)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let message = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "unknown error".to_string());
-            return Err(DiscogsError::Http { status, message });
+        let url = format!("{}/oauth/access_token", self.api_base);
+        let header_value = OAuthCredentials {
+            consumer_key: &self.consumer_key,
+            consumer_secret: self.consumer_secret.expose(),
+            token: Some(request_token),
+            token_secret: request_token_secret,
+            signature_method: self.signature_method,
         }
+        .authorization_header(
+            &Method::POST,
+            &url,
+            &[("oauth_verifier", verifier)],
+            &[],
+            self.clock.as_ref(),
+            self.nonce_source.as_ref(),
+        );
 
-        let text = response.text().await?;
+        let response = self.send(Method::POST, url, &header_value).await?;
+        let text = String::from_utf8_lossy(&response.body).into_owned();
         // Access token exchange uses the same URL-encoded payload shape as request token.
         let values = parse_oauth_form(&text);
         let access_token = values
@@ -152,46 +296,207 @@ impl DiscogsOAuthClient {
             .ok_or_else(|| DiscogsError::InvalidOAuthResponse(text.clone()))?;
 
         Ok(AccessToken {
-            access_token,
-            access_token_secret,
+            access_token: access_token.into(),
+            access_token_secret: access_token_secret.into(),
         })
     }
 
-    fn nonce_safe(&self, nonce: &str) -> String {
-        nonce
-            .chars()
-            .filter(|c| c.is_ascii_alphanumeric())
-            .collect()
+    /// A client builder that signs requests with `access_token`, sharing this client's user
+    /// agent, base URL, transport, timeout, signature method, clock and nonce source.
+    pub fn client_builder(&self, access_token: &AccessToken) -> DiscogsClientBuilder {
+        DiscogsClient::builder(self.user_agent.clone())
+            .base_url(self.api_base.clone())
+            .transport(Arc::clone(&self.transport))
+            .timeout(self.timeout)
+            .oauth_clock(Arc::clone(&self.clock))
+            .oauth_nonce_source(Arc::clone(&self.nonce_source))
+            .auth(Auth::OAuth {
+                consumer_key: self.consumer_key.clone(),
+                consumer_secret: self.consumer_secret.clone(),
+                access_token: access_token.access_token.clone(),
+                access_token_secret: access_token.access_token_secret.clone(),
+                signature_method: self.signature_method,
+            })
+    }
+
+    fn authorize_url(&self, token: &str) -> String {
+        let separator = if self.authorize_base.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("oauth_token", token)
+            .finish();
+        format!("{}{separator}{query}", self.authorize_base)
+    }
+
+    async fn send(&self, method: Method, url: String, authorization: &str) -> Result<HttpResponse> {
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, header_value(&self.user_agent)?);
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        let mut authorization = header_value(authorization)?;
+        authorization.set_sensitive(true);
+        headers.insert(AUTHORIZATION, authorization);
+
+        let request = HttpRequest {
+            method,
+            url,
+            headers,
+            body: None,
+            timeout: Some(self.timeout),
+            extensions: Extensions::new(),
+        };
+        // Enforced here as well, since custom transports may ignore `HttpRequest::timeout`.
+        let response = tokio::time::timeout(self.timeout, self.transport.send(request))
+            .await
+            .map_err(|_| TransportError::timeout())??;
+
+        if !response.status.is_success() {
+            return Err(DiscogsError::from_response(&response));
+        }
+        Ok(response)
+    }
+}
+
+/// Consumer and token credentials for signing one request.
+pub(crate) struct OAuthCredentials<'a> {
+    pub(crate) consumer_key: &'a str,
+    pub(crate) consumer_secret: &'a str,
+    pub(crate) token: Option<&'a str>,
+    pub(crate) token_secret: &'a str,
+    pub(crate) signature_method: SignatureMethod,
+}
+
+impl OAuthCredentials<'_> {
+    /// Builds the `Authorization` header for a request to `url`. `extra` holds additional
+    /// protocol parameters such as `oauth_callback` or `oauth_verifier`; `form` holds the decoded
+    /// fields of an `application/x-www-form-urlencoded` body (RFC 5849 section 3.4.1.3).
+    pub(crate) fn authorization_header(
+        &self,
+        method: &Method,
+        url: &str,
+        extra: &[(&str, &str)],
+        form: &[(&str, &str)],
+        clock: &dyn Clock,
+        nonce_source: &dyn NonceSource,
+    ) -> String {
+        let timestamp = clock
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+            .to_string();
+        let nonce = nonce_source.nonce();
+
+        let mut oauth_params: Vec<(&str, &str)> = vec![
+            ("oauth_consumer_key", self.consumer_key),
+            ("oauth_nonce", &nonce),
+            ("oauth_signature_method", self.signature_method.as_str()),
+            ("oauth_timestamp", &timestamp),
+            ("oauth_version", "1.0"),
+        ];
+        if let Some(token) = self.token {
+            oauth_params.push(("oauth_token", token));
+        }
+        oauth_params.extend_from_slice(extra);
+
+        let signature = self.signature(method, url, &oauth_params, form);
+        oauth_params.push(("oauth_signature", &signature));
+        oauth_params.sort();
+
+        let fields: Vec<String> = oauth_params
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", encode(name), encode(value)))
+            .collect();
+        format!("OAuth {}", fields.join(", "))
+    }
+
+    /// The `oauth_signature` value. `form` holds the decoded fields of an
+    /// `application/x-www-form-urlencoded` body, which are signed too.
+    fn signature(
+        &self,
+        method: &Method,
+        url: &str,
+        oauth_params: &[(&str, &str)],
+        form: &[(&str, &str)],
+    ) -> String {
+        let key = format!(
+            "{}&{}",
+            encode(self.consumer_secret),
+            encode(self.token_secret)
+        );
+        match self.signature_method {
+            SignatureMethod::Plaintext => key,
+            SignatureMethod::HmacSha1 => {
+                let base = signature_base_string(method, url, oauth_params, form);
+                let mut mac = Hmac::<Sha1>::new_from_slice(key.as_bytes())
+                    .expect("HMAC accepts keys of any length");
+                mac.update(base.as_bytes());
+                BASE64.encode(mac.finalize().into_bytes())
+            }
+        }
     }
 }
 
-pub fn build_oauth_header(
-    consumer_key: &str,
-    consumer_secret: &str,
-    access_token: &str,
-    access_token_secret: &str,
+/// RFC 5849 section 3.4.1: `METHOD&base-url&normalized-parameters`.
+fn signature_base_string(
+    method: &Method,
+    url: &str,
+    oauth_params: &[(&str, &str)],
+    form: &[(&str, &str)],
 ) -> String {
-    let nonce = oauth_nonce();
-    let timestamp = oauth_timestamp_seconds();
+    let (base_url, query) = split_url(url);
+    let mut params: Vec<(String, String)> = oauth_params
+        .iter()
+        .chain(form)
+        .map(|(name, value)| (encode(name), encode(value)))
+        .chain(
+            query
+                .iter()
+                .map(|(name, value)| (encode(name), encode(value))),
+        )
+        .collect();
+    params.sort();
+    let normalized: Vec<String> = params
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
 
     format!(
-        "OAuth oauth_consumer_key=\"{consumer_key}\", oauth_token=\"{access_token}\", oauth_signature_method=\"PLAINTEXT\", oauth_signature=\"{consumer_secret}&{access_token_secret}\", oauth_timestamp=\"{timestamp}\", oauth_nonce=\"{nonce}\", oauth_token_secret=\"{access_token_secret}\", oauth_version=\"1.0\""
+        "{}&{}&{}",
+        method.as_str().to_ascii_uppercase(),
+        encode(&base_url),
+        encode(&normalized.join("&"))
     )
 }
 
-fn oauth_nonce() -> String {
-    rand::rng()
-        .sample_iter(&Alphanumeric)
-        .take(64)
-        .map(char::from)
-        .collect()
+/// Splits `url` into its base string URI (lowercase scheme and host, no default port, no query
+/// or fragment) and its decoded query parameters.
+fn split_url(url: &str) -> (String, Vec<(String, String)>) {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return (url.to_string(), Vec::new());
+    };
+    let mut base = format!("{}://{}", parsed.scheme(), parsed.host_str().unwrap_or(""));
+    if let Some(port) = parsed.port() {
+        base.push_str(&format!(":{port}"));
+    }
+    base.push_str(parsed.path());
+    let query = parsed.query_pairs().into_owned().collect();
+    (base, query)
 }
 
-fn oauth_timestamp_seconds() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0)
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, OAUTH_ENCODE).to_string()
+}
+
+fn header_value(value: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|error| {
+        TransportError::with_source(TransportErrorKind::InvalidRequest, error).into()
+    })
 }
 
 fn parse_oauth_form(raw: &str) -> BTreeMap<String, String> {
@@ -199,3 +504,135 @@ fn parse_oauth_form(raw: &str) -> BTreeMap<String, String> {
         .into_owned()
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{OAuthCredentials, SignatureMethod, signature_base_string};
+    use http::Method;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    // The worked example from Twitter's "Creating a signature" guide.
+    const URL: &str = "https://api.twitter.com/1.1/statuses/update.json?include_entities=true";
+    const FORM: [(&str, &str); 1] = [(
+        "status",
+        "Hello Ladies + Gentlemen, a signed OAuth request!",
+    )];
+    const OAUTH_PARAMS: [(&str, &str); 6] = [
+        ("oauth_consumer_key", "xvz1evFS4wEEPTGEFPHBog"),
+        ("oauth_nonce", "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg"),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", "1318622958"),
+        (
+            "oauth_token",
+            "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+        ),
+        ("oauth_version", "1.0"),
+    ];
+
+    fn credentials(signature_method: SignatureMethod) -> OAuthCredentials<'static> {
+        OAuthCredentials {
+            consumer_key: "xvz1evFS4wEEPTGEFPHBog",
+            consumer_secret: "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+            token: Some("370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb"),
+            token_secret: "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+            signature_method,
+        }
+    }
+
+    #[test]
+    fn base_string_normalizes_url_and_parameters() {
+        let base = signature_base_string(&Method::POST, URL, &OAUTH_PARAMS, &FORM);
+
+        assert_eq!(
+            base,
+            "POST&https%3A%2F%2Fapi.twitter.com%2F1.1%2Fstatuses%2Fupdate.json&\
+             include_entities%3Dtrue%26oauth_consumer_key%3Dxvz1evFS4wEEPTGEFPHBog%26\
+             oauth_nonce%3DkYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg%26\
+             oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D1318622958%26\
+             oauth_token%3D370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb%26\
+             oauth_version%3D1.0%26status%3DHello%2520Ladies%2520%252B%2520Gentlemen%252C\
+             %2520a%2520signed%2520OAuth%2520request%2521"
+        );
+        assert!(
+            signature_base_string(&Method::GET, "HTTPS://Api.Discogs.com:443/a?b=c", &[], &[])
+                .starts_with("GET&https%3A%2F%2Fapi.discogs.com%2Fa&")
+        );
+    }
+
+    #[test]
+    fn hmac_sha1_signature_matches_reference_vector() {
+        let signature = credentials(SignatureMethod::HmacSha1).signature(
+            &Method::POST,
+            URL,
+            &OAUTH_PARAMS,
+            &FORM,
+        );
+
+        assert_eq!(signature, "hCtSmYh+iHYCEqBWrE7C7hYmtUk=");
+    }
+
+    // The photo request used by RFC 5849 section 1.2 and OAuth Core 1.0 appendix A.
+    const PHOTOS_URL: &str = "http://photos.example.net/photos?file=vacation.jpg&size=original";
+
+    fn photos_credentials() -> OAuthCredentials<'static> {
+        OAuthCredentials {
+            consumer_key: "dpf43f3p2l4k3l03",
+            consumer_secret: "kd94hf93k423kf44",
+            token: Some("nnch734d00sl2jdk"),
+            token_secret: "pfkkdhi9sl3r4s00",
+            signature_method: SignatureMethod::HmacSha1,
+        }
+    }
+
+    #[test]
+    fn signature_matches_rfc_5849_example() {
+        // The RFC request omits the optional `oauth_version`.
+        let oauth_params = [
+            ("oauth_consumer_key", "dpf43f3p2l4k3l03"),
+            ("oauth_nonce", "chapoH"),
+            ("oauth_signature_method", "HMAC-SHA1"),
+            ("oauth_timestamp", "137131202"),
+            ("oauth_token", "nnch734d00sl2jdk"),
+        ];
+        let signature =
+            photos_credentials().signature(&Method::GET, PHOTOS_URL, &oauth_params, &[]);
+
+        assert_eq!(signature, "MdpQcU8iPSUjWoN/UDMsK2sui9I=");
+    }
+
+    #[test]
+    fn header_matches_oauth_core_example() {
+        let clock = UNIX_EPOCH + Duration::from_secs(1191242096);
+        let nonce = "kllo9940pd9333jh".to_string();
+        let header = photos_credentials().authorization_header(
+            &Method::GET,
+            PHOTOS_URL,
+            &[],
+            &[],
+            &clock,
+            &nonce,
+        );
+
+        assert_eq!(
+            header,
+            "OAuth oauth_consumer_key=\"dpf43f3p2l4k3l03\", oauth_nonce=\"kllo9940pd9333jh\", \
+             oauth_signature=\"tR3%2BTy81lMeYAr%2FFid0kMTYa%2FWM%3D\", \
+             oauth_signature_method=\"HMAC-SHA1\", oauth_timestamp=\"1191242096\", \
+             oauth_token=\"nnch734d00sl2jdk\", oauth_version=\"1.0\""
+        );
+        assert!(!header.contains("kd94hf93k423kf44"));
+        assert!(!header.contains("pfkkdhi9sl3r4s00"));
+        assert!(!header.contains("oauth_token_secret"));
+    }
+
+    #[test]
+    fn plaintext_signature_is_the_encoded_key() {
+        let signature =
+            credentials(SignatureMethod::Plaintext).signature(&Method::GET, URL, &[], &[]);
+
+        assert_eq!(
+            signature,
+            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw&LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Interactive OAuth 1.0a authorization on top of [`DiscogsOAuthClient`].
+//!
+//! <https://www.discogs.com/developers/#page:authentication,header:authentication-oauth-flow>
+
+use crate::client::DiscogsClient;
+use crate::error::{DiscogsError, Result};
+use crate::oauth::{DiscogsOAuthClient, RequestToken};
+use crate::transport::{TransportError, TransportErrorKind};
+use futures_util::StreamExt;
+use futures_util::future::{self, Either};
+use futures_util::stream::FuturesUnordered;
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::pin;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const CALLBACK_PATH: &str = "/callback";
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+/// How long one connection may take to send its request head before it is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Walks a user through authorization and returns a [`DiscogsClient`] signed with the
+/// resulting access token.
+///
+/// [`OAuthFlow::start_loopback`] catches the redirect on a temporary `127.0.0.1` listener;
+/// [`OAuthFlow::start_out_of_band`] asks Discogs to show the verifier as a PIN instead.
+#[derive(Debug, Clone)]
+pub struct OAuthFlow {
+    oauth: DiscogsOAuthClient,
+    callback_addr: SocketAddr,
+    timeout: Duration,
+}
+
+impl OAuthFlow {
+    pub fn new(oauth: DiscogsOAuthClient) -> Self {
+        Self {
+            oauth,
+            callback_addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// Address of the loopback listener. Defaults to `127.0.0.1` on an ephemeral port.
+    pub fn callback_addr(mut self, addr: SocketAddr) -> Self {
+        self.callback_addr = addr;
+        self
+    }
+
+    /// How long [`LoopbackAuthorization::finish`] waits for the redirect. Defaults to five
+    /// minutes.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Binds the loopback listener and fetches a request token whose callback points at it.
+    pub async fn start_loopback(&self) -> Result<LoopbackAuthorization> {
+        let listener = TcpListener::bind(self.callback_addr)
+            .await
+            .map_err(|error| TransportError::with_source(TransportErrorKind::Connect, error))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|error| TransportError::with_source(TransportErrorKind::Other, error))?;
+        let callback_url = format!("http://{addr}{CALLBACK_PATH}");
+        let request_token = self.oauth.request_token(&callback_url).await?;
+
+        Ok(LoopbackAuthorization {
+            oauth: self.oauth.clone(),
+            request_token,
+            callback_url,
+            listener,
+            timeout: self.timeout,
+        })
+    }
+
+    /// Fetches a request token with the `oob` callback, so Discogs displays the verifier.
+    pub async fn start_out_of_band(&self) -> Result<OutOfBandAuthorization> {
+        let request_token = self.oauth.request_token("oob").await?;
+        Ok(OutOfBandAuthorization {
+            oauth: self.oauth.clone(),
+            request_token,
+        })
+    }
+}
+
+/// A started loopback flow. Send the user to [`authorize_url`](Self::authorize_url), then await
+/// [`finish`](Self::finish).
+#[derive(Debug)]
+pub struct LoopbackAuthorization {
+    oauth: DiscogsOAuthClient,
+    request_token: RequestToken,
+    callback_url: String,
+    listener: TcpListener,
+    timeout: Duration,
+}
+
+impl LoopbackAuthorization {
+    pub fn authorize_url(&self) -> &str {
+        &self.request_token.authorize_url
+    }
+
+    pub fn callback_url(&self) -> &str {
+        &self.callback_url
+    }
+
+    pub fn request_token(&self) -> &RequestToken {
+        &self.request_token
+    }
+
+    /// Waits for the redirect that belongs to this flow and exchanges the verifier. Callbacks
+    /// carrying another request token are ignored.
+    pub async fn finish(self) -> Result<DiscogsClient> {
+        let token = self.request_token.token.expose();
+        let params = tokio::time::timeout(self.timeout, accept_callback(&self.listener, token))
+            .await
+            .map_err(|_| TransportError::timeout())??;
+
+        if params.contains_key("denied") {
+            return Err(DiscogsError::InvalidOAuthResponse(
+                "authorization was denied".to_string(),
+            ));
+        }
+        let verifier = params.get("oauth_verifier").ok_or_else(|| {
+            DiscogsError::InvalidOAuthResponse("callback is missing oauth_verifier".to_string())
+        })?;
+
+        exchange(&self.oauth, &self.request_token, verifier).await
+    }
+}
+
+/// A started out-of-band flow. Send the user to [`authorize_url`](Self::authorize_url) and
+/// pass the PIN Discogs shows them to [`finish`](Self::finish).
+#[derive(Debug, Clone)]
+pub struct OutOfBandAuthorization {
+    oauth: DiscogsOAuthClient,
+    request_token: RequestToken,
+}
+
+impl OutOfBandAuthorization {
+    pub fn authorize_url(&self) -> &str {
+        &self.request_token.authorize_url
+    }
+
+    pub fn request_token(&self) -> &RequestToken {
+        &self.request_token
+    }
+
+    pub async fn finish(self, pin: &str) -> Result<DiscogsClient> {
+        exchange(&self.oauth, &self.request_token, pin.trim()).await
+    }
+}
+
+async fn exchange(
+    oauth: &DiscogsOAuthClient,
+    request_token: &RequestToken,
+    verifier: &str,
+) -> Result<DiscogsClient> {
+    let access_token = oauth
+        .access_token(
+            request_token.token.expose(),
+            request_token.token_secret.expose(),
+            verifier,
+        )
+        .await?;
+    oauth.client_builder(&access_token).build()
+}
+
+/// Serves requests until one hits the callback path for `token` and returns its query
+/// parameters. Connections are served concurrently, so an idle socket cannot delay the redirect.
+async fn accept_callback(listener: &TcpListener, token: &str) -> Result<BTreeMap<String, String>> {
+    let mut connections = FuturesUnordered::new();
+    loop {
+        let accepted = if connections.is_empty() {
+            listener.accept().await
+        } else {
+            match future::select(pin!(listener.accept()), connections.next()).await {
+                Either::Left((accepted, _)) => accepted,
+                Either::Right((Some(Some(params)), _)) => return Ok(params),
+                Either::Right(_) => continue,
+            }
+        };
+        let (stream, _) = accepted
+            .map_err(|error| TransportError::with_source(TransportErrorKind::Connect, error))?;
+        connections.push(serve_connection(stream, token));
+    }
+}
+
+/// Answers one connection and returns the query parameters if it is the callback for `token`.
+/// Discogs reports a denial as `denied=<token>` instead of `oauth_token=<token>`.
+async fn serve_connection(mut stream: TcpStream, token: &str) -> Option<BTreeMap<String, String>> {
+    // Browsers also ask for favicons and may open idle connections; ignore anything that is not
+    // a well-formed callback.
+    let target = tokio::time::timeout(READ_TIMEOUT, read_request_target(&mut stream))
+        .await
+        .ok()??;
+    let Ok(url) = url::Url::parse(&format!("http://localhost{target}")) else {
+        respond(&mut stream, "400 Bad Request", "Bad request.").await;
+        return None;
+    };
+    if url.path() != CALLBACK_PATH {
+        respond(&mut stream, "404 Not Found", "Not found.").await;
+        return None;
+    }
+    let params: BTreeMap<String, String> = url.query_pairs().into_owned().collect();
+    let callback_token = params.get("oauth_token").or_else(|| params.get("denied"));
+    if callback_token.map(String::as_str) != Some(token) {
+        // A stale tab or another flow on the same port; keep waiting for ours.
+        respond(
+            &mut stream,
+            "400 Bad Request",
+            "This callback belongs to a different authorization request.",
+        )
+        .await;
+        return None;
+    }
+
+    respond(
+        &mut stream,
+        "200 OK",
+        "Discogs authorization received. You can close this window.",
+    )
+    .await;
+    Some(params)
+}
+
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        head.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next()?.split_whitespace();
+    if request_line.next()? != "GET" {
+        return None;
+    }
+    request_line.next().map(ToOwned::to_owned)
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\ncontent-type: text/plain; charset=utf-8\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    // The browser going away early does not affect the flow.
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
@@ -0,0 +1,310 @@
+//! Helpers for walking paginated Discogs endpoints.
+//!
+//! Reference: <https://www.discogs.com/developers/#page:home,header:home-pagination>
+
+use crate::auth::AuthLevel;
+use crate::client::DiscogsClient;
+use crate::endpoints::Route;
+use crate::error::Result;
+use crate::models::{
+    ApiResponse, ArtistRelease, ArtistReleases, ArtistReleasesQuery, CollectionRelease,
+    CollectionReleases, CollectionReleasesQuery, InventoryExport, InventoryExports, LabelRelease,
+    LabelReleases, Listing, MasterVersion, MasterVersions, MasterVersionsQuery, Order,
+    OrderMessage, OrderMessagesResponse, OrdersQuery, OrdersResponse, Pagination, PaginationParams,
+    RateLimit, Release, SearchQuery, SearchResult, SearchResults, Submission, UserContributions,
+    UserContributionsQuery, UserInventory, UserInventoryQuery, UserListSummary, UserLists,
+    UserSubmissions, WantlistEntries, WantlistEntry,
+};
+use futures_util::future::{self, BoxFuture};
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use http::Method;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// Boxed stream returned by [`Paginator::pages`] and [`Paginator::items`].
+pub type PageStream<'a, T> = BoxStream<'a, Result<T>>;
+
+/// A paginated response body: one page of items plus its [`Pagination`] block.
+pub trait Paginated {
+    type Item;
+
+    fn pagination(&self) -> &Pagination;
+
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+/// One page of items detached from the endpoint-specific response type.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub pagination: Pagination,
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl<T> Page<T> {
+    pub fn from_response<R>(response: ApiResponse<R>) -> Self
+    where
+        R: Paginated<Item = T>,
+    {
+        let pagination = response.data.pagination().clone();
+        Self {
+            items: response.data.into_items(),
+            pagination,
+            rate_limit: response.rate_limit,
+        }
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.next_page().is_some()
+    }
+
+    pub fn next_page(&self) -> Option<u32> {
+        next_page_number(&self.pagination)
+    }
+}
+
+type FetchPage<'a, R> =
+    Arc<dyn Fn(u32) -> BoxFuture<'a, Result<ApiResponse<R>>> + Send + Sync + 'a>;
+
+/// Lazily walks every page of a paginated endpoint.
+///
+/// Pages are fetched one after another by default. [`Paginator::prefetch`] allows several later
+/// pages to be requested concurrently once the total page count is known from the first page.
+/// Streams end after yielding the first error.
+pub struct Paginator<'a, R> {
+    fetch: FetchPage<'a, R>,
+    start_page: u32,
+    prefetch: usize,
+}
+
+impl<'a, R> Paginator<'a, R>
+where
+    R: Paginated + Send + 'a,
+    R::Item: Send + 'a,
+{
+    pub(crate) fn new<Q>(
+        client: &DiscogsClient,
+        route: Route,
+        query: Option<&Q>,
+        required_auth: AuthLevel,
+    ) -> Self
+    where
+        R: DeserializeOwned + 'static,
+        Q: PageQuery,
+    {
+        let query = query.cloned().unwrap_or_default();
+        let start_page = query.pagination().page.unwrap_or(1).max(1);
+        // An owned client keeps the paginator independent of the endpoint group it came from.
+        let client = client.clone();
+        let fetch: FetchPage<'a, R> = Arc::new(move |page| {
+            let mut query = query.clone();
+            query.pagination_mut().page = Some(page);
+            client.request_json::<R, Q, ()>(
+                Method::GET,
+                route.clone(),
+                Some(&query),
+                None,
+                required_auth,
+            )
+        });
+
+        Self {
+            fetch,
+            start_page,
+            prefetch: 1,
+        }
+    }
+
+    /// Number of pages to keep in flight after the first page. Values below 2 disable prefetch.
+    pub fn prefetch(mut self, pages: usize) -> Self {
+        self.prefetch = pages.max(1);
+        self
+    }
+
+    pub fn pages(self) -> PageStream<'a, Page<R::Item>> {
+        let Self {
+            fetch,
+            start_page,
+            prefetch,
+        } = self;
+
+        let first = (fetch)(start_page);
+        let pages = stream::once(first).flat_map(move |first| match first {
+            Ok(response) => {
+                let page = Page::from_response(response);
+                let rest = match page.next_page() {
+                    Some(next) if prefetch > 1 => {
+                        let last = page.pagination.pages;
+                        let fetch = Arc::clone(&fetch);
+                        stream::iter(next..=last)
+                            .map(move |number| (fetch)(number))
+                            .buffered(prefetch)
+                            .map(|result| result.map(Page::from_response))
+                            .boxed()
+                    }
+                    Some(next) => sequential(Arc::clone(&fetch), next),
+                    None => stream::empty().boxed(),
+                };
+                stream::once(future::ready(Ok(page))).chain(rest).boxed()
+            }
+            Err(error) => stream::once(future::ready(Err(error))).boxed(),
+        });
+
+        stop_after_error(pages)
+    }
+
+    pub fn items(self) -> PageStream<'a, R::Item> {
+        self.pages()
+            .flat_map(|page| {
+                let items: Vec<Result<R::Item>> = match page {
+                    Ok(page) => page.items.into_iter().map(Ok).collect(),
+                    Err(error) => vec![Err(error)],
+                };
+                stream::iter(items)
+            })
+            .boxed()
+    }
+}
+
+fn sequential<'a, R>(fetch: FetchPage<'a, R>, first: u32) -> PageStream<'a, Page<R::Item>>
+where
+    R: Paginated + Send + 'a,
+    R::Item: Send + 'a,
+{
+    stream::unfold(Some(first), move |next| {
+        let fetch = Arc::clone(&fetch);
+        async move {
+            let number = next?;
+            match (fetch)(number).await {
+                Ok(response) => {
+                    let page = Page::from_response(response);
+                    let next = page.next_page();
+                    Some((Ok(page), next))
+                }
+                Err(error) => Some((Err(error), None)),
+            }
+        }
+    })
+    .boxed()
+}
+
+fn stop_after_error<'a, T, S>(stream: S) -> PageStream<'a, T>
+where
+    T: Send + 'a,
+    S: Stream<Item = Result<T>> + Send + 'a,
+{
+    stream
+        .scan(false, |failed, item| {
+            if *failed {
+                return future::ready(None);
+            }
+            *failed = item.is_err();
+            future::ready(Some(item))
+        })
+        .boxed()
+}
+
+fn next_page_number(pagination: &Pagination) -> Option<u32> {
+    (pagination.page < pagination.pages).then(|| pagination.page + 1)
+}
+
+/// Query types that carry a [`PaginationParams`] block the paginator can advance.
+pub(crate) trait PageQuery: Serialize + Clone + Default + Send + Sync + 'static {
+    fn pagination(&self) -> &PaginationParams;
+
+    fn pagination_mut(&mut self) -> &mut PaginationParams;
+}
+
+impl PageQuery for PaginationParams {
+    fn pagination(&self) -> &PaginationParams {
+        self
+    }
+
+    fn pagination_mut(&mut self) -> &mut PaginationParams {
+        self
+    }
+}
+
+macro_rules! page_query {
+    ($($query:ty),* $(,)?) => {
+        $(
+            impl PageQuery for $query {
+                fn pagination(&self) -> &PaginationParams {
+                    &self.pagination
+                }
+
+                fn pagination_mut(&mut self) -> &mut PaginationParams {
+                    &mut self.pagination
+                }
+            }
+        )*
+    };
+}
+
+page_query!(
+    ArtistReleasesQuery,
+    MasterVersionsQuery,
+    SearchQuery,
+    OrdersQuery,
+    UserInventoryQuery,
+    UserContributionsQuery,
+    CollectionReleasesQuery,
+);
+
+macro_rules! paginated {
+    ($($response:ty => $field:ident: $item:ty),* $(,)?) => {
+        $(
+            impl Paginated for $response {
+                type Item = $item;
+
+                fn pagination(&self) -> &Pagination {
+                    &self.pagination
+                }
+
+                fn into_items(self) -> Vec<Self::Item> {
+                    self.$field
+                }
+            }
+        )*
+    };
+}
+
+paginated!(
+    ArtistReleases => releases: ArtistRelease,
+    LabelReleases => releases: LabelRelease,
+    MasterVersions => versions: MasterVersion,
+    SearchResults => results: SearchResult,
+    OrdersResponse => orders: Order,
+    OrderMessagesResponse => messages: OrderMessage,
+    InventoryExports => items: InventoryExport,
+    UserInventory => listings: Listing,
+    UserContributions => contributions: Release,
+    UserLists => lists: UserListSummary,
+    CollectionReleases => releases: CollectionRelease,
+    WantlistEntries => wants: WantlistEntry,
+);
+
+impl Paginated for UserSubmissions {
+    type Item = Submission;
+
+    fn pagination(&self) -> &Pagination {
+        &self.pagination
+    }
+
+    fn into_items(self) -> Vec<Self::Item> {
+        let payload = self.submissions;
+        payload
+            .artists
+            .into_iter()
+            .map(Submission::Artist)
+            .chain(payload.labels.into_iter().map(Submission::Label))
+            .chain(
+                payload
+                    .releases
+                    .into_iter()
+                    .map(|release| Submission::Release(Box::new(release))),
+            )
+            .collect()
+    }
+}
@@ -0,0 +1,284 @@
+//! Client-side pacing driven by Discogs rate-limit headers.
+//!
+//! Reference: <https://www.discogs.com/developers/#page:home,header:home-rate-limiting>
+
+use crate::models::RateLimit;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// Pacing settings for the shared limiter enabled via
+/// [`DiscogsClientBuilder::rate_limiter`](crate::DiscogsClientBuilder::rate_limiter).
+#[derive(Debug, Clone)]
+pub struct RateLimiterConfig {
+    /// Length of the moving window Discogs counts requests in.
+    pub window: Duration,
+    /// Requests allowed per window until the first `x-discogs-ratelimit` header is seen.
+    pub default_limit: u32,
+}
+
+impl Default for RateLimiterConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            default_limit: 60,
+        }
+    }
+}
+
+pub(crate) type LowRemainingCallback = Arc<dyn Fn(&RateLimit) + Send + Sync>;
+
+pub(crate) struct LowRemaining {
+    pub(crate) threshold: u32,
+    pub(crate) callback: LowRemainingCallback,
+}
+
+/// Moving-window limiter shared by every clone of a client.
+///
+/// The limiter always tracks the latest [`RateLimit`] seen; pacing only happens when a
+/// [`RateLimiterConfig`] was supplied.
+pub(crate) struct RateLimiter {
+    pacing: Option<RateLimiterConfig>,
+    low: Option<LowRemaining>,
+    state: Mutex<WindowState>,
+    latest: watch::Sender<Option<RateLimit>>,
+}
+
+struct WindowState {
+    limit: u32,
+    sent: VecDeque<Instant>,
+    low: bool,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(pacing: Option<RateLimiterConfig>, low: Option<LowRemaining>) -> Self {
+        let limit = pacing
+            .as_ref()
+            .map(|config| config.default_limit)
+            .unwrap_or(60);
+        let (latest, _) = watch::channel(None);
+        Self {
+            pacing,
+            low,
+            state: Mutex::new(WindowState {
+                limit: limit.max(1),
+                sent: VecDeque::new(),
+                low: false,
+            }),
+            latest,
+        }
+    }
+
+    /// Waits until one more request fits into the current window, then records it.
+    pub(crate) async fn acquire(&self) {
+        let Some(config) = &self.pacing else {
+            return;
+        };
+
+        loop {
+            let wait = {
+                let mut state = self.state.lock().expect("rate limiter lock poisoned");
+                let now = Instant::now();
+                state.prune(now, config.window);
+                if (state.sent.len() as u32) < state.limit {
+                    state.sent.push_back(now);
+                    return;
+                }
+                state
+                    .sent
+                    .front()
+                    .map(|oldest| (*oldest + config.window).saturating_duration_since(now))
+                    .unwrap_or(config.window)
+            };
+
+            tokio::time::sleep(wait.max(Duration::from_millis(1))).await;
+        }
+    }
+
+    /// Reconciles the local window with the server's view after a response.
+    pub(crate) fn observe(&self, rate_limit: Option<&RateLimit>) {
+        let Some(rate_limit) = rate_limit else {
+            return;
+        };
+
+        let fire_low = {
+            let mut state = self.state.lock().expect("rate limiter lock poisoned");
+            if let Some(config) = &self.pacing {
+                let now = Instant::now();
+                state.prune(now, config.window);
+                if rate_limit.limit > 0 {
+                    state.limit = rate_limit.limit;
+                }
+                // The server is authoritative: pad the window with requests we never saw (other
+                // processes sharing the token), or forget our oldest ones once its window has
+                // moved past them.
+                let used = rate_limit
+                    .used
+                    .max(state.limit.saturating_sub(rate_limit.remaining))
+                    .min(state.limit);
+                while (state.sent.len() as u32) < used {
+                    state.sent.push_back(now);
+                }
+                while (state.sent.len() as u32) > used {
+                    state.sent.pop_front();
+                }
+            }
+
+            let is_low = self
+                .low
+                .as_ref()
+                .is_some_and(|low| rate_limit.remaining < low.threshold);
+            let fire = is_low && !state.low;
+            state.low = is_low;
+            fire
+        };
+
+        self.latest.send_replace(Some(rate_limit.clone()));
+        if let Some(low) = self.low.as_ref().filter(|_| fire_low) {
+            (low.callback)(rate_limit);
+        }
+    }
+
+    /// Treats the window as exhausted, used when Discogs answers 429 without usable headers.
+    pub(crate) fn exhaust(&self) {
+        let Some(config) = &self.pacing else {
+            return;
+        };
+
+        let mut state = self.state.lock().expect("rate limiter lock poisoned");
+        let now = Instant::now();
+        state.prune(now, config.window);
+        while (state.sent.len() as u32) < state.limit {
+            state.sent.push_back(now);
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<Option<RateLimit>> {
+        self.latest.subscribe()
+    }
+
+    pub(crate) fn latest(&self) -> Option<RateLimit> {
+        self.latest.borrow().clone()
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("pacing", &self.pacing)
+            .field("low_threshold", &self.low.as_ref().map(|low| low.threshold))
+            .field("latest", &*self.latest.borrow())
+            .finish()
+    }
+}
+
+impl WindowState {
+    fn prune(&mut self, now: Instant, window: Duration) {
+        while let Some(oldest) = self.sent.front() {
+            if now.saturating_duration_since(*oldest) < window {
+                break;
+            }
+            self.sent.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LowRemaining, RateLimiter, RateLimiterConfig};
+    use crate::models::RateLimit;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    fn pacing(limit: u32) -> Option<RateLimiterConfig> {
+        Some(RateLimiterConfig {
+            window: Duration::from_secs(60),
+            default_limit: limit,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_waits_for_the_window_to_slide() {
+        let limiter = RateLimiter::new(pacing(2), None);
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn observed_usage_from_headers_is_respected() {
+        let limiter = RateLimiter::new(pacing(60), None);
+        let start = Instant::now();
+
+        limiter.observe(Some(&RateLimit {
+            limit: 5,
+            used: 5,
+            remaining: 0,
+        }));
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lower_reported_usage_frees_the_window() {
+        let limiter = RateLimiter::new(pacing(3), None);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        limiter.observe(Some(&RateLimit {
+            limit: 3,
+            used: 1,
+            remaining: 2,
+        }));
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn low_remaining_callback_fires_once_per_crossing() {
+        let fired = Arc::new(AtomicUsize::new(0));
+        let limiter = RateLimiter::new(
+            None,
+            Some(LowRemaining {
+                threshold: 5,
+                callback: Arc::new({
+                    let fired = Arc::clone(&fired);
+                    move |_| {
+                        fired.fetch_add(1, Ordering::SeqCst);
+                    }
+                }),
+            }),
+        );
+        let rate = |remaining| RateLimit {
+            limit: 60,
+            used: 60 - remaining,
+            remaining,
+        };
+
+        limiter.observe(Some(&rate(10)));
+        limiter.observe(Some(&rate(4)));
+        limiter.observe(Some(&rate(3)));
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+        limiter.observe(Some(&rate(30)));
+        limiter.observe(Some(&rate(2)));
+        assert_eq!(fired.load(Ordering::SeqCst), 2);
+        assert_eq!(limiter.latest().map(|rate| rate.remaining), Some(2));
+    }
+}
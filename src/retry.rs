@@ -0,0 +1,226 @@
+//! Retry policies applied by the client request pipeline.
+
+use http::header::{HeaderMap, RETRY_AFTER};
+use http::{Method, StatusCode};
+use rand::Rng;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// Decides whether a failed attempt is retried and how long to wait first.
+pub trait RetryPolicy: fmt::Debug + Send + Sync {
+    /// Returns the delay before the next attempt, or `None` to give up.
+    fn next_delay(&self, context: &RetryContext<'_>) -> Option<Duration>;
+}
+
+/// What made an attempt fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryTrigger {
+    /// Discogs answered with a non-success status.
+    Status(StatusCode),
+    /// The request never produced a response (connection reset, timeout, DNS failure, ...).
+    Transport,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryContext<'a> {
+    pub method: &'a Method,
+    /// Zero-based index of the attempt that just failed.
+    pub attempt: u32,
+    pub trigger: RetryTrigger,
+    /// Parsed `Retry-After` header, when the response carried one.
+    pub retry_after: Option<Duration>,
+}
+
+impl RetryContext<'_> {
+    /// GET, HEAD, OPTIONS, PUT and DELETE can be replayed without duplicating side effects.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            *self.method,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub backoff_factor: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::from_millis(2_000),
+            backoff_factor: 2.7,
+        }
+    }
+}
+
+/// The default policy.
+///
+/// `429` is retried for every method because Discogs rejects the request before processing it.
+/// `500`/`502`/`503`/`504` and transport errors are only retried for idempotent methods, so a
+/// POST such as `add_listing` is never replayed blindly. `Retry-After` wins over the computed
+/// backoff; otherwise the delay is drawn uniformly from zero to the exponential backoff
+/// ("full jitter").
+impl RetryPolicy for RetryConfig {
+    fn next_delay(&self, context: &RetryContext<'_>) -> Option<Duration> {
+        if context.attempt >= self.max_retries {
+            return None;
+        }
+
+        let retryable = match context.trigger {
+            RetryTrigger::Status(StatusCode::TOO_MANY_REQUESTS) => true,
+            RetryTrigger::Status(
+                StatusCode::INTERNAL_SERVER_ERROR
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT,
+            )
+            | RetryTrigger::Transport => context.is_idempotent(),
+            RetryTrigger::Status(_) => false,
+        };
+        if !retryable {
+            return None;
+        }
+
+        if let Some(retry_after) = context.retry_after {
+            return Some(retry_after);
+        }
+
+        let ceiling = retry_delay(self, context.attempt);
+        let jittered = rand::rng().random_range(0..=ceiling.as_millis() as u64);
+        Some(Duration::from_millis(jittered.max(1)))
+    }
+}
+
+/// Never retries. Handy as a per-call override for non-idempotent writes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoRetry;
+
+impl RetryPolicy for NoRetry {
+    fn next_delay(&self, _context: &RetryContext<'_>) -> Option<Duration> {
+        None
+    }
+}
+
+/// Upper bound of the backoff for `attempt`, before jitter is applied.
+pub(crate) fn retry_delay(config: &RetryConfig, attempt: u32) -> Duration {
+    let base_ms = config.base_delay.as_millis() as f64;
+    let factor = config.backoff_factor.powi(attempt as i32);
+    let delay_ms = (base_ms * factor).round() as u64;
+    Duration::from_millis(delay_ms.max(1))
+}
+
+/// Parses `Retry-After` given either as delay seconds or as an HTTP date.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(
+        at.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetryConfig, RetryContext, RetryPolicy, RetryTrigger, parse_retry_after};
+    use http::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use http::{Method, StatusCode};
+    use std::time::Duration;
+
+    fn config() -> RetryConfig {
+        RetryConfig {
+            max_retries: 2,
+            base_delay: Duration::from_millis(100),
+            backoff_factor: 2.0,
+        }
+    }
+
+    fn context(method: &Method, trigger: RetryTrigger) -> RetryContext<'_> {
+        RetryContext {
+            method,
+            attempt: 0,
+            trigger,
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn server_errors_are_retried_only_for_idempotent_methods() {
+        let unavailable = RetryTrigger::Status(StatusCode::SERVICE_UNAVAILABLE);
+
+        assert!(
+            config()
+                .next_delay(&context(&Method::GET, unavailable))
+                .is_some()
+        );
+        assert!(
+            config()
+                .next_delay(&context(&Method::DELETE, RetryTrigger::Transport))
+                .is_some()
+        );
+        assert!(
+            config()
+                .next_delay(&context(&Method::POST, unavailable))
+                .is_none()
+        );
+        assert!(
+            config()
+                .next_delay(&context(&Method::POST, RetryTrigger::Transport))
+                .is_none()
+        );
+        assert!(
+            config()
+                .next_delay(&context(
+                    &Method::POST,
+                    RetryTrigger::Status(StatusCode::TOO_MANY_REQUESTS)
+                ))
+                .is_some()
+        );
+        assert!(
+            config()
+                .next_delay(&context(
+                    &Method::GET,
+                    RetryTrigger::Status(StatusCode::NOT_FOUND)
+                ))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn jittered_delay_stays_below_backoff_and_retry_after_wins() {
+        let method = Method::GET;
+        let mut ctx = context(&method, RetryTrigger::Transport);
+        ctx.attempt = 1;
+        for _ in 0..50 {
+            let delay = config().next_delay(&ctx).expect("retry");
+            assert!(delay <= Duration::from_millis(200));
+        }
+
+        ctx.retry_after = Some(Duration::from_secs(7));
+        assert_eq!(config().next_delay(&ctx), Some(Duration::from_secs(7)));
+
+        ctx.attempt = 2;
+        assert_eq!(config().next_delay(&ctx), None);
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_http_dates() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(12)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,104 @@
+//! A string credential that stays out of logs and memory dumps.
+
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fmt;
+use zeroize::Zeroize;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// A token or secret. `Debug` and `Display` print `[REDACTED]`, the bytes are zeroed on drop,
+/// and reading the value takes an explicit [`Secret::expose`].
+///
+/// `Secret` deserializes from a plain string but does not implement `Serialize`; fields that
+/// really must be written out opt in with
+/// `#[serde(serialize_with = "discogs_rs::Secret::serialize_exposed")]`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Serializes the exposed value, for use with `#[serde(serialize_with = "...")]`.
+    pub fn serialize_exposed<S: Serializer>(
+        secret: &Secret,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(secret.expose())
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Secret;
+
+    #[test]
+    fn formatting_never_shows_the_value() {
+        let secret = Secret::from("hunter2");
+
+        assert_eq!(format!("{secret}"), "[REDACTED]");
+        assert_eq!(format!("{secret:?}"), "[REDACTED]");
+        assert_eq!(secret.expose(), "hunter2");
+    }
+
+    #[test]
+    fn serialization_requires_opting_in() {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Stored {
+            #[serde(serialize_with = "Secret::serialize_exposed")]
+            token: Secret,
+        }
+
+        let stored: Stored = serde_json::from_str(r#"{"token":"abc"}"#).expect("deserialize");
+        assert_eq!(stored.token.expose(), "abc");
+        assert_eq!(
+            serde_json::to_string(&stored).expect("serialize"),
+            r#"{"token":"abc"}"#
+        );
+    }
+}
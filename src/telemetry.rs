@@ -0,0 +1,245 @@
+//! Optional instrumentation hooks. Without the `tracing` and `metrics` features every hook is a
+//! no-op.
+//!
+//! Spans only carry endpoint names, path templates, redacted URLs, statuses, sizes and
+//! rate-limit numbers; header values (and with them `Authorization` and OAuth secrets) are never
+//! recorded. Metrics are labelled by endpoint name, never by URL, to keep cardinality bounded.
+
+use crate::auth::AuthLevel;
+use crate::endpoints::Endpoint;
+use crate::error::DiscogsError;
+use crate::transport::{HttpRequest, HttpResponse};
+use http::Method;
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+const REQUESTS_TOTAL: &str = "discogs_requests_total";
+#[cfg(feature = "metrics")]
+const CALL_DURATION_SECONDS: &str = "discogs_call_duration_seconds";
+#[cfg(feature = "metrics")]
+const RETRIES_TOTAL: &str = "discogs_retries_total";
+#[cfg(feature = "metrics")]
+const RATE_LIMITED_TOTAL: &str = "discogs_rate_limited_total";
+#[cfg(feature = "metrics")]
+const RATELIMIT_REMAINING: &str = "discogs_ratelimit_remaining";
+
+/// Span covering one logical call, retries included.
+pub(crate) struct CallSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "metrics")]
+    endpoint: &'static str,
+    #[cfg(feature = "metrics")]
+    started: std::time::Instant,
+}
+
+impl CallSpan {
+    pub(crate) fn new(endpoint: &Endpoint, method: &Method, auth_level: AuthLevel) -> Self {
+        #[cfg(not(feature = "tracing"))]
+        let _ = (method, auth_level);
+        #[cfg(not(any(feature = "tracing", feature = "metrics")))]
+        let _ = endpoint;
+
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "discogs.call",
+                endpoint = endpoint.name,
+                method = %method,
+                path = endpoint.template,
+                auth_level = ?auth_level,
+                status = tracing::field::Empty,
+                attempts = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+            #[cfg(feature = "metrics")]
+            endpoint: endpoint.name,
+            #[cfg(feature = "metrics")]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) async fn instrument<F: Future>(&self, future: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(future, self.span.clone()).await
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            future.await
+        }
+    }
+
+    pub(crate) fn finish(&self, result: Result<&HttpResponse, &DiscogsError>, attempts: u32) {
+        #[cfg(not(feature = "tracing"))]
+        let _ = attempts;
+        #[cfg(not(any(feature = "tracing", feature = "metrics")))]
+        let _ = result;
+
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("attempts", attempts);
+            match result {
+                Ok(response) => {
+                    self.span.record("status", response.status.as_u16());
+                }
+                Err(error) => {
+                    if let Some(status) = error.status() {
+                        self.span.record("status", status.as_u16());
+                    }
+                    self.span.record("error", tracing::field::display(error));
+                }
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            let outcome = match result {
+                Ok(response) => status_class(response.status),
+                Err(error) => error.status().map_or("error", status_class),
+            };
+            metrics::histogram!(
+                CALL_DURATION_SECONDS,
+                "endpoint" => self.endpoint,
+                "outcome" => outcome,
+            )
+            .record(self.started.elapsed().as_secs_f64());
+        }
+    }
+}
+
+/// Span covering one request handed to the transport.
+pub(crate) struct AttemptSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "metrics")]
+    endpoint: &'static str,
+}
+
+impl AttemptSpan {
+    pub(crate) fn new(request: &HttpRequest, attempt: u32) -> Self {
+        #[cfg(not(feature = "tracing"))]
+        let _ = attempt;
+        #[cfg(not(any(feature = "tracing", feature = "metrics")))]
+        let _ = request;
+
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!(
+                "discogs.attempt",
+                attempt,
+                url = %crate::error::redact_url(&request.url),
+                request_bytes = request.body.as_ref().map_or(0, |body| body.len()),
+                status = tracing::field::Empty,
+                response_bytes = tracing::field::Empty,
+                ratelimit = tracing::field::Empty,
+                ratelimit_used = tracing::field::Empty,
+                ratelimit_remaining = tracing::field::Empty,
+                error = tracing::field::Empty,
+            ),
+            #[cfg(feature = "metrics")]
+            endpoint: endpoint_name(request),
+        }
+    }
+
+    pub(crate) async fn instrument<F: Future>(&self, future: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(future, self.span.clone()).await
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            future.await
+        }
+    }
+
+    pub(crate) fn record_response(&self, response: &HttpResponse) {
+        #[cfg(not(any(feature = "tracing", feature = "metrics")))]
+        let _ = response;
+
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("status", response.status.as_u16());
+            self.span.record("response_bytes", response.body.len());
+            if let Some(rate_limit) = crate::client::parse_rate_limit(&response.headers) {
+                self.span.record("ratelimit", rate_limit.limit);
+                self.span.record("ratelimit_used", rate_limit.used);
+                self.span
+                    .record("ratelimit_remaining", rate_limit.remaining);
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!(
+                REQUESTS_TOTAL,
+                "endpoint" => self.endpoint,
+                "status_class" => status_class(response.status),
+            )
+            .increment(1);
+            if response.status == http::StatusCode::TOO_MANY_REQUESTS {
+                metrics::counter!(RATE_LIMITED_TOTAL, "endpoint" => self.endpoint).increment(1);
+            }
+            if let Some(rate_limit) = crate::client::parse_rate_limit(&response.headers) {
+                metrics::gauge!(RATELIMIT_REMAINING).set(f64::from(rate_limit.remaining));
+            }
+        }
+    }
+
+    /// The attempt produced no response at all.
+    pub(crate) fn record_error(&self, error: &DiscogsError) {
+        #[cfg(not(feature = "tracing"))]
+        let _ = error;
+
+        #[cfg(feature = "tracing")]
+        self.span.record("error", tracing::field::display(error));
+
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            REQUESTS_TOTAL,
+            "endpoint" => self.endpoint,
+            "status_class" => "transport_error",
+        )
+        .increment(1);
+    }
+}
+
+/// Called by the retry layer before it sleeps.
+pub(crate) fn backoff(request: &HttpRequest, attempt: u32, delay: Duration) {
+    #[cfg(not(feature = "tracing"))]
+    let _ = (attempt, delay);
+    #[cfg(not(feature = "metrics"))]
+    let _ = request;
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        attempt,
+        delay_ms = delay.as_millis() as u64,
+        "retrying Discogs request after backoff"
+    );
+
+    #[cfg(feature = "metrics")]
+    metrics::counter!(RETRIES_TOTAL, "endpoint" => endpoint_name(request)).increment(1);
+}
+
+#[cfg(feature = "metrics")]
+fn endpoint_name(request: &HttpRequest) -> &'static str {
+    request
+        .extensions
+        .get::<Endpoint>()
+        .map_or("unknown", |endpoint| endpoint.name)
+}
+
+#[cfg(feature = "metrics")]
+fn status_class(status: http::StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
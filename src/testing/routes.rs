@@ -0,0 +1,2276 @@
+//! Routes of the emulator, grouped like the endpoint modules.
+
+use super::RateLimits;
+use super::state::{
+    ALL_FOLDER, CONDITIONS, Export, Failure, Folder, Instance, Params, Store, Want, artist_names,
+    collection_fields, is_for_sale, label, listing_release, owner, paginate, price, seller,
+    sort_items, timestamp,
+};
+use crate::auth::AuthLevel;
+use crate::models::{
+    AddCollectionReleaseResponse, AddFolderRequest, AddOrderMessageRequest, ArtistCredit,
+    ArtistRelease, ArtistReleases, CollectionFields, CollectionFolders, CollectionReleases,
+    CollectionValue, CreateListingRequest, CreateListingResponse, Currency,
+    EditCollectionReleaseRequest, EditInstanceNoteRequest, EditOrderRequest, EditProfileRequest,
+    ExtraFields, Identity, InventoryExport, InventoryExports, LabelRelease, LabelReleases, Listing,
+    MarketplaceReleaseStats, MasterVersion, MasterVersions, Order, OrderMessage,
+    OrderMessagesResponse, OrdersResponse, RatingSummary, Release, ReleaseCommunityRating,
+    ReleaseRating, ReleaseStats, SearchResult, SearchResultUserData, SearchResults,
+    UserContributions, UserInventory, UserListSummary, UserLists, UserSubmissions,
+    UserSubmissionsPayload, WantlistEntries, WantlistUpdateRequest,
+};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, LOCATION};
+use axum::http::{HeaderValue, StatusCode, Uri};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+use tokio::time::Instant;
+
+type Reply = Result<Response, Failure>;
+type Shared = State<Arc<Server>>;
+
+const SELLING_FEE: f64 = 0.09;
+
+pub(super) struct Server {
+    store: Mutex<Store>,
+    limits: RateLimits,
+    windows: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+/// Who sent a request. Requests are rate limited per account, or together when anonymous.
+#[derive(Debug, Clone)]
+struct Caller {
+    level: AuthLevel,
+    username: Option<String>,
+    consumer: Option<String>,
+    bucket: String,
+}
+
+struct Quota {
+    limit: u32,
+    used: u32,
+    allowed: bool,
+}
+
+impl Server {
+    pub(super) fn new(store: Store, limits: RateLimits) -> Self {
+        Self {
+            store: Mutex::new(store),
+            limits,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn store(&self) -> MutexGuard<'_, Store> {
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Resolves the `Authorization` header against the fixture users and consumers. OAuth
+    /// signatures are not verified; the consumer key and access token have to be known.
+    fn identify(&self, header: Option<&str>) -> Result<Caller, Failure> {
+        let Some(header) = header else {
+            return Ok(Caller {
+                level: AuthLevel::None,
+                username: None,
+                consumer: None,
+                bucket: "anonymous".to_string(),
+            });
+        };
+        let store = self.store();
+        let user_for = |token: &str, consumer: Option<String>| {
+            let user = store
+                .users
+                .iter()
+                .find(|user| user.token == token)
+                .ok_or_else(Failure::unauthorized)?;
+            Ok(Caller {
+                level: AuthLevel::User,
+                username: Some(user.username.clone()),
+                consumer,
+                bucket: format!("user:{}", user.username),
+            })
+        };
+
+        if let Some(token) = header.strip_prefix("Discogs token=") {
+            return user_for(token, None);
+        }
+        if let Some(credentials) = header.strip_prefix("Discogs ") {
+            let params: HashMap<&str, &str> = credentials
+                .split(", ")
+                .filter_map(|pair| pair.split_once('='))
+                .collect();
+            let known = store.consumers.iter().any(|consumer| {
+                params.get("key") == Some(&consumer.key.as_str())
+                    && params.get("secret") == Some(&consumer.secret.as_str())
+            });
+            let key = params
+                .get("key")
+                .filter(|_| known)
+                .ok_or_else(Failure::unauthorized)?;
+            return Ok(Caller {
+                level: AuthLevel::Consumer,
+                username: None,
+                consumer: Some(key.to_string()),
+                bucket: format!("consumer:{key}"),
+            });
+        }
+        if let Some(oauth) = header.strip_prefix("OAuth ") {
+            let params: HashMap<&str, String> = oauth
+                .split(',')
+                .filter_map(|pair| {
+                    let (key, value) = pair.trim().split_once('=')?;
+                    let value = percent_encoding::percent_decode_str(value.trim_matches('"'))
+                        .decode_utf8_lossy()
+                        .into_owned();
+                    Some((key, value))
+                })
+                .collect();
+            let consumer = params
+                .get("oauth_consumer_key")
+                .filter(|key| store.consumers.iter().any(|consumer| &consumer.key == *key))
+                .ok_or_else(Failure::unauthorized)?;
+            let token = params
+                .get("oauth_token")
+                .ok_or_else(Failure::unauthorized)?;
+            return user_for(token, Some(consumer.clone()));
+        }
+        Err(Failure::unauthorized())
+    }
+
+    /// Counts the request against a moving window, like Discogs does.
+    fn take_quota(&self, caller: &Caller) -> Quota {
+        let limit = match caller.level {
+            AuthLevel::None => self.limits.anonymous,
+            AuthLevel::Consumer | AuthLevel::User => self.limits.authenticated,
+        };
+        let now = Instant::now();
+        let mut windows = self
+            .windows
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let window = windows.entry(caller.bucket.clone()).or_default();
+        while window
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= self.limits.window)
+        {
+            window.pop_front();
+        }
+
+        let allowed = window.len() < limit as usize;
+        if allowed {
+            window.push_back(now);
+        }
+        Quota {
+            limit,
+            used: (window.len() as u32).min(limit),
+            allowed,
+        }
+    }
+}
+
+impl Caller {
+    fn require(&self, level: AuthLevel) -> Result<(), Failure> {
+        if self.level >= level {
+            Ok(())
+        } else {
+            Err(Failure::unauthorized())
+        }
+    }
+
+    fn user(&self) -> Result<&str, Failure> {
+        self.username.as_deref().ok_or_else(Failure::unauthorized)
+    }
+
+    fn is(&self, username: &str) -> bool {
+        self.username.as_deref() == Some(username)
+    }
+
+    /// Writes and private reads are only allowed on the caller's own account.
+    fn owner(&self, username: &str) -> Result<(), Failure> {
+        if self.user()? == username {
+            Ok(())
+        } else {
+            Err(Failure::forbidden())
+        }
+    }
+}
+
+pub(super) fn router(server: Arc<Server>) -> Router {
+    Router::new()
+        .route("/", get(about))
+        .route("/oauth/identity", get(identity))
+        // Database
+        .route("/artists/{artist_id}", get(get_artist))
+        .route("/artists/{artist_id}/releases", get(get_artist_releases))
+        .route("/releases/{release_id}", get(get_release))
+        .route(
+            "/releases/{release_id}/rating/{username}",
+            get(get_release_rating)
+                .put(set_release_rating)
+                .delete(delete_release_rating),
+        )
+        .route("/releases/{release_id}/rating", get(get_community_rating))
+        .route("/releases/{release_id}/stats", get(get_release_stats))
+        .route("/masters/{master_id}", get(get_master))
+        .route("/masters/{master_id}/versions", get(get_master_versions))
+        .route("/labels/{label_id}", get(get_label))
+        .route("/labels/{label_id}/releases", get(get_label_releases))
+        .route("/database/search", get(search))
+        // User identity and lists
+        .route("/users/{username}", get(get_profile).post(edit_profile))
+        .route("/users/{username}/inventory", get(get_inventory))
+        .route("/users/{username}/contributions", get(get_contributions))
+        .route("/users/{username}/submissions", get(get_submissions))
+        .route("/users/{username}/lists", get(get_user_lists))
+        .route("/lists/{list_id}", get(get_list))
+        // Collection
+        .route(
+            "/users/{username}/collection/folders",
+            get(get_folders).post(add_folder),
+        )
+        .route(
+            "/users/{username}/collection/folders/{folder_id}",
+            get(get_folder).post(rename_folder).delete(delete_folder),
+        )
+        .route(
+            "/users/{username}/collection/folders/{folder_id}/releases",
+            get(get_folder_releases),
+        )
+        .route(
+            "/users/{username}/collection/folders/{folder_id}/releases/{release_id}",
+            post(add_to_collection),
+        )
+        .route(
+            "/users/{username}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}",
+            post(edit_instance).delete(remove_instance),
+        )
+        .route(
+            "/users/{username}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}/fields/{field_id}",
+            post(edit_instance_note),
+        )
+        .route(
+            "/users/{username}/collection/releases/{release_id}",
+            get(get_release_instances),
+        )
+        .route("/users/{username}/collection/fields", get(get_fields))
+        .route("/users/{username}/collection/value", get(get_value))
+        // Wantlist
+        .route("/users/{username}/wants", get(get_wants))
+        .route(
+            "/users/{username}/wants/{release_id}",
+            get(get_want).put(add_want).post(edit_want).delete(remove_want),
+        )
+        // Marketplace
+        .route("/marketplace/listings", post(add_listing))
+        .route(
+            "/marketplace/listings/{listing_id}",
+            get(get_listing).post(edit_listing).delete(delete_listing),
+        )
+        .route("/marketplace/orders", get(get_orders))
+        .route("/marketplace/orders/{order_id}", get(get_order).post(edit_order))
+        .route(
+            "/marketplace/orders/{order_id}/messages",
+            get(get_order_messages).post(add_order_message),
+        )
+        .route("/marketplace/fee/{amount}", get(get_fee))
+        .route("/marketplace/fee/{amount}/{currency}", get(get_fee_in))
+        .route(
+            "/marketplace/price_suggestions/{release_id}",
+            get(get_price_suggestions),
+        )
+        .route("/marketplace/stats/{release_id}", get(get_marketplace_stats))
+        // Inventory export
+        .route("/inventory/export", get(get_exports).post(export_inventory))
+        .route("/inventory/export/{export_id}", get(get_export))
+        .route("/inventory/export/{export_id}/download", get(download_export))
+        .fallback(|| async { Failure::not_found("The requested resource was not found.") })
+        .layer(middleware::from_fn_with_state(Arc::clone(&server), gate))
+        .with_state(server)
+}
+
+/// Authenticates the caller, applies the rate limit and stamps the rate-limit headers.
+async fn gate(State(server): Shared, mut request: Request, next: Next) -> Response {
+    let header = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    let caller = match server.identify(header) {
+        Ok(caller) => caller,
+        Err(failure) => return failure.into_response(),
+    };
+
+    let quota = server.take_quota(&caller);
+    let mut response = if quota.allowed {
+        request.extensions_mut().insert(caller);
+        next.run(request).await
+    } else {
+        Failure::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "You are making requests too quickly.",
+        )
+        .into_response()
+    };
+
+    let headers = response.headers_mut();
+    headers.insert("x-discogs-ratelimit", HeaderValue::from(quota.limit));
+    headers.insert("x-discogs-ratelimit-used", HeaderValue::from(quota.used));
+    headers.insert(
+        "x-discogs-ratelimit-remaining",
+        HeaderValue::from(quota.limit - quota.used),
+    );
+    response
+}
+
+fn ok<T: Serialize>(value: T) -> Reply {
+    Ok(Json(value).into_response())
+}
+
+fn created<T: Serialize>(value: T) -> Reply {
+    Ok((StatusCode::CREATED, Json(value)).into_response())
+}
+
+fn no_content() -> Reply {
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+fn body<T>(body: Result<Json<T>, JsonRejection>) -> Result<T, Failure> {
+    body.map(|Json(value)| value)
+        .map_err(|rejection| Failure::invalid("body", rejection.body_text()))
+}
+
+/// Whether a `key=value` filter from the query matches one of `values`, ignoring case.
+/// Filters that are not in the query always match.
+fn matches<'a>(params: &Params, key: &str, mut values: impl Iterator<Item = &'a str>) -> bool {
+    params
+        .get(key)
+        .is_none_or(|wanted| values.any(|value| value.eq_ignore_ascii_case(wanted)))
+}
+
+fn credited(artists: &Option<Vec<ArtistCredit>>, artist_id: u64) -> bool {
+    artists
+        .iter()
+        .flatten()
+        .any(|artist| artist.id == artist_id)
+}
+
+fn now() -> String {
+    timestamp(SystemTime::now())
+}
+
+async fn about(State(server): Shared) -> Reply {
+    let store = server.store();
+    ok(json!({
+        "hello": "Welcome to the Discogs API.",
+        "api_version": "v2",
+        "documentation_url": "https://www.discogs.com/developers/",
+        "statistics": {
+            "releases": store.releases.len(),
+            "artists": store.artists.len(),
+            "labels": store.labels.len(),
+        },
+    }))
+}
+
+async fn identity(State(server): Shared, Extension(caller): Extension<Caller>) -> Reply {
+    let username = caller.user()?;
+    let store = server.store();
+    let user = store.user(username)?;
+    ok(Identity {
+        id: user.id,
+        username: user.username.clone(),
+        resource_url: store.url(&format!("/users/{username}")).into(),
+        consumer_name: caller.consumer.clone(),
+        extra: ExtraFields::new(),
+    })
+}
+
+// Database
+
+async fn get_artist(State(server): Shared, Path(artist_id): Path<u64>) -> Reply {
+    let store = server.store();
+    ok(store
+        .artists
+        .get(&artist_id)
+        .ok_or_else(|| Failure::not_found("Artist not found."))?)
+}
+
+async fn get_artist_releases(
+    State(server): Shared,
+    Path(artist_id): Path<u64>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    let artist = store
+        .artists
+        .get(&artist_id)
+        .ok_or_else(|| Failure::not_found("Artist not found."))?;
+
+    // Masters stand in for all of their versions, as on Discogs.
+    let masters = store
+        .masters
+        .values()
+        .filter(|master| credited(&master.artists, artist_id))
+        .map(|master| ArtistRelease {
+            artist: Some(artist.name.clone()),
+            id: master.id,
+            main_release: master.main_release,
+            resource_url: master.resource_url.to_string(),
+            role: Some("Main".to_string()),
+            thumb: None,
+            title: master.title.clone(),
+            r#type: Some("master".to_string()),
+            year: master.year,
+            extra: ExtraFields::new(),
+        });
+    let releases = store
+        .releases
+        .values()
+        .filter(|release| release.master_id.is_none() && credited(&release.artists, artist_id))
+        .map(|release| ArtistRelease {
+            artist: Some(artist.name.clone()),
+            id: release.id,
+            main_release: None,
+            resource_url: release.resource_url.to_string(),
+            role: Some("Main".to_string()),
+            thumb: release.thumb.clone(),
+            title: release.title.clone(),
+            r#type: Some("release".to_string()),
+            year: release.year,
+            extra: ExtraFields::new(),
+        });
+    let mut items: Vec<ArtistRelease> = masters.chain(releases).collect();
+    sort_items(&mut items, &params, |release, sort| match sort {
+        "year" => format!("{:06}", release.year.unwrap_or_default()),
+        "title" => release.title.to_lowercase(),
+        _ => String::new(),
+    });
+
+    let (releases, pagination) = paginate(items, &params, &store.base_url, &uri);
+    ok(ArtistReleases {
+        releases,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_release(State(server): Shared, Path(release_id): Path<u64>) -> Reply {
+    let store = server.store();
+    ok(store.release(release_id)?)
+}
+
+#[derive(Deserialize)]
+struct RatingBody {
+    rating: u8,
+}
+
+async fn get_release_rating(
+    State(server): Shared,
+    Path((release_id, username)): Path<(u64, String)>,
+) -> Reply {
+    let store = server.store();
+    store.release(release_id)?;
+    store.user(&username)?;
+    let rating = store
+        .ratings
+        .get(&(release_id, username.clone()))
+        .copied()
+        .unwrap_or_default();
+    ok(ReleaseRating {
+        username,
+        release_id,
+        rating,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn set_release_rating(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((release_id, username)): Path<(u64, String)>,
+    request: Result<Json<RatingBody>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let RatingBody { rating } = body(request)?;
+    if !(1..=5).contains(&rating) {
+        return Err(Failure::invalid(
+            "rating",
+            "Rating must be between 1 and 5.",
+        ));
+    }
+    let mut store = server.store();
+    store.release(release_id)?;
+    store.ratings.insert((release_id, username.clone()), rating);
+    created(ReleaseRating {
+        username,
+        release_id,
+        rating,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn delete_release_rating(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((release_id, username)): Path<(u64, String)>,
+) -> Reply {
+    caller.owner(&username)?;
+    let mut store = server.store();
+    store.release(release_id)?;
+    store.ratings.remove(&(release_id, username));
+    no_content()
+}
+
+async fn get_community_rating(State(server): Shared, Path(release_id): Path<u64>) -> Reply {
+    let store = server.store();
+    store.release(release_id)?;
+    let ratings: Vec<u8> = store
+        .ratings
+        .iter()
+        .filter(|((rated, _), _)| *rated == release_id)
+        .map(|(_, rating)| *rating)
+        .collect();
+    let average = if ratings.is_empty() {
+        0.0
+    } else {
+        let total: u32 = ratings.iter().map(|rating| u32::from(*rating)).sum();
+        (f64::from(total) / ratings.len() as f64 * 100.0).round() / 100.0
+    };
+    ok(ReleaseCommunityRating {
+        release_id,
+        rating: RatingSummary {
+            average,
+            count: ratings.len() as u64,
+        },
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_release_stats(State(server): Shared, Path(release_id): Path<u64>) -> Reply {
+    let store = server.store();
+    store.release(release_id)?;
+    let count = |owns: &dyn Fn(&str) -> bool| {
+        store
+            .users
+            .iter()
+            .filter(|user| owns(&user.username))
+            .count() as u64
+    };
+    ok(ReleaseStats {
+        num_have: count(&|username| store.in_collection(username, release_id)),
+        num_want: count(&|username| store.in_wantlist(username, release_id)),
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_master(State(server): Shared, Path(master_id): Path<u64>) -> Reply {
+    let store = server.store();
+    ok(store
+        .masters
+        .get(&master_id)
+        .ok_or_else(|| Failure::not_found("Master not found."))?)
+}
+
+async fn get_master_versions(
+    State(server): Shared,
+    Path(master_id): Path<u64>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    if !store.masters.contains_key(&master_id) {
+        return Err(Failure::not_found("Master not found."));
+    }
+
+    let mut versions: Vec<MasterVersion> = store
+        .releases
+        .values()
+        .filter(|release| release.master_id == Some(master_id))
+        .map(|release| {
+            let label = release.labels.iter().flatten().next();
+            let formats: Vec<String> = release
+                .formats
+                .iter()
+                .flatten()
+                .map(|format| format.name.clone())
+                .collect();
+            MasterVersion {
+                status: release.status,
+                stats: None,
+                thumb: release.thumb.clone(),
+                format: Some(formats.join(", ")),
+                country: release.country.clone(),
+                title: release.title.clone(),
+                label: label.map(|label| label.name.clone()),
+                released: release.released.clone(),
+                major_formats: Some(formats),
+                catno: label.and_then(|label| label.catno.clone()),
+                resource_url: release.resource_url.clone(),
+                id: release.id,
+                extra: ExtraFields::new(),
+            }
+        })
+        .filter(|version| {
+            matches(
+                &params,
+                "format",
+                version.major_formats.iter().flatten().map(String::as_str),
+            ) && matches(&params, "label", version.label.as_deref().into_iter())
+                && matches(&params, "released", version.released.as_deref().into_iter())
+                && matches(&params, "country", version.country.as_deref().into_iter())
+        })
+        .collect();
+    sort_items(&mut versions, &params, |version, sort| {
+        match sort {
+            "released" => version.released.clone(),
+            "title" => Some(version.title.to_lowercase()),
+            "format" => version.format.clone(),
+            "label" => version.label.clone(),
+            "catno" => version.catno.clone(),
+            "country" => version.country.clone(),
+            _ => None,
+        }
+        .unwrap_or_default()
+    });
+
+    let (versions, pagination) = paginate(versions, &params, &store.base_url, &uri);
+    ok(MasterVersions {
+        versions,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_label(State(server): Shared, Path(label_id): Path<u64>) -> Reply {
+    let store = server.store();
+    ok(store
+        .labels
+        .get(&label_id)
+        .ok_or_else(|| Failure::not_found("Label not found."))?)
+}
+
+async fn get_label_releases(
+    State(server): Shared,
+    Path(label_id): Path<u64>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    if !store.labels.contains_key(&label_id) {
+        return Err(Failure::not_found("Label not found."));
+    }
+
+    let releases: Vec<LabelRelease> = store
+        .releases
+        .values()
+        .filter_map(|release| {
+            let label = release
+                .labels
+                .iter()
+                .flatten()
+                .find(|label| label.id == label_id)?;
+            Some(LabelRelease {
+                artist: Some(artist_names(release)),
+                catno: label.catno.clone(),
+                format: release
+                    .formats
+                    .iter()
+                    .flatten()
+                    .next()
+                    .map(|format| format.name.clone()),
+                id: release.id,
+                resource_url: release.resource_url.clone(),
+                status: release.status,
+                thumb: release.thumb.clone(),
+                title: release.title.clone(),
+                year: release.year,
+                extra: ExtraFields::new(),
+            })
+        })
+        .collect();
+
+    let (releases, pagination) = paginate(releases, &params, &store.base_url, &uri);
+    ok(LabelReleases {
+        releases,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+/// Search parameters matched as case-insensitive substrings of the indexed fields.
+const SEARCH_FILTERS: [&str; 11] = [
+    "title",
+    "release_title",
+    "artist",
+    "label",
+    "genre",
+    "style",
+    "country",
+    "year",
+    "format",
+    "catno",
+    "barcode",
+];
+
+fn search_result(
+    id: u64,
+    kind: &str,
+    title: String,
+    uri: String,
+    resource_url: String,
+) -> SearchResult {
+    SearchResult {
+        id,
+        result_type: kind.to_string(),
+        user_data: None,
+        master_id: None,
+        master_url: None,
+        uri,
+        title,
+        thumb: None,
+        cover_image: None,
+        resource_url,
+        country: None,
+        year: None,
+        format: None,
+        label: None,
+        genre: None,
+        style: None,
+        barcode: None,
+        catno: None,
+        community: None,
+        format_quantity: None,
+        formats: None,
+        extra: ExtraFields::new(),
+    }
+}
+
+fn release_search_fields(release: &Release) -> BTreeMap<&'static str, Vec<String>> {
+    let labels = release.labels.iter().flatten();
+    BTreeMap::from([
+        (
+            "title",
+            vec![format!("{} - {}", artist_names(release), release.title)],
+        ),
+        ("release_title", vec![release.title.clone()]),
+        (
+            "artist",
+            release
+                .artists
+                .iter()
+                .flatten()
+                .map(|artist| artist.name.clone())
+                .collect(),
+        ),
+        (
+            "label",
+            labels.clone().map(|label| label.name.clone()).collect(),
+        ),
+        (
+            "catno",
+            labels.filter_map(|label| label.catno.clone()).collect(),
+        ),
+        ("genre", release.genres.clone().unwrap_or_default()),
+        ("style", release.styles.clone().unwrap_or_default()),
+        ("country", release.country.iter().cloned().collect()),
+        (
+            "year",
+            release.year.iter().map(ToString::to_string).collect(),
+        ),
+        (
+            "format",
+            release
+                .formats
+                .iter()
+                .flatten()
+                .map(|format| format.name.clone())
+                .collect(),
+        ),
+        (
+            "barcode",
+            release
+                .identifiers
+                .iter()
+                .flatten()
+                .filter(|identifier| identifier.id_type == "Barcode")
+                .map(|identifier| identifier.value.clone())
+                .collect(),
+        ),
+    ])
+}
+
+async fn search(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    caller.require(AuthLevel::Consumer)?;
+    let store = server.store();
+
+    let mut documents = Vec::new();
+    for release in store.releases.values() {
+        let fields = release_search_fields(release);
+        let mut result = search_result(
+            release.id,
+            "release",
+            fields["title"][0].clone(),
+            release
+                .uri
+                .clone()
+                .unwrap_or_else(|| format!("/release/{}", release.id)),
+            release.resource_url.to_string(),
+        );
+        result.master_id = release.master_id;
+        result.master_url = release.master_url.clone();
+        result.thumb = release.thumb.clone();
+        result.country = release.country.clone();
+        result.year = release.year.map(|year| year.to_string());
+        result.format = Some(fields["format"].clone());
+        result.label = Some(fields["label"].clone());
+        result.genre = release.genres.clone();
+        result.style = release.styles.clone();
+        result.barcode = Some(fields["barcode"].clone());
+        result.catno = fields["catno"].first().cloned();
+        result.formats = release.formats.clone();
+        result.user_data = caller
+            .username
+            .as_deref()
+            .map(|username| SearchResultUserData {
+                in_wantlist: Some(store.in_wantlist(username, release.id)),
+                in_collection: Some(store.in_collection(username, release.id)),
+            });
+        documents.push((result, fields));
+    }
+    for master in store.masters.values() {
+        let artists: Vec<String> = master
+            .artists
+            .iter()
+            .flatten()
+            .map(|artist| artist.name.clone())
+            .collect();
+        let title = format!("{} - {}", artists.join(", "), master.title);
+        let mut result = search_result(
+            master.id,
+            "master",
+            title.clone(),
+            master
+                .uri
+                .clone()
+                .unwrap_or_else(|| format!("/master/{}", master.id)),
+            master.resource_url.to_string(),
+        );
+        result.year = master.year.map(|year| year.to_string());
+        result.genre = master.genres.clone();
+        result.style = master.styles.clone();
+        let fields = BTreeMap::from([
+            ("title", vec![title]),
+            ("artist", artists),
+            ("genre", master.genres.clone().unwrap_or_default()),
+            ("style", master.styles.clone().unwrap_or_default()),
+            (
+                "year",
+                master.year.iter().map(ToString::to_string).collect(),
+            ),
+        ]);
+        documents.push((result, fields));
+    }
+    for artist in store.artists.values() {
+        let result = search_result(
+            artist.id,
+            "artist",
+            artist.name.clone(),
+            artist
+                .uri
+                .clone()
+                .unwrap_or_else(|| format!("/artist/{}", artist.id)),
+            artist.resource_url.to_string(),
+        );
+        let fields = BTreeMap::from([
+            ("title", vec![artist.name.clone()]),
+            ("artist", vec![artist.name.clone()]),
+        ]);
+        documents.push((result, fields));
+    }
+    for label in store.labels.values() {
+        let result = search_result(
+            label.id,
+            "label",
+            label.name.clone(),
+            label
+                .uri
+                .clone()
+                .unwrap_or_else(|| format!("/label/{}", label.id)),
+            label.resource_url.to_string(),
+        );
+        let fields = BTreeMap::from([
+            ("title", vec![label.name.clone()]),
+            ("label", vec![label.name.clone()]),
+        ]);
+        documents.push((result, fields));
+    }
+
+    let contains = |values: &[String], wanted: &str| {
+        let wanted = wanted.to_lowercase();
+        values
+            .iter()
+            .any(|value| value.to_lowercase().contains(&wanted))
+    };
+    let results: Vec<SearchResult> = documents
+        .into_iter()
+        .filter(|(result, fields)| {
+            params
+                .get("type")
+                .is_none_or(|kind| result.result_type == *kind)
+                && params
+                    .get("q")
+                    .is_none_or(|query| fields.values().any(|values| contains(values, query)))
+                && SEARCH_FILTERS.iter().all(|filter| {
+                    params.get(*filter).is_none_or(|wanted| {
+                        fields
+                            .get(filter)
+                            .is_some_and(|values| contains(values, wanted))
+                    })
+                })
+        })
+        .map(|(result, _)| result)
+        .collect();
+
+    let (results, pagination) = paginate(results, &params, &store.base_url, &uri);
+    ok(SearchResults {
+        results,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+// User identity and lists
+
+async fn get_profile(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+) -> Reply {
+    let store = server.store();
+    ok(store.profile(&username, caller.is(&username))?)
+}
+
+async fn edit_profile(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+    request: Result<Json<EditProfileRequest>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let request = body(request)?;
+    let mut store = server.store();
+    if let Some(profile) = store.profiles.get_mut(&username) {
+        if request.name.is_some() {
+            profile.name = request.name;
+        }
+        if request.home_page.is_some() {
+            profile.home_page = request.home_page;
+        }
+        if request.location.is_some() {
+            profile.location = request.location;
+        }
+        if request.profile.is_some() {
+            profile.profile = request.profile;
+        }
+        if request.curr_abbr.is_some() {
+            profile.curr_abbr = request.curr_abbr;
+        }
+    }
+    ok(store.profile(&username, true)?)
+}
+
+async fn get_inventory(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    // Sellers also see their drafts and expired listings.
+    let is_owner = caller.is(&username);
+    let mut listings: Vec<Listing> = store
+        .listings
+        .values()
+        .filter(|listing| seller(listing) == Some(username.as_str()))
+        .filter(|listing| is_owner || is_for_sale(listing))
+        .filter(|listing| {
+            matches(
+                &params,
+                "status",
+                listing.status.as_ref().map(label).as_deref().into_iter(),
+            )
+        })
+        .cloned()
+        .collect();
+    sort_items(&mut listings, &params, |listing, sort| match sort {
+        "listed" => listing.posted.clone().unwrap_or_default(),
+        "price" => format!(
+            "{:015.2}",
+            listing
+                .price
+                .as_ref()
+                .and_then(|price| price.value)
+                .unwrap_or_default()
+        ),
+        "item" => listing
+            .release
+            .as_ref()
+            .map(|release| release.title.to_lowercase())
+            .unwrap_or_default(),
+        "status" => listing.status.as_ref().map(label).unwrap_or_default(),
+        _ => String::new(),
+    });
+
+    let (listings, pagination) = paginate(listings, &params, &store.base_url, &uri);
+    ok(UserInventory {
+        listings,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_contributions(
+    State(server): Shared,
+    Path(username): Path<String>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    let (contributions, pagination) = paginate(Vec::new(), &params, &store.base_url, &uri);
+    ok(UserContributions {
+        contributions,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_submissions(
+    State(server): Shared,
+    Path(username): Path<String>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    let (_, pagination) = paginate(Vec::<()>::new(), &params, &store.base_url, &uri);
+    ok(UserSubmissions {
+        submissions: UserSubmissionsPayload {
+            artists: Vec::new(),
+            labels: Vec::new(),
+            releases: Vec::new(),
+            extra: ExtraFields::new(),
+        },
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_user_lists(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    let is_owner = caller.is(&username);
+    let lists: Vec<UserListSummary> = store
+        .lists
+        .values()
+        .filter(|list| owner(&list.extra) == Some(username.as_str()))
+        .filter(|list| is_owner || list.public != Some(false))
+        .map(|list| UserListSummary {
+            id: list.list_id,
+            name: list.name.clone(),
+            resource_url: list.resource_url.clone(),
+            uri: list.url.clone(),
+            public: list.public,
+            description: list.description.clone(),
+            date_added: list.created_ts.clone(),
+            date_changed: list.modified_ts.clone(),
+            extra: ExtraFields::new(),
+        })
+        .collect();
+
+    let (lists, pagination) = paginate(lists, &params, &store.base_url, &uri);
+    ok(UserLists {
+        lists,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_list(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(list_id): Path<u64>,
+) -> Reply {
+    let store = server.store();
+    let list = store
+        .lists
+        .get(&list_id)
+        .ok_or_else(|| Failure::not_found("List not found."))?;
+    if list.public == Some(false) && !owner(&list.extra).is_some_and(|owner| caller.is(owner)) {
+        return Err(Failure::forbidden());
+    }
+    ok(list)
+}
+
+// Collection
+
+/// Other users only see folder 0, as with a public collection.
+fn visible_folder(caller: &Caller, username: &str, folder_id: u64) -> Result<(), Failure> {
+    if folder_id == ALL_FOLDER {
+        Ok(())
+    } else {
+        caller.owner(username)
+    }
+}
+
+fn folder<'a>(store: &'a Store, username: &str, folder_id: u64) -> Result<&'a Folder, Failure> {
+    store
+        .folders(username)?
+        .iter()
+        .find(|folder| folder.id == folder_id)
+        .ok_or_else(|| Failure::not_found("Folder not found."))
+}
+
+fn instance_index(
+    store: &Store,
+    username: &str,
+    (folder_id, release_id, instance_id): (u64, u64, u64),
+) -> Result<usize, Failure> {
+    store
+        .instances
+        .iter()
+        .position(|instance| {
+            instance.username == username
+                && instance.instance_id == instance_id
+                && instance.release_id == release_id
+                && (folder_id == ALL_FOLDER || instance.folder_id == folder_id)
+        })
+        .ok_or_else(|| Failure::not_found("Release instance not found."))
+}
+
+fn folder_name(request: Result<Json<AddFolderRequest>, JsonRejection>) -> Result<String, Failure> {
+    let name = body(request)?.name;
+    if name.trim().is_empty() {
+        return Err(Failure::invalid("name", "Folder name must not be empty."));
+    }
+    Ok(name)
+}
+
+async fn get_folders(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+) -> Reply {
+    let store = server.store();
+    let is_owner = caller.is(&username);
+    let folders = store
+        .folders(&username)?
+        .iter()
+        .filter(|folder| is_owner || folder.id == ALL_FOLDER)
+        .map(|folder| store.folder_view(&username, folder))
+        .collect();
+    ok(CollectionFolders {
+        folders,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn add_folder(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+    request: Result<Json<AddFolderRequest>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let name = folder_name(request)?;
+    let mut store = server.store();
+    store.user(&username)?;
+    let folders = store.folders.entry(username.clone()).or_default();
+    let folder = Folder {
+        id: folders
+            .iter()
+            .map(|folder| folder.id)
+            .max()
+            .unwrap_or_default()
+            + 1,
+        name,
+    };
+    folders.push(folder.clone());
+    created(store.folder_view(&username, &folder))
+}
+
+async fn get_folder(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id)): Path<(String, u64)>,
+) -> Reply {
+    visible_folder(&caller, &username, folder_id)?;
+    let store = server.store();
+    let folder = folder(&store, &username, folder_id)?;
+    ok(store.folder_view(&username, folder))
+}
+
+async fn rename_folder(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id)): Path<(String, u64)>,
+    request: Result<Json<AddFolderRequest>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let name = folder_name(request)?;
+    if folder_id <= 1 {
+        return Err(Failure::new(
+            StatusCode::BAD_REQUEST,
+            "The All and Uncategorized folders cannot be renamed.",
+        ));
+    }
+    let mut store = server.store();
+    let mut renamed = folder(&store, &username, folder_id)?.clone();
+    renamed.name = name;
+    if let Some(folders) = store.folders.get_mut(&username) {
+        for folder in folders.iter_mut().filter(|folder| folder.id == folder_id) {
+            folder.name = renamed.name.clone();
+        }
+    }
+    ok(store.folder_view(&username, &renamed))
+}
+
+async fn delete_folder(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id)): Path<(String, u64)>,
+) -> Reply {
+    caller.owner(&username)?;
+    if folder_id <= 1 {
+        return Err(Failure::new(
+            StatusCode::BAD_REQUEST,
+            "The All and Uncategorized folders cannot be deleted.",
+        ));
+    }
+    let mut store = server.store();
+    let folder = folder(&store, &username, folder_id)?;
+    if store.folder_view(&username, folder).count > 0 {
+        return Err(Failure::new(
+            StatusCode::BAD_REQUEST,
+            "Folder must be empty before it can be deleted.",
+        ));
+    }
+    if let Some(folders) = store.folders.get_mut(&username) {
+        folders.retain(|folder| folder.id != folder_id);
+    }
+    no_content()
+}
+
+async fn get_folder_releases(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id)): Path<(String, u64)>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    visible_folder(&caller, &username, folder_id)?;
+    let store = server.store();
+    folder(&store, &username, folder_id)?;
+
+    let mut releases: Vec<_> = store
+        .instances
+        .iter()
+        .filter(|instance| {
+            instance.username == username
+                && (folder_id == ALL_FOLDER || instance.folder_id == folder_id)
+        })
+        .map(|instance| store.collection_release(instance))
+        .collect();
+    sort_items(&mut releases, &params, |release, sort| {
+        let info = release.basic_information.as_ref();
+        match sort {
+            "added" => format!(
+                "{}{:020}",
+                release.date_added.clone().unwrap_or_default(),
+                release.instance_id
+            ),
+            "title" => info
+                .map(|info| info.title.to_lowercase())
+                .unwrap_or_default(),
+            "year" => format!("{:06}", info.and_then(|info| info.year).unwrap_or_default()),
+            "artist" => info
+                .and_then(|info| info.artists.as_ref()?.first())
+                .map(|artist| artist.name.to_lowercase())
+                .unwrap_or_default(),
+            "rating" => release.rating.unwrap_or_default().to_string(),
+            _ => String::new(),
+        }
+    });
+
+    let (releases, pagination) = paginate(releases, &params, &store.base_url, &uri);
+    ok(CollectionReleases {
+        releases,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn add_to_collection(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id, release_id)): Path<(String, u64, u64)>,
+) -> Reply {
+    caller.owner(&username)?;
+    if folder_id == ALL_FOLDER {
+        return Err(Failure::new(
+            StatusCode::BAD_REQUEST,
+            "Releases cannot be added to the All folder.",
+        ));
+    }
+    let mut store = server.store();
+    store.release(release_id)?;
+    folder(&store, &username, folder_id)?;
+
+    let instance_id = store.next_id();
+    store.instances.push(Instance {
+        username: username.clone(),
+        instance_id,
+        release_id,
+        folder_id,
+        rating: 0,
+        notes: BTreeMap::new(),
+        date_added: now(),
+    });
+    created(AddCollectionReleaseResponse {
+        instance_id,
+        resource_url: store.url(&format!(
+            "/users/{username}/collection/folders/{folder_id}/releases/{release_id}/instances/{instance_id}"
+        )),
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn edit_instance(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id, release_id, instance_id)): Path<(String, u64, u64, u64)>,
+    request: Result<Json<EditCollectionReleaseRequest>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let request = body(request)?;
+    if request.rating.is_some_and(|rating| rating > 5) {
+        return Err(Failure::invalid(
+            "rating",
+            "Rating must be between 0 and 5.",
+        ));
+    }
+    let mut store = server.store();
+    if let Some(target) = request.folder_id {
+        if target == ALL_FOLDER || folder(&store, &username, target).is_err() {
+            return Err(Failure::invalid("folder_id", "Folder does not exist."));
+        }
+    }
+    let index = instance_index(&store, &username, (folder_id, release_id, instance_id))?;
+    let instance = &mut store.instances[index];
+    if let Some(rating) = request.rating {
+        instance.rating = rating;
+    }
+    if let Some(folder_id) = request.folder_id {
+        instance.folder_id = folder_id;
+    }
+    no_content()
+}
+
+async fn remove_instance(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id, release_id, instance_id)): Path<(String, u64, u64, u64)>,
+) -> Reply {
+    caller.owner(&username)?;
+    let mut store = server.store();
+    let index = instance_index(&store, &username, (folder_id, release_id, instance_id))?;
+    store.instances.remove(index);
+    no_content()
+}
+
+async fn edit_instance_note(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, folder_id, release_id, instance_id, field_id)): Path<(
+        String,
+        u64,
+        u64,
+        u64,
+        u64,
+    )>,
+    request: Result<Json<EditInstanceNoteRequest>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let value = body(request)?.value;
+    let field = collection_fields()
+        .into_iter()
+        .find(|field| field.id == field_id)
+        .ok_or_else(|| Failure::not_found("Field not found."))?;
+    if field
+        .options
+        .as_ref()
+        .is_some_and(|options| !options.contains(&value))
+    {
+        return Err(Failure::invalid(
+            "value",
+            format!("{value} is not an option of {}.", field.name),
+        ));
+    }
+    let mut store = server.store();
+    let index = instance_index(&store, &username, (folder_id, release_id, instance_id))?;
+    store.instances[index].notes.insert(field_id, value);
+    no_content()
+}
+
+async fn get_release_instances(
+    State(server): Shared,
+    Path((username, release_id)): Path<(String, u64)>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    let releases = store
+        .instances
+        .iter()
+        .filter(|instance| instance.username == username && instance.release_id == release_id)
+        .map(|instance| store.collection_release(instance))
+        .collect();
+
+    let (releases, pagination) = paginate(releases, &params, &store.base_url, &uri);
+    ok(CollectionReleases {
+        releases,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_fields(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    let is_owner = caller.is(&username);
+    ok(CollectionFields {
+        fields: collection_fields()
+            .into_iter()
+            .filter(|field| is_owner || field.public)
+            .collect(),
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_value(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+) -> Reply {
+    caller.owner(&username)?;
+    let store = server.store();
+    let median: f64 = store
+        .instances
+        .iter()
+        .filter(|instance| instance.username == username)
+        .filter_map(|instance| store.releases.get(&instance.release_id)?.lowest_price)
+        .sum();
+    let dollars = |value: f64| format!("${value:.2}");
+    ok(CollectionValue {
+        minimum: dollars(median * 0.6),
+        median: dollars(median),
+        maximum: dollars(median * 1.6),
+        extra: ExtraFields::new(),
+    })
+}
+
+// Wantlist
+
+fn want_update(
+    request: Result<Json<WantlistUpdateRequest>, JsonRejection>,
+) -> Result<WantlistUpdateRequest, Failure> {
+    let request = body(request)?;
+    if request.rating.is_some_and(|rating| rating > 5) {
+        return Err(Failure::invalid(
+            "rating",
+            "Rating must be between 0 and 5.",
+        ));
+    }
+    Ok(request)
+}
+
+fn apply_want_update(want: &mut Want, request: WantlistUpdateRequest) {
+    if request.notes.is_some() {
+        want.notes = request.notes;
+    }
+    if let Some(rating) = request.rating {
+        want.rating = rating;
+    }
+}
+
+async fn get_wants(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(username): Path<String>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    let is_owner = caller.is(&username);
+    let mut wants: Vec<_> = store
+        .wants
+        .get(&username)
+        .into_iter()
+        .flatten()
+        .map(|want| store.want_entry(want, is_owner))
+        .collect();
+    sort_items(&mut wants, &params, |want, sort| {
+        let info = want.basic_information.as_ref();
+        match sort {
+            "title" => info
+                .map(|info| info.title.to_lowercase())
+                .unwrap_or_default(),
+            "year" => format!("{:06}", info.and_then(|info| info.year).unwrap_or_default()),
+            "rating" => want.rating.unwrap_or_default().to_string(),
+            _ => String::new(),
+        }
+    });
+
+    let (wants, pagination) = paginate(wants, &params, &store.base_url, &uri);
+    ok(WantlistEntries {
+        wants,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_want(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, release_id)): Path<(String, u64)>,
+) -> Reply {
+    let store = server.store();
+    store.user(&username)?;
+    let want = store
+        .wants
+        .get(&username)
+        .and_then(|wants| wants.iter().find(|want| want.release_id == release_id))
+        .ok_or_else(|| Failure::not_found("Release is not in the wantlist."))?;
+    ok(store.want_entry(want, caller.is(&username)))
+}
+
+async fn add_want(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, release_id)): Path<(String, u64)>,
+    request: Result<Json<WantlistUpdateRequest>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let request = want_update(request)?;
+    let mut store = server.store();
+    store.release(release_id)?;
+
+    let wants = store.wants.entry(username).or_default();
+    let index = match wants.iter().position(|want| want.release_id == release_id) {
+        Some(index) => index,
+        None => {
+            wants.push(Want {
+                release_id,
+                notes: None,
+                rating: 0,
+            });
+            wants.len() - 1
+        }
+    };
+    apply_want_update(&mut wants[index], request);
+    let want = wants[index].clone();
+    created(store.want_entry(&want, true))
+}
+
+async fn edit_want(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, release_id)): Path<(String, u64)>,
+    request: Result<Json<WantlistUpdateRequest>, JsonRejection>,
+) -> Reply {
+    caller.owner(&username)?;
+    let request = want_update(request)?;
+    let mut store = server.store();
+    let want = store
+        .wants
+        .get_mut(&username)
+        .and_then(|wants| wants.iter_mut().find(|want| want.release_id == release_id))
+        .ok_or_else(|| Failure::not_found("Release is not in the wantlist."))?;
+    apply_want_update(want, request);
+    let want = want.clone();
+    ok(store.want_entry(&want, true))
+}
+
+async fn remove_want(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path((username, release_id)): Path<(String, u64)>,
+) -> Reply {
+    caller.owner(&username)?;
+    let mut store = server.store();
+    let wants = store
+        .wants
+        .get_mut(&username)
+        .filter(|wants| wants.iter().any(|want| want.release_id == release_id))
+        .ok_or_else(|| Failure::not_found("Release is not in the wantlist."))?;
+    wants.retain(|want| want.release_id != release_id);
+    no_content()
+}
+
+// Marketplace
+
+/// Checks a listing body and returns the release it is for.
+fn listing_release_for(store: &Store, request: &CreateListingRequest) -> Result<Release, Failure> {
+    if label(&request.status) == "Expired" {
+        return Err(Failure::invalid(
+            "status",
+            "Listings can only be posted as For Sale or Draft.",
+        ));
+    }
+    if request.price.is_nan() || request.price <= 0.0 {
+        return Err(Failure::invalid(
+            "price",
+            "Price must be greater than zero.",
+        ));
+    }
+    store
+        .releases
+        .get(&request.release_id)
+        .cloned()
+        .ok_or_else(|| Failure::invalid("release_id", "Release does not exist."))
+}
+
+fn fill_listing(listing: &mut Listing, release: &Release, request: CreateListingRequest) {
+    listing.status = Some(request.status);
+    listing.price = Some(price(request.price, Currency::Usd));
+    listing.condition = Some(request.condition);
+    listing.sleeve_condition = request.sleeve_condition;
+    listing.comments = request.comments;
+    listing.allow_offers = Some(request.allow_offers.unwrap_or_default());
+    listing.release = Some(listing_release(release));
+    if let Some(location) = request.location {
+        listing
+            .extra
+            .insert("location".to_string(), json!(location));
+    }
+    if let Some(external_id) = request.external_id {
+        listing
+            .extra
+            .insert("external_id".to_string(), json!(external_id));
+    }
+}
+
+fn own_listing<'a>(
+    store: &'a mut Store,
+    caller: &Caller,
+    listing_id: u64,
+) -> Result<&'a mut Listing, Failure> {
+    let username = caller.user()?;
+    let listing = store
+        .listings
+        .get_mut(&listing_id)
+        .ok_or_else(|| Failure::not_found("Listing not found."))?;
+    if seller(listing) != Some(username) {
+        return Err(Failure::forbidden());
+    }
+    Ok(listing)
+}
+
+async fn get_listing(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(listing_id): Path<u64>,
+) -> Reply {
+    let store = server.store();
+    let listing = store
+        .listings
+        .get(&listing_id)
+        .filter(|listing| is_for_sale(listing) || seller(listing).is_some_and(|s| caller.is(s)))
+        .ok_or_else(|| Failure::not_found("Listing not found."))?;
+    ok(listing)
+}
+
+async fn add_listing(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    request: Result<Json<CreateListingRequest>, JsonRejection>,
+) -> Reply {
+    let username = caller.user()?.to_string();
+    let request = body(request)?;
+    let mut store = server.store();
+    let release = listing_release_for(&store, &request)?;
+    let seller_id = store.user(&username)?.id;
+
+    let listing_id = store.next_id();
+    let resource_url = store.url(&format!("/marketplace/listings/{listing_id}"));
+    let mut listing = Listing {
+        id: listing_id,
+        status: None,
+        resource_url: Some(resource_url.clone().into()),
+        uri: Some(format!("https://www.discogs.com/sell/item/{listing_id}")),
+        price: None,
+        sleeve_condition: None,
+        condition: None,
+        posted: Some(now()),
+        comments: None,
+        allow_offers: None,
+        ships_from: None,
+        audio: Some(false),
+        release: None,
+        extra: ExtraFields::from([(
+            "seller".to_string(),
+            json!({
+                "id": seller_id,
+                "username": username,
+                "resource_url": store.url(&format!("/users/{username}")),
+            }),
+        )]),
+    };
+    fill_listing(&mut listing, &release, request);
+    store.listings.insert(listing_id, listing);
+    created(CreateListingResponse {
+        listing_id,
+        resource_url: resource_url.into(),
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn edit_listing(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(listing_id): Path<u64>,
+    request: Result<Json<CreateListingRequest>, JsonRejection>,
+) -> Reply {
+    let request = body(request)?;
+    let mut store = server.store();
+    let release = listing_release_for(&store, &request)?;
+    let listing = own_listing(&mut store, &caller, listing_id)?;
+    fill_listing(listing, &release, request);
+    no_content()
+}
+
+async fn delete_listing(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(listing_id): Path<u64>,
+) -> Reply {
+    let mut store = server.store();
+    own_listing(&mut store, &caller, listing_id)?;
+    store.listings.remove(&listing_id);
+    no_content()
+}
+
+fn is_seller(order: &Order, username: &str) -> bool {
+    order
+        .seller
+        .as_ref()
+        .is_some_and(|seller| seller.username == username)
+}
+
+fn is_party(order: &Order, username: &str) -> bool {
+    is_seller(order, username)
+        || order
+            .buyer
+            .as_ref()
+            .is_some_and(|buyer| buyer.username == username)
+}
+
+/// The order if `username` is its buyer or seller.
+fn order_for<'a>(
+    store: &'a mut Store,
+    order_id: &str,
+    username: &str,
+) -> Result<&'a mut Order, Failure> {
+    let order = store
+        .orders
+        .get_mut(order_id)
+        .ok_or_else(|| Failure::not_found("Order not found."))?;
+    if !is_party(order, username) {
+        return Err(Failure::forbidden());
+    }
+    Ok(order)
+}
+
+fn status_message(order: &Order, previous: Option<&str>, timestamp: &str) -> OrderMessage {
+    let current = order.status.as_ref().map(label).unwrap_or_default();
+    OrderMessage {
+        message: format!(
+            "Seller changed status from {} to {current}",
+            previous.unwrap_or("none")
+        ),
+        timestamp: Some(timestamp.to_string()),
+        subject: Some(format!("Discogs Order #{} status changed", order.id)),
+        r#type: Some("status".to_string()),
+        status_id: None,
+        extra: ExtraFields::new(),
+    }
+}
+
+async fn get_orders(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let username = caller.user()?;
+    let store = server.store();
+    let mut orders: Vec<Order> = store
+        .orders
+        .values()
+        .filter(|order| is_seller(order, username))
+        .filter(|order| {
+            matches(
+                &params,
+                "status",
+                order.status.as_ref().map(label).as_deref().into_iter(),
+            ) && matches(
+                &params,
+                "archived",
+                [order.archived.unwrap_or_default().to_string()]
+                    .iter()
+                    .map(String::as_str),
+            ) && params.get("created_after").is_none_or(|after| {
+                order
+                    .created
+                    .as_deref()
+                    .is_some_and(|created| created >= after.as_str())
+            }) && params.get("created_before").is_none_or(|before| {
+                order
+                    .created
+                    .as_deref()
+                    .is_some_and(|created| created <= before.as_str())
+            })
+        })
+        .cloned()
+        .collect();
+    sort_items(&mut orders, &params, |order, sort| {
+        match sort {
+            "id" => Some(order.id.clone()),
+            "buyer" => order
+                .buyer
+                .as_ref()
+                .map(|buyer| buyer.username.to_lowercase()),
+            "created" => order.created.clone(),
+            "status" => order.status.as_ref().map(label),
+            "last_activity" => order.last_activity.clone(),
+            _ => None,
+        }
+        .unwrap_or_default()
+    });
+
+    let (orders, pagination) = paginate(orders, &params, &store.base_url, &uri);
+    ok(OrdersResponse {
+        orders,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_order(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(order_id): Path<String>,
+) -> Reply {
+    let username = caller.user()?;
+    let mut store = server.store();
+    ok(order_for(&mut store, &order_id, username)?.clone())
+}
+
+async fn edit_order(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(order_id): Path<String>,
+    request: Result<Json<EditOrderRequest>, JsonRejection>,
+) -> Reply {
+    let username = caller.user()?;
+    let request = body(request)?;
+    let mut store = server.store();
+    let timestamp = now();
+    let order = order_for(&mut store, &order_id, username)?;
+    if !is_seller(order, username) {
+        return Err(Failure::forbidden());
+    }
+
+    let mut events = Vec::new();
+    if let Some(status) = request.status {
+        let previous = order.status.as_ref().map(label);
+        order.status = Some(status);
+        events.push(status_message(order, previous.as_deref(), &timestamp));
+    }
+    if let Some(shipping) = request.shipping {
+        let currency = order
+            .total
+            .as_ref()
+            .and_then(|total| total.currency)
+            .unwrap_or(Currency::Usd);
+        let items: f64 = order
+            .items
+            .iter()
+            .flatten()
+            .filter_map(|item| item.price.as_ref()?.value)
+            .sum();
+        order.extra.insert(
+            "shipping".to_string(),
+            json!({ "currency": currency.as_code(), "value": shipping }),
+        );
+        order.total = Some(price(items + shipping, currency));
+    }
+    order.last_activity = Some(timestamp);
+    let order = order.clone();
+    store.messages.entry(order_id).or_default().extend(events);
+    ok(order)
+}
+
+async fn get_order_messages(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(order_id): Path<String>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let username = caller.user()?;
+    let mut store = server.store();
+    order_for(&mut store, &order_id, username)?;
+    let messages = store.messages.get(&order_id).cloned().unwrap_or_default();
+
+    let (messages, pagination) = paginate(messages, &params, &store.base_url, &uri);
+    ok(OrderMessagesResponse {
+        messages,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn add_order_message(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(order_id): Path<String>,
+    request: Result<Json<AddOrderMessageRequest>, JsonRejection>,
+) -> Reply {
+    let username = caller.user()?;
+    let request = body(request)?;
+    if request.message.is_none() && request.status.is_none() {
+        return Err(Failure::invalid(
+            "message",
+            "Either a message or a status is required.",
+        ));
+    }
+    let mut store = server.store();
+    let timestamp = now();
+    let order = order_for(&mut store, &order_id, username)?;
+
+    let message = match request.status {
+        Some(status) => {
+            if !is_seller(order, username) {
+                return Err(Failure::forbidden());
+            }
+            let previous = order.status.as_ref().map(label);
+            order.status = Some(status);
+            let mut message = status_message(order, previous.as_deref(), &timestamp);
+            if let Some(text) = request.message {
+                message.message = text;
+            }
+            message
+        }
+        None => OrderMessage {
+            message: request.message.unwrap_or_default(),
+            timestamp: Some(timestamp.clone()),
+            subject: Some(format!("Discogs Order #{order_id}, new message")),
+            r#type: Some("message".to_string()),
+            status_id: None,
+            extra: ExtraFields::from([("from".to_string(), json!({ "username": username }))]),
+        },
+    };
+    order.last_activity = Some(timestamp);
+    store
+        .messages
+        .entry(order_id)
+        .or_default()
+        .push(message.clone());
+    created(message)
+}
+
+fn fee(amount: &str, currency: Currency) -> Reply {
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| Failure::not_found("The requested resource was not found."))?;
+    ok(price(amount * SELLING_FEE, currency))
+}
+
+async fn get_fee(Path(amount): Path<String>) -> Reply {
+    fee(&amount, Currency::Usd)
+}
+
+async fn get_fee_in(Path((amount, currency)): Path<(String, String)>) -> Reply {
+    let currency = serde_json::from_value(json!(currency))
+        .map_err(|_| Failure::not_found("The requested resource was not found."))?;
+    fee(&amount, currency)
+}
+
+async fn get_price_suggestions(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(release_id): Path<u64>,
+) -> Reply {
+    caller.require(AuthLevel::User)?;
+    let store = server.store();
+    let base = store.release(release_id)?.lowest_price.unwrap_or(10.0);
+    let factors = [1.5, 1.3, 1.0, 0.75, 0.5, 0.35, 0.2, 0.1];
+    let suggestions: BTreeMap<String, _> = CONDITIONS
+        .iter()
+        .zip(factors)
+        .map(|(condition, factor)| (condition.to_string(), price(base * factor, Currency::Usd)))
+        .collect();
+    ok(suggestions)
+}
+
+async fn get_marketplace_stats(
+    State(server): Shared,
+    Path(release_id): Path<u64>,
+    Query(params): Query<Params>,
+) -> Reply {
+    let store = server.store();
+    store.release(release_id)?;
+    let currency = params
+        .get("curr_abbr")
+        .and_then(|code| serde_json::from_value(json!(code)).ok())
+        .unwrap_or(Currency::Usd);
+    let prices: Vec<f64> = store
+        .listings
+        .values()
+        .filter(|listing| is_for_sale(listing))
+        .filter(|listing| {
+            listing
+                .release
+                .as_ref()
+                .is_some_and(|release| release.id == release_id)
+        })
+        .filter_map(|listing| listing.price.as_ref()?.value)
+        .collect();
+    ok(MarketplaceReleaseStats {
+        lowest_price: prices
+            .iter()
+            .copied()
+            .reduce(f64::min)
+            .map(|lowest| price(lowest, currency)),
+        num_for_sale: Some(prices.len() as u64),
+        blocked_from_sale: Some(false),
+        extra: ExtraFields::new(),
+    })
+}
+
+// Inventory export
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn inventory_csv(store: &Store, username: &str) -> String {
+    let mut csv = String::from(
+        "listing_id,release_id,artist,title,format,status,price,currency,media_condition,sleeve_condition,comments,listed\n",
+    );
+    for listing in store
+        .listings
+        .values()
+        .filter(|listing| seller(listing) == Some(username))
+    {
+        let release = listing.release.as_ref();
+        let row = [
+            listing.id.to_string(),
+            release
+                .map(|release| release.id.to_string())
+                .unwrap_or_default(),
+            release
+                .and_then(|release| release.artist.clone())
+                .unwrap_or_default(),
+            release
+                .map(|release| release.title.clone())
+                .unwrap_or_default(),
+            release
+                .and_then(|release| release.format.clone())
+                .unwrap_or_default(),
+            listing.status.as_ref().map(label).unwrap_or_default(),
+            listing
+                .price
+                .as_ref()
+                .and_then(|price| price.value)
+                .map(|value| format!("{value:.2}"))
+                .unwrap_or_default(),
+            listing
+                .price
+                .as_ref()
+                .and_then(|price| price.currency)
+                .map(|currency| currency.as_code().to_string())
+                .unwrap_or_default(),
+            listing.condition.as_ref().map(label).unwrap_or_default(),
+            listing
+                .sleeve_condition
+                .as_ref()
+                .map(label)
+                .unwrap_or_default(),
+            listing.comments.clone().unwrap_or_default(),
+            listing.posted.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn own_export<'a>(
+    store: &'a Store,
+    caller: &Caller,
+    export_id: u64,
+) -> Result<&'a Export, Failure> {
+    let username = caller.user()?;
+    store
+        .exports
+        .get(&export_id)
+        .filter(|export| export.owner == username)
+        .ok_or_else(|| Failure::not_found("Export not found."))
+}
+
+/// Exports finish immediately, with the seller's listings as of the request.
+async fn export_inventory(State(server): Shared, Extension(caller): Extension<Caller>) -> Reply {
+    let username = caller.user()?.to_string();
+    let mut store = server.store();
+    let csv = inventory_csv(&store, &username);
+    let export_id = store.next_id();
+    let timestamp = now();
+    let url = store.url(&format!("/inventory/export/{export_id}"));
+    let export = InventoryExport {
+        id: export_id,
+        status: Some("success".to_string()),
+        created_ts: Some(timestamp.clone()),
+        finished_ts: Some(timestamp.clone()),
+        url: Some(url.clone()),
+        download_url: Some(format!("{url}/download")),
+        filename: Some(format!(
+            "{username}-inventory-{}-{export_id}.csv",
+            &timestamp[..10]
+        )),
+        extra: ExtraFields::new(),
+    };
+    store.exports.insert(
+        export_id,
+        Export {
+            owner: username,
+            export,
+            csv,
+        },
+    );
+    Ok((StatusCode::CREATED, [(LOCATION, url)]).into_response())
+}
+
+async fn get_exports(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<Params>,
+    uri: Uri,
+) -> Reply {
+    let username = caller.user()?;
+    let store = server.store();
+    let items: Vec<InventoryExport> = store
+        .exports
+        .values()
+        .rev()
+        .filter(|export| export.owner == username)
+        .map(|export| export.export.clone())
+        .collect();
+
+    let (items, pagination) = paginate(items, &params, &store.base_url, &uri);
+    ok(InventoryExports {
+        items,
+        pagination,
+        extra: ExtraFields::new(),
+    })
+}
+
+async fn get_export(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(export_id): Path<u64>,
+) -> Reply {
+    let store = server.store();
+    ok(&own_export(&store, &caller, export_id)?.export)
+}
+
+async fn download_export(
+    State(server): Shared,
+    Extension(caller): Extension<Caller>,
+    Path(export_id): Path<u64>,
+) -> Reply {
+    let store = server.store();
+    let csv = own_export(&store, &caller, export_id)?.csv.clone();
+    Ok(([(CONTENT_TYPE, "text/csv; charset=utf-8")], csv).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimits, Server};
+    use crate::testing::Fixtures;
+    use crate::testing::state::Store;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn quota_recovers_as_the_window_moves() {
+        let limits = RateLimits {
+            authenticated: 2,
+            anonymous: 1,
+            window: Duration::from_secs(60),
+        };
+        let server = Server::new(Store::new(Fixtures::sample(), "http://emulator"), limits);
+        let alice = server
+            .identify(Some("Discogs token=alice-token"))
+            .expect("known token");
+        let anonymous = server.identify(None).expect("anonymous caller");
+        assert!(server.identify(Some("Discogs token=nope")).is_err());
+
+        assert!(server.take_quota(&alice).allowed);
+        tokio::time::advance(Duration::from_secs(30)).await;
+        let quota = server.take_quota(&alice);
+        assert!(quota.allowed);
+        assert_eq!((quota.limit, quota.used), (2, 2));
+        assert!(!server.take_quota(&alice).allowed);
+        assert!(server.take_quota(&anonymous).allowed);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        let quota = server.take_quota(&alice);
+        assert!(quota.allowed);
+        assert_eq!(quota.used, 2);
+    }
+}
@@ -0,0 +1,682 @@
+//! In-memory state behind the emulator and the views it serves from it.
+
+use super::{FixtureConsumer, FixtureUser, Fixtures};
+use crate::models::{
+    Artist, BasicReleaseInfo, CollectionField, CollectionFolder, CollectionNote, CollectionRelease,
+    Currency, DiscogsList, ExtraFields, InventoryExport, Label, Listing, ListingReleaseSummary,
+    Master, Order, OrderMessage, Pagination, PaginationUrls, Price, Release, UserProfile,
+    WantlistBasicInformation, WantlistEntry,
+};
+use axum::Json;
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(super) type Params = BTreeMap<String, String>;
+
+/// Folder 0 holds every instance; new instances land in folder 1 by default.
+pub(super) const ALL_FOLDER: u64 = 0;
+pub(super) const UNCATEGORIZED_FOLDER: u64 = 1;
+
+/// Media grades, best first.
+pub(super) const CONDITIONS: [&str; 8] = [
+    "Mint (M)",
+    "Near Mint (NM or M-)",
+    "Very Good Plus (VG+)",
+    "Very Good (VG)",
+    "Good Plus (G+)",
+    "Good (G)",
+    "Fair (F)",
+    "Poor (P)",
+];
+
+const DEFAULT_PER_PAGE: u32 = 50;
+const MAX_PER_PAGE: u32 = 100;
+
+/// An error response in the shape Discogs uses: `{"message": ...}`, plus `detail` for
+/// validation failures.
+#[derive(Debug)]
+pub(super) struct Failure {
+    status: StatusCode,
+    message: String,
+    detail: Option<serde_json::Value>,
+}
+
+impl Failure {
+    pub(super) fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            detail: None,
+        }
+    }
+
+    pub(super) fn unauthorized() -> Self {
+        Self::new(
+            StatusCode::UNAUTHORIZED,
+            "You must authenticate to access this resource.",
+        )
+    }
+
+    pub(super) fn forbidden() -> Self {
+        Self::new(
+            StatusCode::FORBIDDEN,
+            "You don't have permission to access this resource.",
+        )
+    }
+
+    pub(super) fn not_found(message: &str) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    pub(super) fn invalid(field: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            detail: Some(json!([{ "loc": ["body", field], "msg": message }])),
+            message: "Validation error.".to_string(),
+        }
+    }
+}
+
+impl IntoResponse for Failure {
+    fn into_response(self) -> Response {
+        let body = match self.detail {
+            Some(detail) => json!({ "message": self.message, "detail": detail }),
+            None => json!({ "message": self.message }),
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Folder {
+    pub(super) id: u64,
+    pub(super) name: String,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Instance {
+    pub(super) username: String,
+    pub(super) instance_id: u64,
+    pub(super) release_id: u64,
+    pub(super) folder_id: u64,
+    pub(super) rating: u8,
+    pub(super) notes: BTreeMap<u64, String>,
+    pub(super) date_added: String,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Want {
+    pub(super) release_id: u64,
+    pub(super) notes: Option<String>,
+    pub(super) rating: u8,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Export {
+    pub(super) owner: String,
+    pub(super) export: InventoryExport,
+    pub(super) csv: String,
+}
+
+#[derive(Debug)]
+pub(super) struct Store {
+    pub(super) base_url: String,
+    pub(super) users: Vec<FixtureUser>,
+    pub(super) consumers: Vec<FixtureConsumer>,
+    pub(super) profiles: BTreeMap<String, UserProfile>,
+    pub(super) artists: BTreeMap<u64, Artist>,
+    pub(super) releases: BTreeMap<u64, Release>,
+    pub(super) masters: BTreeMap<u64, Master>,
+    pub(super) labels: BTreeMap<u64, Label>,
+    pub(super) ratings: BTreeMap<(u64, String), u8>,
+    pub(super) folders: BTreeMap<String, Vec<Folder>>,
+    pub(super) instances: Vec<Instance>,
+    pub(super) wants: BTreeMap<String, Vec<Want>>,
+    pub(super) listings: BTreeMap<u64, Listing>,
+    pub(super) orders: BTreeMap<String, Order>,
+    pub(super) messages: BTreeMap<String, Vec<OrderMessage>>,
+    pub(super) exports: BTreeMap<u64, Export>,
+    pub(super) lists: BTreeMap<u64, DiscogsList>,
+    next_id: u64,
+}
+
+impl Store {
+    pub(super) fn new(fixtures: Fixtures, base_url: &str) -> Self {
+        let fixtures = rebase(fixtures, base_url);
+        let registered = timestamp(SystemTime::now());
+        let profiles = fixtures
+            .users
+            .iter()
+            .map(|user| {
+                let profile = UserProfile {
+                    id: user.id,
+                    username: user.username.clone(),
+                    resource_url: format!("{base_url}/users/{}", user.username).into(),
+                    curr_abbr: Some(Currency::Usd),
+                    avatar_url: None,
+                    profile: None,
+                    name: None,
+                    home_page: None,
+                    location: None,
+                    email: None,
+                    num_collection: None,
+                    num_wantlist: None,
+                    num_for_sale: None,
+                    num_lists: None,
+                    registered: Some(registered.clone()),
+                    extra: ExtraFields::new(),
+                };
+                (user.username.clone(), profile)
+            })
+            .collect();
+        let folders = fixtures
+            .users
+            .iter()
+            .map(|user| {
+                let folders = vec![
+                    Folder {
+                        id: ALL_FOLDER,
+                        name: "All".to_string(),
+                    },
+                    Folder {
+                        id: UNCATEGORIZED_FOLDER,
+                        name: "Uncategorized".to_string(),
+                    },
+                ];
+                (user.username.clone(), folders)
+            })
+            .collect();
+        let messages = fixtures
+            .orders
+            .iter()
+            .map(|order| (order.id.clone(), Vec::new()))
+            .collect();
+        // New listings, instances and exports get ids above anything in the fixtures.
+        let next_id = fixtures
+            .listings
+            .iter()
+            .map(|listing| listing.id)
+            .chain(fixtures.lists.iter().map(|list| list.list_id))
+            .max()
+            .unwrap_or(0)
+            .max(1_000)
+            + 1;
+
+        Self {
+            base_url: base_url.to_string(),
+            users: fixtures.users,
+            consumers: fixtures.consumers,
+            profiles,
+            artists: by_id(fixtures.artists, |artist| artist.id),
+            releases: by_id(fixtures.releases, |release| release.id),
+            masters: by_id(fixtures.masters, |master| master.id),
+            labels: by_id(fixtures.labels, |label| label.id),
+            ratings: BTreeMap::new(),
+            folders,
+            instances: Vec::new(),
+            wants: BTreeMap::new(),
+            listings: by_id(fixtures.listings, |listing| listing.id),
+            orders: fixtures
+                .orders
+                .into_iter()
+                .map(|order| (order.id.clone(), order))
+                .collect(),
+            messages,
+            exports: BTreeMap::new(),
+            lists: by_id(fixtures.lists, |list| list.list_id),
+            next_id,
+        }
+    }
+
+    pub(super) fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub(super) fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    pub(super) fn user(&self, username: &str) -> Result<&FixtureUser, Failure> {
+        self.users
+            .iter()
+            .find(|user| user.username == username)
+            .ok_or_else(|| Failure::not_found("User does not exist or may have been deleted."))
+    }
+
+    pub(super) fn release(&self, release_id: u64) -> Result<&Release, Failure> {
+        self.releases
+            .get(&release_id)
+            .ok_or_else(|| Failure::not_found("Release not found."))
+    }
+
+    pub(super) fn folders(&self, username: &str) -> Result<&Vec<Folder>, Failure> {
+        self.user(username)?;
+        self.folders
+            .get(username)
+            .ok_or_else(|| Failure::not_found("Folder not found."))
+    }
+
+    pub(super) fn folder_view(&self, username: &str, folder: &Folder) -> CollectionFolder {
+        let count = self
+            .instances
+            .iter()
+            .filter(|instance| {
+                instance.username == username
+                    && (folder.id == ALL_FOLDER || instance.folder_id == folder.id)
+            })
+            .count();
+        CollectionFolder {
+            id: folder.id,
+            count: count as u64,
+            name: folder.name.clone(),
+            resource_url: self.url(&format!(
+                "/users/{username}/collection/folders/{}",
+                folder.id
+            )),
+            extra: ExtraFields::new(),
+        }
+    }
+
+    pub(super) fn collection_release(&self, instance: &Instance) -> CollectionRelease {
+        CollectionRelease {
+            id: instance.release_id,
+            instance_id: instance.instance_id,
+            folder_id: Some(instance.folder_id),
+            rating: Some(instance.rating),
+            basic_information: self
+                .releases
+                .get(&instance.release_id)
+                .map(basic_information),
+            notes: Some(
+                instance
+                    .notes
+                    .iter()
+                    .map(|(field_id, value)| CollectionNote {
+                        field_id: *field_id,
+                        value: value.clone(),
+                        extra: ExtraFields::new(),
+                    })
+                    .collect(),
+            ),
+            date_added: Some(instance.date_added.clone()),
+            extra: ExtraFields::new(),
+        }
+    }
+
+    /// `notes` are only shown to the wantlist's owner.
+    pub(super) fn want_entry(&self, want: &Want, show_notes: bool) -> WantlistEntry {
+        let release = self.releases.get(&want.release_id);
+        WantlistEntry {
+            id: want.release_id,
+            resource_url: release.map_or_else(
+                || self.url(&format!("/releases/{}", want.release_id)),
+                |release| release.resource_url.to_string(),
+            ),
+            notes: want.notes.clone().filter(|_| show_notes),
+            rating: Some(want.rating),
+            basic_information: release.map(|release| WantlistBasicInformation {
+                resource_url: release.resource_url.clone(),
+                id: release.id,
+                formats: release.formats.clone(),
+                thumb: release.thumb.clone(),
+                cover_image: None,
+                title: release.title.clone(),
+                labels: release.labels.clone(),
+                year: release.year,
+                artists: release.artists.clone(),
+                genres: release.genres.clone(),
+                styles: release.styles.clone(),
+                extra: ExtraFields::new(),
+            }),
+            extra: ExtraFields::new(),
+        }
+    }
+
+    /// The profile with counts derived from the current state. `email` is only shown to its
+    /// owner.
+    pub(super) fn profile(&self, username: &str, is_owner: bool) -> Result<UserProfile, Failure> {
+        let mut profile =
+            self.profiles.get(username).cloned().ok_or_else(|| {
+                Failure::not_found("User does not exist or may have been deleted.")
+            })?;
+        profile.num_collection = Some(
+            self.instances
+                .iter()
+                .filter(|instance| instance.username == username)
+                .count() as u64,
+        );
+        profile.num_wantlist = Some(self.wants.get(username).map_or(0, Vec::len) as u64);
+        profile.num_for_sale = Some(
+            self.listings
+                .values()
+                .filter(|listing| seller(listing) == Some(username) && is_for_sale(listing))
+                .count() as u64,
+        );
+        profile.num_lists = Some(
+            self.lists
+                .values()
+                .filter(|list| owner(&list.extra) == Some(username))
+                .count() as u64,
+        );
+        if is_owner {
+            profile.email = Some(format!("{username}@example.com"));
+        }
+        Ok(profile)
+    }
+
+    pub(super) fn in_collection(&self, username: &str, release_id: u64) -> bool {
+        self.instances
+            .iter()
+            .any(|instance| instance.username == username && instance.release_id == release_id)
+    }
+
+    pub(super) fn in_wantlist(&self, username: &str, release_id: u64) -> bool {
+        self.wants
+            .get(username)
+            .is_some_and(|wants| wants.iter().any(|want| want.release_id == release_id))
+    }
+}
+
+/// Points fixture links at the emulator so that they can be followed.
+fn rebase(fixtures: Fixtures, base_url: &str) -> Fixtures {
+    let json = serde_json::to_string(&fixtures).expect("fixtures serialize");
+    let json = json.replace("\"https://api.discogs.com/", &format!("\"{base_url}/"));
+    serde_json::from_str(&json).expect("rebased fixtures deserialize")
+}
+
+fn by_id<T>(items: Vec<T>, id: impl Fn(&T) -> u64) -> BTreeMap<u64, T> {
+    items.into_iter().map(|item| (id(&item), item)).collect()
+}
+
+/// The fields every collection starts with.
+pub(super) fn collection_fields() -> Vec<CollectionField> {
+    let dropdown = |id: u64, name: &str| CollectionField {
+        id,
+        name: name.to_string(),
+        field_type: "dropdown".to_string(),
+        public: true,
+        position: id as u32,
+        options: Some(CONDITIONS.iter().map(ToString::to_string).collect()),
+        lines: None,
+        extra: ExtraFields::new(),
+    };
+    vec![
+        dropdown(1, "Media Condition"),
+        dropdown(2, "Sleeve Condition"),
+        CollectionField {
+            id: 3,
+            name: "Notes".to_string(),
+            field_type: "textarea".to_string(),
+            public: false,
+            position: 3,
+            options: None,
+            lines: Some(3),
+            extra: ExtraFields::new(),
+        },
+    ]
+}
+
+pub(super) fn basic_information(release: &Release) -> BasicReleaseInfo {
+    BasicReleaseInfo {
+        id: release.id,
+        title: release.title.clone(),
+        year: release.year,
+        resource_url: release.resource_url.clone(),
+        thumb: release.thumb.clone(),
+        cover_image: None,
+        formats: release.formats.clone(),
+        labels: release.labels.clone(),
+        artists: release.artists.clone(),
+        genres: release.genres.clone(),
+        styles: release.styles.clone(),
+        extra: ExtraFields::new(),
+    }
+}
+
+pub(super) fn listing_release(release: &Release) -> ListingReleaseSummary {
+    let artist = artist_names(release);
+    ListingReleaseSummary {
+        catalog_number: release
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.first())
+            .and_then(|label| label.catno.clone()),
+        resource_url: release.resource_url.clone(),
+        year: release.year,
+        id: release.id,
+        description: Some(format!("{artist} - {}", release.title)),
+        images: release.images.clone(),
+        artist: Some(artist),
+        title: release.title.clone(),
+        format: release
+            .formats
+            .as_ref()
+            .and_then(|formats| formats.first())
+            .map(|format| format.name.clone()),
+        thumbnail: release.thumb.clone(),
+        extra: ExtraFields::new(),
+    }
+}
+
+pub(super) fn artist_names(release: &Release) -> String {
+    release
+        .artists
+        .iter()
+        .flatten()
+        .map(|artist| artist.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(super) fn price(value: f64, currency: Currency) -> Price {
+    let value = (value * 100.0).round() / 100.0;
+    Price {
+        currency: Some(currency),
+        value: Some(value),
+        curr_abbr: None,
+        curr_id: None,
+        formatted: Some(format!("{value:.2} {}", currency.as_code())),
+        extra: ExtraFields::new(),
+    }
+}
+
+/// Username of the account embedded under `key`, e.g. a listing's `seller`.
+pub(super) fn username_in<'a>(extra: &'a ExtraFields, key: &str) -> Option<&'a str> {
+    extra.get(key)?.get("username")?.as_str()
+}
+
+pub(super) fn seller(listing: &Listing) -> Option<&str> {
+    username_in(&listing.extra, "seller")
+}
+
+pub(super) fn owner(list_extra: &ExtraFields) -> Option<&str> {
+    username_in(list_extra, "user")
+}
+
+pub(super) fn is_for_sale(listing: &Listing) -> bool {
+    listing
+        .status
+        .as_ref()
+        .is_some_and(|status| label(status) == "For Sale")
+}
+
+/// The wire name of an enum value, e.g. `"For Sale"`.
+pub(super) fn label<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(label)) => label,
+        _ => String::new(),
+    }
+}
+
+/// Sorts by the `sort` parameter when `key` knows it, then applies `sort_order=desc`.
+pub(super) fn sort_items<T>(items: &mut [T], params: &Params, key: impl Fn(&T, &str) -> String) {
+    let Some(sort) = params.get("sort") else {
+        return;
+    };
+    items.sort_by_cached_key(|item| key(item, sort));
+    if params.get("sort_order").map(String::as_str) == Some("desc") {
+        items.reverse();
+    }
+}
+
+/// Cuts one page out of `items` and builds the pagination block, with `urls` pointing back at
+/// the emulator.
+pub(super) fn paginate<T>(
+    items: Vec<T>,
+    params: &Params,
+    base_url: &str,
+    uri: &Uri,
+) -> (Vec<T>, Pagination) {
+    let per_page = params
+        .get("per_page")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+    let page = params
+        .get("page")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(1u32)
+        .max(1);
+    let total = items.len() as u32;
+    let pages = total.div_ceil(per_page).max(1);
+
+    // Computed in u64 so huge page numbers land past the end (an empty page) instead of
+    // overflowing.
+    let start = usize::try_from(u64::from(page - 1) * u64::from(per_page)).unwrap_or(usize::MAX);
+    let items: Vec<T> = items
+        .into_iter()
+        .skip(start)
+        .take(per_page as usize)
+        .collect();
+
+    let link = |page: u32| page_url(base_url, uri, page, per_page);
+    let urls = PaginationUrls {
+        first: (page > 1).then(|| link(1)),
+        prev: (page > 1).then(|| link((page - 1).min(pages))),
+        next: (page < pages).then(|| link(page + 1)),
+        last: (page < pages).then(|| link(pages)),
+    };
+    let pagination = Pagination {
+        per_page,
+        pages,
+        page,
+        items: total,
+        urls: Some(urls),
+    };
+    (items, pagination)
+}
+
+fn page_url(base_url: &str, uri: &Uri, page: u32, per_page: u32) -> String {
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in url::form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes()) {
+        if key != "page" && key != "per_page" {
+            query.append_pair(&key, &value);
+        }
+    }
+    query.append_pair("page", &page.to_string());
+    query.append_pair("per_page", &per_page.to_string());
+    format!("{base_url}{}?{}", uri.path(), query.finish())
+}
+
+/// RFC 3339 in UTC, the format Discogs uses for dates.
+pub(super) fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (days, rest) = ((seconds / 86_400) as i64, seconds % 86_400);
+
+    // Days since the epoch to a civil date, after Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}+00:00",
+        rest / 3_600,
+        rest % 3_600 / 60,
+        rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{paginate, timestamp};
+    use axum::http::Uri;
+    use std::collections::BTreeMap;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn timestamps_are_rfc3339_utc() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00+00:00");
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            "2024-02-29T12:34:56+00:00"
+        );
+    }
+
+    #[test]
+    fn pages_link_to_their_neighbours() {
+        let params = BTreeMap::from([
+            ("page".to_string(), "2".to_string()),
+            ("per_page".to_string(), "2".to_string()),
+        ]);
+        let uri: Uri = "/users/alice/wants?page=2&per_page=2&sort=added"
+            .parse()
+            .expect("uri");
+
+        let (items, pagination) = paginate((1..=5).collect(), &params, "http://emulator", &uri);
+
+        assert_eq!(items, vec![3, 4]);
+        assert_eq!(
+            (pagination.page, pagination.pages, pagination.items),
+            (2, 3, 5)
+        );
+        let urls = pagination.urls.expect("urls");
+        assert_eq!(
+            urls.next.as_deref(),
+            Some("http://emulator/users/alice/wants?sort=added&page=3&per_page=2")
+        );
+        assert_eq!(
+            urls.first.as_deref(),
+            Some("http://emulator/users/alice/wants?sort=added&page=1&per_page=2")
+        );
+    }
+
+    #[test]
+    fn pages_past_the_end_are_empty() {
+        let params = BTreeMap::from([
+            ("page".to_string(), u32::MAX.to_string()),
+            ("per_page".to_string(), "100".to_string()),
+        ]);
+        let uri: Uri = "/users/alice/wants".parse().expect("uri");
+
+        let (items, pagination) = paginate((1..=5).collect(), &params, "http://emulator", &uri);
+
+        assert!(items.is_empty());
+        assert_eq!((pagination.page, pagination.pages), (u32::MAX, 1));
+        let urls = pagination.urls.expect("urls");
+        assert!(urls.next.is_none());
+        assert_eq!(
+            urls.prev.as_deref(),
+            Some("http://emulator/users/alice/wants?page=1&per_page=100")
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! HTTP transport abstraction used by [`DiscogsClient`](crate::DiscogsClient) and
+//! [`DiscogsOAuthClient`](crate::DiscogsOAuthClient).
+
+use crate::error::ErrorContext;
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use http::{Extensions, HeaderMap, Method, StatusCode};
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A fully prepared request: URL with query string, headers (including auth) and body.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<Bytes>,
+    /// Per-request timeout budget. The client enforces it as well, so honouring it is optional.
+    pub timeout: Option<Duration>,
+    /// Typed values middleware layers can use to pass data along the chain.
+    pub extensions: Extensions,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    pub extensions: Extensions,
+}
+
+/// Sends [`HttpRequest`]s over the wire.
+///
+/// Non-success statuses are regular responses; only failures to obtain a response at all are
+/// reported as [`TransportError`].
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>>;
+}
+
+impl<T> HttpTransport for Arc<T>
+where
+    T: HttpTransport + ?Sized,
+{
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        (**self).send(request)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// The connection could not be established or was reset.
+    Connect,
+    /// No response arrived within the timeout.
+    Timeout,
+    /// The request could not be built (bad URL, header value, ...). Never worth retrying.
+    InvalidRequest,
+    /// Any other I/O or protocol failure.
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    message: String,
+    source: Option<Arc<dyn StdError + Send + Sync>>,
+    context: Option<Box<ErrorContext>>,
+}
+
+impl TransportError {
+    pub fn new(kind: TransportErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            source: None,
+            context: None,
+        }
+    }
+
+    pub fn with_source(
+        kind: TransportErrorKind,
+        source: impl StdError + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            kind,
+            message: source.to_string(),
+            source: Some(Arc::new(source)),
+            context: None,
+        }
+    }
+
+    pub fn timeout() -> Self {
+        Self::new(TransportErrorKind::Timeout, "request timed out")
+    }
+
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.kind == TransportErrorKind::Timeout
+    }
+
+    pub fn is_connect(&self) -> bool {
+        self.kind == TransportErrorKind::Connect
+    }
+
+    /// Set by the client once the request has finished failing.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        self.context.as_deref()
+    }
+
+    pub(crate) fn set_context(&mut self, context: ErrorContext) {
+        self.context = Some(Box::new(context));
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some(context) = &self.context {
+            write!(f, " ({context})")?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for TransportError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn StdError + 'static))
+    }
+}
+
+/// Default transport backed by `reqwest`, available with the `rustls-tls` or `native-tls`
+/// feature.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    pub fn new() -> crate::error::Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::builder().build()?,
+        })
+    }
+
+    /// Reuses an existing, possibly shared, `reqwest::Client`.
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, request.url)
+                .headers(request.headers);
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let response = builder.send().await.map_err(reqwest_error)?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.bytes().await.map_err(reqwest_error)?;
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+                extensions: Extensions::new(),
+            })
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn reqwest_error(error: reqwest::Error) -> TransportError {
+    let kind = if error.is_timeout() {
+        TransportErrorKind::Timeout
+    } else if error.is_connect() {
+        TransportErrorKind::Connect
+    } else if error.is_builder() {
+        TransportErrorKind::InvalidRequest
+    } else {
+        TransportErrorKind::Other
+    };
+    TransportError::with_source(kind, error)
+}
+
+/// Transport used when the builder was not given one explicitly.
+pub(crate) fn default_transport() -> crate::error::Result<Arc<dyn HttpTransport>> {
+    #[cfg(feature = "reqwest")]
+    {
+        Ok(Arc::new(ReqwestTransport::new()?))
+    }
+
+    #[cfg(not(feature = "reqwest"))]
+    {
+        Err(TransportError::new(
+            TransportErrorKind::InvalidRequest,
+            "no HTTP transport configured: enable the `rustls-tls` or `native-tls` feature or supply one with `transport(..)`",
+        )
+        .into())
+    }
+}
@@ -1,16 +1,26 @@
-use axum::extract::Request;
+use axum::extract::{Query, Request};
 use axum::http::{HeaderValue, StatusCode, header::AUTHORIZATION};
 use axum::middleware::{self, Next};
 use axum::response::{IntoResponse, Response};
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::{Json, Router};
-use discogs_rs::{AuthLevel, DiscogsClient, DiscogsError, RetryConfig};
+use bytes::Bytes;
+use discogs_rs::{
+    ApiFuture, Auth, AuthLevel, CacheConfig, DatabaseApi, DiscogsClient, DiscogsError,
+    DiscogsOAuthClient, HttpRequest, HttpResponse, HttpTransport, MemoryCacheStore, NoRetry,
+    OAuthFlow, OutputFormat, RequestOptions, RetryConfig, TransportError, TransportErrorKind,
+    middleware_fn,
+};
+use futures_util::StreamExt;
+use futures_util::future::BoxFuture;
+use http::{Extensions, HeaderMap};
 use serde_json::json;
 use std::sync::{
-    Arc,
+    Arc, Mutex,
     atomic::{AtomicUsize, Ordering},
 };
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
 
 async fn spawn_server(app: Router) -> String {
@@ -155,7 +165,9 @@ async fn get_identity_without_user_auth_fails_before_network_call() {
 
     let error = client.get_identity().await.expect_err("auth should fail");
     match error {
-        DiscogsError::AuthRequired { required, current } => {
+        DiscogsError::AuthRequired {
+            required, current, ..
+        } => {
             assert_eq!(required, AuthLevel::User);
             assert_eq!(current, AuthLevel::None);
         }
@@ -164,7 +176,7 @@ async fn get_identity_without_user_auth_fails_before_network_call() {
 }
 
 #[tokio::test]
-async fn non_success_response_maps_to_http_error_with_message() {
+async fn not_found_response_maps_to_not_found_error_with_message() {
     let app = Router::new().route(
         "/",
         get(|| async {
@@ -182,11 +194,1759 @@ async fn non_success_response_maps_to_http_error_with_message() {
         .expect("build client");
 
     let error = client.about().await.expect_err("request should fail");
+    assert!(error.is_not_found());
+    match error {
+        DiscogsError::NotFound(error) => {
+            assert_eq!(error.status, StatusCode::NOT_FOUND);
+            assert!(error.message.contains("not found"));
+            assert!(error.body.contains("\"message\""));
+        }
+        other => panic!("expected NotFound error, got {other:?}"),
+    }
+}
+
+fn wants_page(page: u32, pages: u32) -> serde_json::Value {
+    json!({
+        "pagination": {
+            "per_page": 2,
+            "pages": pages,
+            "page": page,
+            "items": pages * 2,
+            "urls": {}
+        },
+        "wants": [
+            {
+                "id": page * 10 + 1,
+                "resource_url": format!("https://api.discogs.com/releases/{}", page * 10 + 1)
+            },
+            {
+                "id": page * 10 + 2,
+                "resource_url": format!("https://api.discogs.com/releases/{}", page * 10 + 2)
+            }
+        ]
+    })
+}
+
+fn paged_wantlist_app(pages: u32, failing_page: Option<u32>, hits: Arc<AtomicUsize>) -> Router {
+    Router::new().route(
+        "/users/tester/wants",
+        get(
+            move |Query(params): Query<std::collections::HashMap<String, String>>| {
+                let hits = Arc::clone(&hits);
+                async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    let page = params
+                        .get("page")
+                        .and_then(|value| value.parse::<u32>().ok())
+                        .unwrap_or(1);
+                    if Some(page) == failing_page {
+                        return (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({ "message": "boom" })),
+                        )
+                            .into_response();
+                    }
+                    (StatusCode::OK, Json(wants_page(page, pages))).into_response()
+                }
+            },
+        ),
+    )
+}
+
+#[tokio::test]
+async fn paginated_stream_walks_every_page_in_order() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(paged_wantlist_app(3, None, Arc::clone(&hits))).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .build()
+        .expect("build client");
+
+    let ids: Vec<u64> = client
+        .wantlist()
+        .get_releases_paginated("tester", None)
+        .items()
+        .map(|item| item.expect("wantlist entry").id)
+        .collect()
+        .await;
+
+    assert_eq!(ids, vec![11, 12, 21, 22, 31, 32]);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    let prefetched: Vec<u32> = client
+        .wantlist()
+        .get_releases_paginated("tester", None)
+        .prefetch(4)
+        .pages()
+        .map(|page| page.expect("wantlist page").pagination.page)
+        .collect()
+        .await;
+
+    assert_eq!(prefetched, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn paginated_stream_stops_after_first_error() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(paged_wantlist_app(5, Some(2), Arc::clone(&hits))).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .build()
+        .expect("build client");
+
+    let pages: Vec<_> = client
+        .wantlist()
+        .get_releases_paginated("tester", None)
+        .pages()
+        .collect()
+        .await;
+
+    assert_eq!(pages.len(), 2);
+    assert!(pages[0].is_ok());
+    assert!(pages[1].is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+fn about_body() -> serde_json::Value {
+    json!({
+        "hello": "Welcome to the Discogs API.",
+        "api_version": "v2",
+        "documentation_url": "https://www.discogs.com/developers/",
+        "statistics": { "releases": 1, "artists": 2, "labels": 3 }
+    })
+}
+
+fn flaky_app(failures: usize, hits: Arc<AtomicUsize>) -> Router {
+    async fn respond(failures: usize, hits: Arc<AtomicUsize>) -> Response {
+        if hits.fetch_add(1, Ordering::SeqCst) < failures {
+            let mut response = (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "<html>upstream unavailable</html>",
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert("retry-after", HeaderValue::from_static("0"));
+            return response;
+        }
+        (StatusCode::OK, Json(about_body())).into_response()
+    }
+
+    Router::new().route("/", get(move || respond(failures, Arc::clone(&hits))))
+}
+
+fn retrying_client(base_url: String) -> DiscogsClient {
+    DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .retry(RetryConfig {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            backoff_factor: 1.0,
+        })
+        .build()
+        .expect("build client")
+}
+
+#[tokio::test]
+async fn idempotent_get_is_retried_after_503_with_retry_after() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(flaky_app(2, Arc::clone(&hits))).await;
+
+    let response = retrying_client(base_url)
+        .about()
+        .await
+        .expect("about response");
+    assert_eq!(response.data.api_version, "v2");
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn oauth_retries_are_signed_with_a_fresh_nonce() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let headers = Arc::new(Mutex::new(Vec::new()));
+    let app = flaky_app(1, Arc::clone(&hits)).layer(middleware::from_fn({
+        let headers = Arc::clone(&headers);
+        move |request: Request, next: Next| {
+            let headers = Arc::clone(&headers);
+            async move {
+                let header = request
+                    .headers()
+                    .get(AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                headers.lock().expect("headers lock").push(header);
+                next.run(request).await
+            }
+        }
+    }));
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .auth(Auth::oauth("ck", "cs", "token", "token-secret"))
+        .retry(RetryConfig {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            backoff_factor: 1.0,
+        })
+        .build()
+        .expect("build client");
+
+    client.about().await.expect("about after one retry");
+
+    let nonce = |header: &str| {
+        header
+            .split(", ")
+            .find_map(|field| field.strip_prefix("oauth_nonce="))
+            .map(ToOwned::to_owned)
+            .expect("nonce")
+    };
+    let headers = headers.lock().expect("headers lock");
+    assert_eq!(headers.len(), 2);
+    assert_ne!(nonce(&headers[0]), nonce(&headers[1]));
+}
+
+#[tokio::test]
+async fn retry_policy_can_be_overridden_per_call() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(flaky_app(1, Arc::clone(&hits))).await;
+
+    let error = retrying_client(base_url)
+        .with_retry_policy(NoRetry)
+        .about()
+        .await
+        .expect_err("request should fail without retries");
     match error {
-        DiscogsError::Http { status, message } => {
-            assert_eq!(status, StatusCode::NOT_FOUND);
-            assert!(message.contains("not found"));
+        DiscogsError::ServerError(error) => {
+            assert_eq!(error.body, "<html>upstream unavailable</html>");
+        }
+        other => panic!("expected ServerError, got {other:?}"),
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[derive(Debug)]
+struct CannedTransport {
+    body: &'static str,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl CannedTransport {
+    fn new(body: &'static str) -> Arc<Self> {
+        Arc::new(Self {
+            body,
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+impl HttpTransport for CannedTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        self.requests.lock().expect("requests lock").push(request);
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: Bytes::from_static(self.body.as_bytes()),
+                extensions: Extensions::new(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn custom_transport_receives_prepared_requests() {
+    let transport = CannedTransport::new(
+        r#"{"hello":"hi","api_version":"v2","documentation_url":"d","statistics":{"releases":1,"artists":2,"labels":3}}"#,
+    );
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("https://discogs.invalid")
+        .user_token("test-token")
+        .transport(Arc::clone(&transport))
+        .build()
+        .expect("build client");
+
+    let response = client.about().await.expect("about response");
+    assert_eq!(response.data.hello, "hi");
+
+    let requests = transport.requests.lock().expect("requests lock");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url, "https://discogs.invalid/");
+    assert_eq!(
+        requests[0].headers.get(AUTHORIZATION).map(|v| v.as_bytes()),
+        Some(&b"Discogs token=test-token"[..])
+    );
+    assert_eq!(
+        requests[0].headers.get("user-agent").map(|v| v.as_bytes()),
+        Some(&b"test-agent"[..])
+    );
+}
+
+#[tokio::test]
+async fn oauth_client_uses_custom_transport() {
+    let transport = CannedTransport::new(
+        "oauth_token=request-token&oauth_token_secret=request-secret&oauth_callback_confirmed=true",
+    );
+    let oauth = DiscogsOAuthClient::with_transport(
+        "consumer-key",
+        "consumer-secret",
+        "test-agent",
+        Arc::clone(&transport),
+    );
+
+    let token = oauth
+        .request_token("https://example.com/callback")
+        .await
+        .expect("request token");
+    assert_eq!(token.token.expose(), "request-token");
+    assert_eq!(token.token_secret.expose(), "request-secret");
+    assert!(token.callback_confirmed);
+    assert_eq!(
+        token.authorize_url,
+        "https://discogs.com/oauth/authorize?oauth_token=request-token"
+    );
+
+    let token = oauth
+        .with_authorize_url("http://127.0.0.1:9/authorize?lang=en")
+        .request_token("https://example.com/callback")
+        .await
+        .expect("request token");
+    assert_eq!(
+        token.authorize_url,
+        "http://127.0.0.1:9/authorize?lang=en&oauth_token=request-token"
+    );
+
+    let requests = transport.requests.lock().expect("requests lock");
+    assert!(requests[0].url.ends_with("/oauth/request_token"));
+}
+
+#[derive(Debug)]
+struct HangingTransport;
+
+impl HttpTransport for HangingTransport {
+    fn send(&self, _request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        Box::pin(std::future::pending())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn oauth_token_requests_time_out() {
+    let oauth = DiscogsOAuthClient::with_transport(
+        "consumer-key",
+        "consumer-secret",
+        "test-agent",
+        HangingTransport,
+    )
+    .with_timeout(Duration::from_secs(5));
+
+    let started = tokio::time::Instant::now();
+    let error = oauth
+        .request_token("https://example.com/callback")
+        .await
+        .expect_err("token request should time out");
+    assert!(
+        matches!(&error, DiscogsError::Transport(error) if error.is_timeout()),
+        "{error:?}"
+    );
+    assert_eq!(started.elapsed(), Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn oauth_requests_are_signed_with_hmac_sha1() {
+    let clock = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let nonce = "fixednonce".to_string();
+    let transport = CannedTransport::new(ABOUT_JSON);
+    let client = DiscogsClient::builder("test-agent")
+        .auth(Auth::oauth("ck", "cs", "token", "token-secret"))
+        .oauth_clock(clock)
+        .oauth_nonce_source(nonce.clone())
+        .transport(Arc::clone(&transport))
+        .build()
+        .expect("build client");
+
+    client.about().await.expect("about");
+    client.about().await.expect("about");
+
+    let requests = transport.requests.lock().expect("requests lock");
+    let header = |index: usize| {
+        requests[index]
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .expect("authorization header")
+            .to_string()
+    };
+    assert_eq!(header(0), header(1));
+    assert!(header(0).contains("oauth_signature_method=\"HMAC-SHA1\""));
+    assert!(header(0).contains("oauth_nonce=\"fixednonce\""));
+    assert!(header(0).contains("oauth_timestamp=\"1700000000\""));
+    assert!(!header(0).contains("token-secret"));
+}
+
+fn oauth_stand_in() -> Router {
+    fn authorization(headers: &axum::http::HeaderMap) -> String {
+        headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    Router::new()
+        .route(
+            "/oauth/request_token",
+            get(|headers: axum::http::HeaderMap| async move {
+                assert!(authorization(&headers).contains("oauth_callback="));
+                "oauth_token=request-token&oauth_token_secret=request-secret&oauth_callback_confirmed=true"
+            }),
+        )
+        .route(
+            "/oauth/access_token",
+            post(|headers: axum::http::HeaderMap| async move {
+                let header = authorization(&headers);
+                if !header.contains("oauth_token=\"request-token\"")
+                    || !header.contains("oauth_verifier=\"verifier-1\"")
+                {
+                    return (StatusCode::UNAUTHORIZED, String::new());
+                }
+                (
+                    StatusCode::OK,
+                    "oauth_token=access-token&oauth_token_secret=access-secret".to_string(),
+                )
+            }),
+        )
+        .route(
+            "/oauth/identity",
+            get(|headers: axum::http::HeaderMap| async move {
+                assert!(authorization(&headers).contains("oauth_token=\"access-token\""));
+                Json(json!({
+                    "id": 1,
+                    "username": "tester",
+                    "resource_url": "https://api.discogs.com/users/tester",
+                    "consumer_name": "discogs-rs"
+                }))
+            }),
+        )
+}
+
+#[tokio::test]
+async fn oauth_flow_catches_loopback_redirect_and_builds_client() {
+    let base_url = spawn_server(oauth_stand_in()).await;
+    let oauth = DiscogsOAuthClient::new("consumer-key", "consumer-secret", "test-agent")
+        .expect("oauth client")
+        .with_base_url(base_url);
+    let pending = OAuthFlow::new(oauth)
+        .timeout(Duration::from_secs(5))
+        .start_loopback()
+        .await
+        .expect("start flow");
+    assert!(
+        pending
+            .authorize_url()
+            .ends_with("oauth_token=request-token")
+    );
+
+    let callback = url::Url::parse(pending.callback_url()).expect("callback url");
+    let addr = format!(
+        "{}:{}",
+        callback.host_str().expect("host"),
+        callback.port().expect("port")
+    );
+    let browser = tokio::spawn(async move {
+        // A preconnected socket that never sends a request must not block the redirect.
+        let _idle = tokio::net::TcpStream::connect(&addr)
+            .await
+            .expect("connect idle socket");
+        for target in [
+            "/favicon.ico".to_string(),
+            format!(
+                "{}?oauth_token=stale-token&oauth_verifier=verifier-0",
+                callback.path()
+            ),
+            format!(
+                "{}?oauth_token=request-token&oauth_verifier=verifier-1",
+                callback.path()
+            ),
+        ] {
+            let mut stream = tokio::net::TcpStream::connect(&addr)
+                .await
+                .expect("connect to callback");
+            stream
+                .write_all(format!("GET {target} HTTP/1.1\r\nhost: {addr}\r\n\r\n").as_bytes())
+                .await
+                .expect("send callback");
+            let mut response = String::new();
+            stream
+                .read_to_string(&mut response)
+                .await
+                .expect("read callback response");
+            assert!(response.starts_with("HTTP/1.1"));
+        }
+    });
+
+    let client = pending.finish().await.expect("finish flow");
+    browser.await.expect("browser task");
+
+    assert_eq!(client.auth_level(), AuthLevel::User);
+    let identity = client.get_identity().await.expect("identity");
+    assert_eq!(identity.data.username, "tester");
+}
+
+#[tokio::test]
+async fn oauth_flow_out_of_band_exchanges_pin() {
+    let base_url = spawn_server(oauth_stand_in()).await;
+    let oauth = DiscogsOAuthClient::new("consumer-key", "consumer-secret", "test-agent")
+        .expect("oauth client")
+        .with_base_url(base_url);
+    let pending = OAuthFlow::new(oauth)
+        .start_out_of_band()
+        .await
+        .expect("start flow");
+
+    let client = pending.finish(" verifier-1\n").await.expect("finish flow");
+    match client.auth() {
+        Auth::OAuth {
+            access_token,
+            access_token_secret,
+            ..
+        } => {
+            assert_eq!(access_token.expose(), "access-token");
+            assert_eq!(access_token_secret.expose(), "access-secret");
+        }
+        other => panic!("expected OAuth credentials, got {other:?}"),
+    }
+}
+
+const ABOUT_JSON: &str = r#"{"hello":"hi","api_version":"v2","documentation_url":"d","statistics":{"releases":1,"artists":2,"labels":3}}"#;
+
+#[tokio::test]
+async fn middleware_layers_run_in_order_and_see_prepared_headers() {
+    let transport = CannedTransport::new(ABOUT_JSON);
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let layer = |name: &'static str| {
+        let order = Arc::clone(&order);
+        middleware_fn(move |mut request: HttpRequest, next| {
+            let order = Arc::clone(&order);
+            async move {
+                assert!(request.headers.contains_key(AUTHORIZATION));
+                order.lock().expect("order lock").push(name);
+                request
+                    .headers
+                    .insert("x-correlation-id", HeaderValue::from_static(name));
+                let mut response = next.run(request).await?;
+                response
+                    .headers
+                    .insert("x-seen-by", HeaderValue::from_static(name));
+                Ok(response)
+            }
+        })
+    };
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("https://discogs.invalid")
+        .user_token("test-token")
+        .transport(Arc::clone(&transport))
+        .layer(layer("outer"))
+        .layer(layer("inner"))
+        .build()
+        .expect("build client");
+
+    client.about().await.expect("about response");
+
+    assert_eq!(*order.lock().expect("order lock"), ["outer", "inner"]);
+    let requests = transport.requests.lock().expect("requests lock");
+    assert_eq!(
+        requests[0]
+            .headers
+            .get("x-correlation-id")
+            .map(|v| v.as_bytes()),
+        Some(&b"inner"[..])
+    );
+}
+
+#[tokio::test]
+async fn middleware_can_short_circuit_with_synthetic_response() {
+    let transport = CannedTransport::new("not json");
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("https://discogs.invalid")
+        .transport(Arc::clone(&transport))
+        .layer(middleware_fn(|_request, _next| async {
+            Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: Bytes::from_static(ABOUT_JSON.as_bytes()),
+                extensions: Extensions::new(),
+            })
+        }))
+        .build()
+        .expect("build client");
+
+    let response = client.about().await.expect("synthetic response");
+    assert_eq!(response.data.api_version, "v2");
+    assert!(transport.requests.lock().expect("requests lock").is_empty());
+}
+
+#[tokio::test]
+async fn auth_check_runs_before_user_layers() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("https://discogs.invalid")
+        .transport(CannedTransport::new(ABOUT_JSON))
+        .layer(middleware_fn({
+            let calls = Arc::clone(&calls);
+            move |request, next: discogs_rs::Next| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                next.run(request)
+            }
+        }))
+        .build()
+        .expect("build client");
+
+    let error = client
+        .get_identity()
+        .await
+        .expect_err("identity requires user auth");
+    assert!(matches!(error, DiscogsError::AuthRequired { .. }));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+fn etag_app(hits: Arc<AtomicUsize>, not_modified: Arc<AtomicUsize>) -> Router {
+    Router::new().route(
+        "/",
+        get(move |request: Request| {
+            let hits = Arc::clone(&hits);
+            let not_modified = Arc::clone(&not_modified);
+            async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                if request.headers().get("if-none-match").map(|v| v.as_bytes()) == Some(b"\"v1\"") {
+                    not_modified.fetch_add(1, Ordering::SeqCst);
+                    return StatusCode::NOT_MODIFIED.into_response();
+                }
+                let mut response = Json(about_body()).into_response();
+                response
+                    .headers_mut()
+                    .insert("etag", HeaderValue::from_static("\"v1\""));
+                response
+            }
+        }),
+    )
+}
+
+fn cached_client(base_url: String, cache: CacheConfig) -> DiscogsClient {
+    DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .cache(MemoryCacheStore::new(16), cache)
+        .build()
+        .expect("build client")
+}
+
+#[tokio::test]
+async fn fresh_cache_entries_skip_the_network() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(etag_app(Arc::clone(&hits), Arc::default())).await;
+    let client = cached_client(
+        base_url,
+        CacheConfig::default().endpoint("/", Duration::from_secs(60)),
+    );
+
+    for _ in 0..3 {
+        let response = client.about().await.expect("about response");
+        assert_eq!(response.data.api_version, "v2");
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn expired_entries_are_revalidated_with_etag() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let not_modified = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(etag_app(Arc::clone(&hits), Arc::clone(&not_modified))).await;
+    let client = cached_client(
+        base_url,
+        CacheConfig {
+            default_ttl: Some(Duration::ZERO),
+            endpoint_ttls: Vec::new(),
+            stale_while_revalidate: Duration::ZERO,
+        },
+    );
+
+    client.about().await.expect("first response");
+    let response = client.about().await.expect("revalidated response");
+    assert_eq!(response.data.api_version, "v2");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn stale_entries_are_served_while_revalidating() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let not_modified = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(etag_app(Arc::clone(&hits), Arc::clone(&not_modified))).await;
+    let client = cached_client(
+        base_url,
+        CacheConfig {
+            default_ttl: Some(Duration::ZERO),
+            endpoint_ttls: Vec::new(),
+            stale_while_revalidate: Duration::from_secs(60),
+        },
+    );
+
+    client.about().await.expect("first response");
+    client.about().await.expect("stale response");
+    for _ in 0..100 {
+        if not_modified.load(Ordering::SeqCst) == 1 {
+            break;
         }
-        other => panic!("expected Http error, got {other:?}"),
+        tokio::time::sleep(Duration::from_millis(10)).await;
     }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn cache_hits_do_not_replay_rate_limit_headers() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new().route(
+        "/",
+        get({
+            let hits = Arc::clone(&hits);
+            move || async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                (
+                    [
+                        ("x-discogs-ratelimit", "60"),
+                        ("x-discogs-ratelimit-used", "1"),
+                        ("x-discogs-ratelimit-remaining", "59"),
+                    ],
+                    Json(about_body()),
+                )
+            }
+        }),
+    );
+    let base_url = spawn_server(app).await;
+    let client = cached_client(
+        base_url,
+        CacheConfig::default().endpoint("/", Duration::from_secs(60)),
+    );
+
+    let first = client.about().await.expect("first response");
+    assert_eq!(first.rate_limit.map(|rate| rate.remaining), Some(59));
+    let cached = client.about().await.expect("cached response");
+    assert_eq!(cached.data.api_version, "v2");
+    assert!(cached.rate_limit.is_none());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn not_modified_without_a_cache_entry_refetches_the_body() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let not_modified = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(etag_app(Arc::clone(&hits), Arc::clone(&not_modified))).await;
+    let client = cached_client(
+        base_url,
+        CacheConfig::default().endpoint("/", Duration::from_secs(60)),
+    );
+
+    let response = client
+        .with_options(RequestOptions::new().header(
+            http::header::IF_NONE_MATCH,
+            HeaderValue::from_static("\"v1\""),
+        ))
+        .about()
+        .await
+        .expect("refetched response");
+    assert_eq!(response.data.api_version, "v2");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+
+    client.about().await.expect("cached response");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn successful_writes_evict_cached_reads() {
+    let rating = Arc::new(AtomicUsize::new(3));
+    let reads = Arc::new(AtomicUsize::new(0));
+    let app = Router::new().route(
+        "/releases/{id}/rating/{username}",
+        get({
+            let rating = Arc::clone(&rating);
+            let reads = Arc::clone(&reads);
+            move || async move {
+                reads.fetch_add(1, Ordering::SeqCst);
+                Json(json!({
+                    "username": "alice",
+                    "release_id": 1,
+                    "rating": rating.load(Ordering::SeqCst)
+                }))
+            }
+        })
+        .put({
+            let rating = Arc::clone(&rating);
+            move |Json(body): Json<serde_json::Value>| async move {
+                let value = body["rating"].as_u64().unwrap_or_default() as usize;
+                rating.store(value, Ordering::SeqCst);
+                Json(json!({ "username": "alice", "release_id": 1, "rating": value }))
+            }
+        }),
+    );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .user_token("token")
+        .cache(MemoryCacheStore::new(16), CacheConfig::default())
+        .build()
+        .expect("build client");
+    let database = client.database();
+
+    let before = database
+        .get_release_rating(1, "alice")
+        .await
+        .expect("rating");
+    assert_eq!(before.data.rating, 3);
+    database
+        .get_release_rating(1, "alice")
+        .await
+        .expect("cached rating");
+    assert_eq!(reads.load(Ordering::SeqCst), 1);
+
+    database
+        .set_release_rating(1, "alice", 5)
+        .await
+        .expect("set rating");
+    let after = database
+        .get_release_rating(1, "alice")
+        .await
+        .expect("rating");
+    assert_eq!(after.data.rating, 5);
+    assert_eq!(reads.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn errors_carry_request_context_and_rate_limit() {
+    let app = Router::new().route(
+        "/releases/{id}",
+        get(|| async {
+            let mut response = (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Release not found." })),
+            )
+                .into_response();
+            let headers = response.headers_mut();
+            headers.insert("x-discogs-ratelimit", HeaderValue::from_static("60"));
+            headers.insert("x-discogs-ratelimit-used", HeaderValue::from_static("59"));
+            headers.insert(
+                "x-discogs-ratelimit-remaining",
+                HeaderValue::from_static("1"),
+            );
+            response
+        }),
+    );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url.clone())
+        .build()
+        .expect("build client");
+
+    let error = client
+        .database()
+        .get_release(1, None)
+        .await
+        .expect_err("release should be missing");
+    let context = error.context().expect("error context");
+    assert_eq!(context.method, http::Method::GET);
+    assert_eq!(context.url, format!("{base_url}/releases/1"));
+    assert_eq!(context.attempts, 1);
+    assert_eq!(
+        context.rate_limit.as_ref().map(|rate| rate.remaining),
+        Some(1)
+    );
+    assert!(error.to_string().contains("/releases/1"));
+}
+
+#[tokio::test]
+async fn decode_errors_carry_request_context() {
+    let app = Router::new().route("/releases/{id}", get(|| async { "not json" }));
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url.clone())
+        .build()
+        .expect("build client");
+
+    let error = client
+        .database()
+        .get_release(1, None)
+        .await
+        .expect_err("body is not json");
+    assert!(matches!(error, DiscogsError::Json { .. }), "{error:?}");
+    let context = error.context().expect("error context");
+    assert_eq!(context.method, http::Method::GET);
+    assert_eq!(context.url, format!("{base_url}/releases/1"));
+    assert_eq!(context.attempts, 1);
+}
+
+#[tokio::test]
+async fn auth_and_build_errors_carry_request_context() {
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("http://127.0.0.1:1")
+        .build()
+        .expect("build client");
+
+    let error = client.get_identity().await.expect_err("auth should fail");
+    assert!(matches!(error, DiscogsError::AuthRequired { .. }));
+    let context = error.context().expect("auth error context");
+    assert_eq!(context.method, http::Method::GET);
+    assert_eq!(context.url, "http://127.0.0.1:1/oauth/identity");
+    assert_eq!(context.attempts, 0);
+
+    let error = client
+        .raw(http::Method::DELETE, "https://example.com/releases/1")
+        .send_empty()
+        .await
+        .expect_err("url outside the base url");
+    let context = error.context().expect("build error context");
+    assert_eq!(context.method, http::Method::DELETE);
+    assert_eq!(context.url, "https://example.com/releases/1");
+    assert_eq!(context.attempts, 0);
+}
+
+#[tokio::test]
+async fn transport_errors_count_every_attempt() {
+    let error = DiscogsClient::builder("test-agent")
+        .base_url("http://127.0.0.1:9")
+        .retry(RetryConfig {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            backoff_factor: 1.0,
+        })
+        .build()
+        .expect("build client")
+        .about()
+        .await
+        .expect_err("nothing listens on the discard port");
+
+    assert!(matches!(error, DiscogsError::Transport(_)));
+    assert_eq!(error.context().map(|context| context.attempts), Some(3));
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn cassette_replays_recorded_exchanges_without_network() {
+    use discogs_rs::{CassetteConfig, TransportErrorKind};
+
+    let app = Router::new().route(
+        "/",
+        get(|| async {
+            let mut response = ABOUT_JSON.into_response();
+            let headers = response.headers_mut();
+            headers.insert("x-discogs-ratelimit", HeaderValue::from_static("60"));
+            headers.insert("x-discogs-ratelimit-used", HeaderValue::from_static("18"));
+            headers.insert(
+                "x-discogs-ratelimit-remaining",
+                HeaderValue::from_static("42"),
+            );
+            response
+        }),
+    );
+    let base_url = spawn_server(app).await;
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("about.json");
+
+    let recorder = DiscogsClient::builder("test-agent")
+        .base_url(base_url.clone())
+        .user_token("recorded-token")
+        .cassette(CassetteConfig::record(&path))
+        .build()
+        .expect("build recording client");
+    recorder.about().await.expect("recorded about");
+    drop(recorder);
+    let cassette = std::fs::read_to_string(&path).expect("cassette written");
+    assert!(!cassette.contains("recorded-token"));
+
+    let replayer = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .user_token("another-token")
+        .transport(CannedTransport::new("{}"))
+        .cassette(CassetteConfig::replay(&path))
+        .build()
+        .expect("build replaying client");
+    let about = replayer.about().await.expect("replayed about");
+    assert_eq!(about.data.api_version, "v2");
+    assert_eq!(about.rate_limit.map(|limit| limit.remaining), Some(42));
+
+    let error = replayer
+        .user()
+        .get_identity()
+        .await
+        .expect_err("identity was never recorded");
+    let DiscogsError::Transport(error) = error else {
+        panic!("expected a transport error, got {error:?}");
+    };
+    assert_eq!(error.kind(), TransportErrorKind::InvalidRequest);
+    assert!(error.to_string().contains("/oauth/identity"), "{error}");
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test(start_paused = true)]
+async fn chaos_faults_are_retried_or_surface_as_errors() {
+    use discogs_rs::{ChaosConfig, Fault};
+
+    let transport = CannedTransport::new(ABOUT_JSON);
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("https://discogs.invalid")
+        .transport(Arc::clone(&transport))
+        .retry(RetryConfig {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            backoff_factor: 1.0,
+        })
+        .timeout(Duration::from_secs(5))
+        .chaos(ChaosConfig::scripted([
+            Some(Fault::RateLimited {
+                retry_after: Some(Duration::from_secs(2)),
+            }),
+            Some(Fault::ServerError(StatusCode::BAD_GATEWAY)),
+            Some(Fault::ConnectionReset),
+            None,
+            Some(Fault::MalformedJson),
+            Some(Fault::Timeout),
+        ]))
+        .build()
+        .expect("build client");
+
+    let started = tokio::time::Instant::now();
+    let about = client.about().await.expect("recovers after three faults");
+    assert_eq!(about.data.api_version, "v2");
+    assert!(started.elapsed() >= Duration::from_secs(2));
+    assert_eq!(transport.requests.lock().expect("requests lock").len(), 1);
+
+    let error = client.about().await.expect_err("malformed body");
+    assert!(matches!(error, DiscogsError::Json { .. }), "{error:?}");
+
+    let error = client
+        .with_retry_policy(NoRetry)
+        .about()
+        .await
+        .expect_err("injected timeout");
+    assert!(
+        matches!(&error, DiscogsError::Transport(error) if error.is_timeout()),
+        "{error:?}"
+    );
+    assert_eq!(transport.requests.lock().expect("requests lock").len(), 2);
+}
+
+#[tokio::test]
+async fn raw_requests_share_auth_and_errors_with_typed_endpoints() {
+    #[derive(Debug, serde::Deserialize)]
+    struct Echo {
+        authorization: String,
+        query: serde_json::Value,
+        body: serde_json::Value,
+    }
+
+    let app = Router::new().route(
+        "/users/tester/badges",
+        post(
+            |headers: http::HeaderMap,
+             Query(query): Query<serde_json::Value>,
+             Json(body): Json<serde_json::Value>| async move {
+                let authorization = headers[AUTHORIZATION].to_str().unwrap().to_string();
+                Json(json!({ "authorization": authorization, "query": query, "body": body }))
+            },
+        ),
+    );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url.clone())
+        .user_token("test-token")
+        .build()
+        .expect("build client");
+
+    let echo = client
+        .raw(http::Method::POST, "/users/tester/badges")
+        .param("page", 2)
+        .query(&json!({ "sort": "added" }))
+        .json(&json!({ "badge": "collector" }))
+        .auth(AuthLevel::User)
+        .send::<Echo>()
+        .await
+        .expect("typed raw response")
+        .data;
+    assert_eq!(echo.authorization, "Discogs token=test-token");
+    assert_eq!(echo.query, json!({ "page": "2", "sort": "added" }));
+    assert_eq!(echo.body, json!({ "badge": "collector" }));
+
+    let missing = client
+        .raw(http::Method::GET, "/users/tester/unknown")
+        .send_value()
+        .await
+        .expect_err("unknown path");
+    assert!(matches!(missing, DiscogsError::NotFound(_)));
+
+    let anonymous = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .build()
+        .expect("build client");
+    let error = anonymous
+        .raw(http::Method::POST, "/users/tester/badges")
+        .auth(AuthLevel::User)
+        .send_empty()
+        .await
+        .expect_err("auth should fail");
+    assert!(matches!(
+        error,
+        DiscogsError::AuthRequired {
+            required: AuthLevel::User,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn typed_links_are_followed_within_the_base_url() {
+    fn base(headers: &http::HeaderMap) -> String {
+        format!("http://{}", headers["host"].to_str().unwrap())
+    }
+
+    let app = Router::new()
+        .route(
+            "/releases/1",
+            get(|headers: http::HeaderMap| async move {
+                Json(json!({
+                    "id": 1,
+                    "title": "Stockholm",
+                    "resource_url": format!("{}/releases/1", base(&headers)),
+                    "artists": [{
+                        "id": 1,
+                        "name": "The Persuader",
+                        "resource_url": format!("{}/artists/1", base(&headers))
+                    }]
+                }))
+            }),
+        )
+        .route(
+            "/artists/1",
+            get(|headers: http::HeaderMap| async move {
+                Json(json!({
+                    "id": 1,
+                    "name": "The Persuader",
+                    "resource_url": format!("{}/artists/1", base(&headers))
+                }))
+            }),
+        )
+        .route(
+            "/users/tester/wants",
+            get(
+                |headers: http::HeaderMap,
+                 Query(params): Query<std::collections::HashMap<String, String>>| async move {
+                    let page = params["page"].parse::<u32>().unwrap();
+                    let mut body = wants_page(page, 2);
+                    if page < 2 {
+                        body["pagination"]["urls"]["next"] = json!(format!(
+                            "{}/users/tester/wants?page={}&per_page=2",
+                            base(&headers),
+                            page + 1
+                        ));
+                    }
+                    Json(body)
+                },
+            ),
+        );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .build()
+        .expect("build client");
+
+    let release = client
+        .database()
+        .get_release(1, None)
+        .await
+        .expect("release")
+        .data;
+    let credit = &release.artists.as_ref().expect("artists")[0];
+    let artist = client
+        .follow(&credit.resource_url)
+        .await
+        .expect("followed artist");
+    assert_eq!(artist.data.name, "The Persuader");
+
+    let first = client
+        .wantlist()
+        .get_releases(
+            "tester",
+            Some(&discogs_rs::PaginationParams {
+                page: Some(1),
+                per_page: Some(2),
+            }),
+        )
+        .await
+        .expect("first page");
+    let second = client
+        .next_page(&first)
+        .await
+        .expect("second page")
+        .expect("a second page");
+    assert_eq!(second.data.pagination.page, 2);
+    assert_eq!(
+        second.meta.endpoint.map(|endpoint| endpoint.name),
+        Some("wantlist.get_releases")
+    );
+    assert!(
+        client
+            .next_page(&second)
+            .await
+            .expect("no third page")
+            .is_none()
+    );
+
+    let mut private = first.clone();
+    private.meta.required_auth = AuthLevel::User;
+    let error = client
+        .next_page(&private)
+        .await
+        .expect_err("next page needs the original auth level");
+    assert!(matches!(
+        error,
+        DiscogsError::AuthRequired {
+            required: AuthLevel::User,
+            ..
+        }
+    ));
+
+    let foreign = discogs_rs::Link::<discogs_rs::Artist>::new("https://example.com/artists/1");
+    let error = client.follow(&foreign).await.expect_err("foreign host");
+    assert!(
+        matches!(&error, DiscogsError::Transport(error) if error.kind() == TransportErrorKind::InvalidRequest),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn successful_responses_carry_status_headers_url_and_attempts() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = flaky_app(1, Arc::clone(&hits)).route(
+        "/users/tester/lists",
+        post(|| async {
+            let mut response = (StatusCode::CREATED, Json(json!({ "id": 7 }))).into_response();
+            response
+                .headers_mut()
+                .insert("location", HeaderValue::from_static("/lists/7"));
+            response
+        }),
+    );
+    let base_url = spawn_server(app).await;
+    let client = retrying_client(base_url.clone());
+
+    let about = client.about().await.expect("about response");
+    assert_eq!(about.meta.status, StatusCode::OK);
+    assert_eq!(about.meta.method, http::Method::GET);
+    assert_eq!(about.meta.url, format!("{base_url}/"));
+    assert_eq!(about.meta.attempts, 2);
+    assert!(about.meta.elapsed > Duration::ZERO);
+    assert_eq!(
+        about.meta.headers["content-type"],
+        HeaderValue::from_static("application/json")
+    );
+
+    let created = client
+        .raw(http::Method::POST, "/users/tester/lists")
+        .param("token", "secret-token")
+        .send_value()
+        .await
+        .expect("created response");
+    assert_eq!(created.meta.status, StatusCode::CREATED);
+    assert_eq!(created.meta.headers["location"], "/lists/7");
+    assert_eq!(
+        created.meta.url,
+        format!("{base_url}/users/tester/lists?token=REDACTED")
+    );
+    assert_eq!(created.meta.attempts, 1);
+}
+
+#[tokio::test]
+async fn request_options_override_a_single_call() {
+    let app = Router::new()
+        .route(
+            "/releases/1",
+            get(|headers: http::HeaderMap| async move {
+                let echo = |name: &str| {
+                    headers
+                        .get(name)
+                        .map(|value| value.to_str().unwrap().to_string())
+                };
+                Json(json!({
+                    "id": 1,
+                    "title": "Stockholm",
+                    "resource_url": "https://api.discogs.com/releases/1",
+                    "accept": echo("accept"),
+                    "authorization": echo("authorization"),
+                    "trace": echo("x-trace-id")
+                }))
+            }),
+        )
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                StatusCode::NO_CONTENT
+            }),
+        );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .user_token("client-token")
+        .timeout(Duration::from_secs(5))
+        .build()
+        .expect("build client");
+
+    let options = RequestOptions::new()
+        .output_format(OutputFormat::Html)
+        .header(
+            http::HeaderName::from_static("x-trace-id"),
+            HeaderValue::from_static("abc"),
+        )
+        .auth(Auth::UserToken {
+            token: "call-token".into(),
+        });
+    let release = client
+        .database()
+        .with_options(options)
+        .get_release(1, None)
+        .await
+        .expect("release with options")
+        .data;
+    assert_eq!(
+        release.extra["accept"],
+        "application/vnd.discogs.v2.html+json"
+    );
+    assert_eq!(release.extra["authorization"], "Discogs token=call-token");
+    assert_eq!(release.extra["trace"], "abc");
+
+    let release = client
+        .database()
+        .get_release(1, None)
+        .await
+        .expect("release without options")
+        .data;
+    assert_eq!(
+        release.extra["accept"],
+        "application/vnd.discogs.v2.discogs+json"
+    );
+    assert_eq!(release.extra["authorization"], "Discogs token=client-token");
+    assert_eq!(release.extra["trace"], serde_json::Value::Null);
+
+    let error = client
+        .with_options(
+            RequestOptions::new()
+                .timeout(Duration::from_millis(50))
+                .retry_policy(NoRetry),
+        )
+        .raw(http::Method::GET, "/slow")
+        .send_empty()
+        .await
+        .expect_err("per-call timeout");
+    assert!(
+        matches!(&error, DiscogsError::Transport(error) if error.kind() == TransportErrorKind::Timeout),
+        "{error:?}"
+    );
+
+    let error = client
+        .with_options(RequestOptions::new().auth(Auth::None))
+        .get_identity()
+        .await
+        .expect_err("auth override is checked");
+    assert!(matches!(error, DiscogsError::AuthRequired { .. }));
+}
+
+#[tokio::test]
+async fn owned_endpoint_handles_outlive_the_client_and_spawn() {
+    struct Catalog {
+        database: DatabaseApi<'static>,
+    }
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = flaky_app(0, Arc::clone(&hits)).route(
+        "/artists/{id}",
+        get(
+            |axum::extract::Path(id): axum::extract::Path<u64>| async move {
+                Json(json!({
+                    "id": id,
+                    "name": format!("Artist {id}"),
+                    "resource_url": format!("https://api.discogs.com/artists/{id}")
+                }))
+            },
+        ),
+    );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .build()
+        .expect("build client");
+
+    let catalog = Catalog {
+        database: client.database().into_owned(),
+    };
+    let about: ApiFuture<discogs_rs::AboutResponse> = client.about();
+    let raw = client
+        .raw(http::Method::GET, "/artists/6")
+        .with_options(RequestOptions::new().retry_policy(NoRetry))
+        .into_owned();
+    drop(client);
+
+    let tasks: Vec<_> = (1..=3)
+        .map(|id| tokio::spawn(catalog.database.get_artist(id)))
+        .collect();
+    for (id, task) in (1..=3).zip(tasks) {
+        let artist = task.await.expect("task").expect("artist");
+        assert_eq!(artist.data.name, format!("Artist {id}"));
+    }
+
+    let report = tokio::spawn(catalog.database.get_artists(vec![4, 5], 2))
+        .await
+        .expect("batch task");
+    assert_eq!(report.succeeded.len(), 2);
+
+    let about = tokio::spawn(about).await.expect("task").expect("about");
+    assert_eq!(about.data.api_version, "v2");
+
+    let artist = tokio::spawn(async move { raw.send_value().await })
+        .await
+        .expect("raw task")
+        .expect("raw artist");
+    assert_eq!(artist.data["name"], "Artist 6");
+}
+
+#[cfg(feature = "tracing")]
+#[tokio::test]
+async fn tracing_spans_name_the_endpoint_without_leaking_credentials() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().expect("capture lock").extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .with_writer({
+            let captured = captured.clone();
+            move || captured.clone()
+        })
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("https://discogs.invalid")
+        .user_token("super-secret-token")
+        .transport(CannedTransport::new(r#"{"id":1,"title":"t"}"#))
+        .build()
+        .expect("build client");
+    let _ = client.database().get_release(1, None).await;
+
+    let output =
+        String::from_utf8(captured.0.lock().expect("capture lock").clone()).expect("utf8 output");
+    assert!(output.contains("database.get_release"), "{output}");
+    assert!(output.contains("/releases/{release_id}"), "{output}");
+    assert!(output.contains("discogs.attempt"), "{output}");
+    assert!(!output.contains("super-secret-token"), "{output}");
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn metrics_are_keyed_by_endpoint_name() {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let base_url = spawn_server(flaky_app(1, Arc::clone(&hits))).await;
+    retrying_client(base_url)
+        .about()
+        .await
+        .expect("about response");
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let value = |name: &str, label: (&str, &str)| {
+        snapshot.iter().find_map(|(key, _, _, value)| {
+            let key = key.key();
+            let matches = key.name() == name
+                && key
+                    .labels()
+                    .any(|l| l.key() == label.0 && l.value() == label.1);
+            matches.then_some(value)
+        })
+    };
+
+    assert_eq!(
+        value("discogs_requests_total", ("status_class", "5xx")),
+        Some(&DebugValue::Counter(1))
+    );
+    assert_eq!(
+        value("discogs_requests_total", ("status_class", "2xx")),
+        Some(&DebugValue::Counter(1))
+    );
+    assert_eq!(
+        value("discogs_retries_total", ("endpoint", "about")),
+        Some(&DebugValue::Counter(1))
+    );
+    assert!(value("discogs_call_duration_seconds", ("endpoint", "about")).is_some());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_client_matches_async_behaviour() {
+    let transport = CannedTransport::new(ABOUT_JSON);
+    let client = DiscogsClient::builder("test-agent")
+        .base_url("https://discogs.invalid")
+        .transport(Arc::clone(&transport))
+        .build_blocking()
+        .expect("build blocking client");
+
+    let response = client.about().expect("about response");
+    assert_eq!(response.data.api_version, "v2");
+
+    let error = client
+        .user()
+        .get_identity()
+        .expect_err("identity requires user auth");
+    assert!(matches!(error, DiscogsError::AuthRequired { .. }));
+    assert_eq!(transport.requests.lock().expect("requests lock").len(), 1);
+}
+
+#[cfg(feature = "credentials")]
+#[tokio::test]
+async fn client_registry_isolates_rate_limits_per_profile() {
+    use discogs_rs::{ClientRegistry, TomlCredentialStore};
+
+    let app = Router::new().route(
+        "/",
+        get(|| async {
+            let mut response = ABOUT_JSON.into_response();
+            let headers = response.headers_mut();
+            headers.insert("x-discogs-ratelimit", HeaderValue::from_static("60"));
+            headers.insert("x-discogs-ratelimit-used", HeaderValue::from_static("10"));
+            headers.insert(
+                "x-discogs-ratelimit-remaining",
+                HeaderValue::from_static("50"),
+            );
+            response
+        }),
+    );
+    let base_url = spawn_server(app).await;
+    let store = TomlCredentialStore::from_toml_str(
+        r#"
+        [profiles.shop-a]
+        token = "token-a"
+
+        [profiles.shop-b]
+        token = "token-b"
+        "#,
+    )
+    .expect("parse profiles");
+    let registry = ClientRegistry::new("test-agent")
+        .configure(move |builder| builder.base_url(base_url.clone()));
+
+    assert_eq!(
+        registry.load(&store).expect("load profiles"),
+        ["shop-a", "shop-b"]
+    );
+    let shop_a = registry.get("shop-a").expect("shop-a client");
+    let shop_b = registry.get("shop-b").expect("shop-b client");
+    assert_eq!(shop_a.auth_level(), AuthLevel::User);
+
+    shop_a.about().await.expect("about");
+    assert_eq!(shop_a.rate_limit().map(|rate| rate.remaining), Some(50));
+    assert!(shop_b.rate_limit().is_none());
+    assert!(registry.get("shop-c").is_none());
+}
+
+#[tokio::test]
+async fn concurrent_identical_gets_are_coalesced() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new().route(
+        "/",
+        get({
+            let hits = Arc::clone(&hits);
+            move || {
+                let hits = Arc::clone(&hits);
+                async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    let mut response = ABOUT_JSON.into_response();
+                    let headers = response.headers_mut();
+                    headers.insert("x-discogs-ratelimit", HeaderValue::from_static("60"));
+                    headers.insert("x-discogs-ratelimit-used", HeaderValue::from_static("1"));
+                    headers.insert(
+                        "x-discogs-ratelimit-remaining",
+                        HeaderValue::from_static("59"),
+                    );
+                    response
+                }
+            }
+        }),
+    );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .coalesce_requests()
+        .build()
+        .expect("build client");
+
+    let responses = futures_util::future::join_all((0..5).map(|_| client.about())).await;
+
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    for response in responses {
+        let response = response.expect("about");
+        assert_eq!(response.data.api_version, "v2");
+        assert_eq!(response.rate_limit.map(|rate| rate.remaining), Some(59));
+    }
+
+    client.about().await.expect("about");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn batch_fetch_reports_successes_and_per_id_errors() {
+    let app = Router::new().route(
+        "/artists/{id}",
+        get(
+            |axum::extract::Path(id): axum::extract::Path<u64>| async move {
+                if id == 3 {
+                    return (
+                        StatusCode::NOT_FOUND,
+                        Json(json!({ "message": "Artist not found." })),
+                    );
+                }
+                (
+                    StatusCode::OK,
+                    Json(json!({
+                        "id": id,
+                        "name": format!("Artist {id}"),
+                        "resource_url": format!("https://api.discogs.com/artists/{id}")
+                    })),
+                )
+            },
+        ),
+    );
+    let base_url = spawn_server(app).await;
+    let client = DiscogsClient::builder("test-agent")
+        .base_url(base_url)
+        .retry(RetryConfig {
+            max_retries: 0,
+            ..RetryConfig::default()
+        })
+        .build()
+        .expect("build client");
+
+    let report = client.database().get_artists([1, 2, 3, 4], 2).await;
+
+    assert_eq!(
+        report
+            .succeeded
+            .iter()
+            .map(|(id, artist)| (*id, artist.data.name.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(1, "Artist 1"), (2, "Artist 2"), (4, "Artist 4")]
+    );
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, 3);
+    assert!(report.failed[0].1.is_not_found());
+    assert!(report.skipped.is_empty());
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn emulator_keeps_state_between_calls() {
+    use discogs_rs::testing::{Emulator, Fixtures};
+
+    let emulator = Emulator::start(Fixtures::sample())
+        .await
+        .expect("start emulator");
+    let alice = emulator
+        .client_builder("test-agent")
+        .user_token("alice-token")
+        .build()
+        .expect("build client");
+
+    let added = alice
+        .user()
+        .collection()
+        .add_release("alice", 249504, None)
+        .await
+        .expect("add release");
+    let releases = alice
+        .user()
+        .collection()
+        .get_releases("alice", 0, None)
+        .await
+        .expect("collection releases");
+    assert_eq!(releases.data.releases.len(), 1);
+    assert_eq!(
+        releases.data.releases[0].instance_id,
+        added.data.instance_id
+    );
+    assert_eq!(releases.data.pagination.items, 1);
+    let rate_limit = releases.rate_limit.expect("rate-limit headers");
+    assert_eq!((rate_limit.limit, rate_limit.used), (60, 2));
+
+    let error = alice
+        .user()
+        .collection()
+        .add_release("bob", 249504, None)
+        .await
+        .expect_err("other users' collections are read-only");
+    assert!(matches!(error, DiscogsError::Forbidden(_)));
+
+    let stranger = emulator
+        .client_builder("test-agent")
+        .user_token("unknown-token")
+        .build()
+        .expect("build client");
+    let error = stranger
+        .user()
+        .wantlist()
+        .get_releases("alice", None)
+        .await
+        .expect_err("unknown tokens are rejected");
+    assert!(matches!(error, DiscogsError::Unauthorized(_)));
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn emulator_returns_empty_pages_past_the_end() {
+    use discogs_rs::testing::{Emulator, Fixtures};
+
+    let emulator = Emulator::start(Fixtures::sample())
+        .await
+        .expect("start emulator");
+    let client = emulator
+        .client_builder("test-agent")
+        .user_token("alice-token")
+        .build()
+        .expect("build client");
+
+    let page = client
+        .raw(http::Method::GET, "/users/alice/wants")
+        .param("page", u32::MAX)
+        .param("per_page", 100)
+        .send_value()
+        .await
+        .expect("page past the end")
+        .data;
+    assert_eq!(page["wants"], json!([]));
+    assert_eq!(page["pagination"]["page"], u32::MAX);
 }